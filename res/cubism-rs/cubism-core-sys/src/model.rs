@@ -70,6 +70,7 @@ extern "C" {
     pub fn csmResetDrawableDynamicFlags(model: *mut csmModel);
     pub fn csmGetDrawableMultiplyColors(model: *mut csmModel) -> *mut *const csmVector4;
     pub fn csmGetDrawableScreenColors(model: *mut csmModel) -> *mut *const csmVector4;
+    pub fn csmGetDrawableParentPartIndices(model: *const csmModel) -> *const c_int;
 }
 
 #[test]
@@ -244,6 +244,18 @@ impl Model {
         }
     }
 
+    /// Returns the parts the drawables belong to.
+    /// If the value of a part is -1 it means the drawable belongs to none.
+    #[inline]
+    pub fn drawable_parent_parts(&self) -> &[i32] {
+        unsafe {
+            slice::from_raw_parts(
+                ffi::csmGetDrawableParentPartIndices(self.as_ptr()),
+                self.drawable_count(),
+            )
+        }
+    }
+
     /// Updates this model and finalizes its parameters and part opacities.
     /// This has to be called before accessing the drawables.
     #[inline]
@@ -19,26 +19,26 @@ pub struct Cdi3 {
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct Cdi3Parameter {
-    id: String,
+    pub id: String,
     #[serde(default)]
-    group_id: String,
-    name: String,
+    pub group_id: String,
+    pub name: String,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct Cdi3ParameterGroup {
-    id: String,
+    pub id: String,
     #[serde(default)]
-    group_id: String,
-    name: String,
+    pub group_id: String,
+    pub name: String,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct Cdi3Part {
-    id: String,
-    name: String,
+    pub id: String,
+    pub name: String,
 }
 
 impl Cdi3 {
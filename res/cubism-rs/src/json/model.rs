@@ -46,6 +46,7 @@ pub struct FileReferences {
     #[serde(default)]
     pub motions: Motions,
     pub user_data: Option<PathBuf>,
+    pub display_info: Option<PathBuf>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -90,6 +91,7 @@ pub type Motions = HashMap<String, Vec<Motion>>;
 #[serde(rename_all = "PascalCase")]
 pub struct Motion {
     pub file: PathBuf,
+    pub sound: Option<PathBuf>,
     #[serde(rename = "FadeInTime", default = "super::float_1")]
     pub fade_in_time: f32,
     #[serde(rename = "FadeOutTime", default = "super::float_1")]
@@ -114,13 +116,13 @@ pub struct HitArea {
 #[serde(rename_all = "PascalCase")]
 pub struct Layout {
     #[serde(rename = "CenterX")]
-    pub center_x: f32,
+    pub center_x: Option<f32>,
     #[serde(rename = "CenterY")]
-    pub center_y: f32,
-    pub x: f32,
-    pub y: f32,
-    pub width: f32,
-    pub height: f32,
+    pub center_y: Option<f32>,
+    pub x: Option<f32>,
+    pub y: Option<f32>,
+    pub width: Option<f32>,
+    pub height: Option<f32>,
 }
 
 #[test]
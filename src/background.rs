@@ -0,0 +1,546 @@
+use std::{
+    mem,
+    thread,
+    fs::{self, File},
+    io::BufReader,
+    error::Error,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, TryRecvError},
+};
+use image::{
+    RgbaImage,
+    ImageFormat,
+    AnimationDecoder,
+    codecs::{
+        gif::GifDecoder,
+        png::PngDecoder,
+        webp::WebPDecoder,
+    },
+};
+use crate::{
+    calc_aspect,
+    framework,
+    renderer::{Gl, Pass, Device, Renderer, Transform},
+    config::{
+        BgType,
+        BgConfig,
+        FitConfig,
+        GradientType,
+        GradientConfig,
+        SlideshowConfig,
+    },
+};
+
+// Seconds a frame is shown when the animation does not say
+const FRAME:    f32 = 0.1;
+const GRADIENT: u32 = 256;
+
+type Frames = Vec<(RgbaImage, f32)>;
+
+//  ____             _                                   _
+// | __ )  __ _  ___| | ____ _ _ __ ___  _   _ _ __   __| |
+// |  _ \ / _` |/ __| |/ / _` | '__/ _ \| | | | '_ \ / _` |
+// | |_) | (_| | (__|   < (_| | | | (_) | |_| | | | | (_| |
+// |____/ \__,_|\___|_|\_\__, |_|  \___/ \__,_|_| |_|\__,_|
+//                       |___/
+
+pub enum Background<D = Gl>
+where D: Device
+{
+    Color([f32; 4]),
+    Image(Picture<D>),
+    Slideshow(Slideshow<D>),
+    Gradient(Picture<D>, GradientType),
+}
+
+//  ____  _      _
+// |  _ \(_) ___| |_ _   _ _ __ ___
+// | |_) | |/ __| __| | | | '__/ _ \
+// |  __/| | (__| |_| |_| | | |  __/
+// |_|   |_|\___|\__|\__,_|_|  \___|
+
+// Image with frames of its own, or just the one
+pub struct Picture<D>
+where D: Device
+{
+    frames:  Vec<(D::Texture, f32)>,
+    mesh:    D::Mesh,
+    size:    [f32; 2],
+    length:  f32,
+    elapsed: f32,
+}
+
+//  ____  _ _     _           _
+// / ___|| (_) __| | ___  ___| |__   _____      __
+// \___ \| | |/ _` |/ _ \/ __| '_ \ / _ \ \ /\ / /
+//  ___) | | | (_| |  __/\__ \ | | | (_) \ V  V /
+// |____/|_|_|\__,_|\___||___/_| |_|\___/ \_/\_/
+
+pub struct Slideshow<D>
+where D: Device
+{
+    files:    Vec<PathBuf>,
+    next:     usize,
+    current:  Picture<D>,
+    // Picture on its way out, under the current one
+    previous: Option<Picture<D>>,
+    // Next picture, decoded while the current one is shown
+    coming:   Option<(PathBuf, Receiver<Result<Frames, String>>)>,
+    shown:    f32,
+    seconds:  f32,
+    fade:     f32,
+}
+
+//  ____             _                                   _
+// | __ )  __ _  ___| | ____ _ _ __ ___  _   _ _ __   __| |  _ _
+// |  _ \ / _` |/ __| |/ / _` | '__/ _ \| | | | '_ \ / _` | (_|_)
+// | |_) | (_| | (__|   < (_| | | | (_) | |_| | | | | (_| |  _ _
+// |____/ \__,_|\___|_|\_\__, |_|  \___/ \__,_|_| |_|\__,_| (_|_)
+//                       |___/
+
+impl<D> Background<D>
+where D: Device
+{
+
+    //  _ _   _ __   _____      __
+    // (_|_) | '_ \ / _ \ \ /\ / /
+    //  _ _  | | | |  __/\ V  V /
+    // (_|_) |_| |_|\___| \_/\_/
+
+    pub fn new(config: &BgConfig,
+               device: &D) -> Result<Self, Box<dyn Error>>
+    {
+        Ok(match config.variant {
+            BgType::Color     => Background::Color(config.color),
+            BgType::Image     => {
+                let path = expanduser::expanduser(&config.image)?;
+                Background::Image(Picture::open(&path,
+                                                device)?)
+            }
+            BgType::Slideshow => Background::Slideshow(
+                Slideshow::new(&config.slideshow,
+                               device)?),
+            BgType::Gradient  => Background::Gradient(
+                Picture::new(vec![(gradient(&config.gradient), 0.)],
+                             device)?,
+                config.gradient.variant),
+        })
+    }
+
+    //                        _       _
+    //  _ _   _   _ _ __   __| | __ _| |_ ___
+    // (_|_) | | | | '_ \ / _` |/ _` | __/ _ \
+    //  _ _  | |_| | |_) | (_| | (_| | ||  __/
+    // (_|_)  \__,_| .__/ \__,_|\__,_|\__\___|
+    //             |_|
+
+    // Device is there for the slideshow to load the next picture
+    pub fn update(&mut self,
+                  dt:     f64,
+                  device: &D)
+    {
+        match self {
+            Background::Image(p)     => p.update(dt as f32),
+            Background::Slideshow(s) => s.update(dt as f32,
+                                                 device),
+            _                        => {}
+        }
+    }
+
+    //            _
+    //  _ _    __| |_ __ __ ___      __
+    // (_|_)  / _` | '__/ _` \ \ /\ / /
+    //  _ _  | (_| | | | (_| |\ V  V /
+    // (_|_)  \__,_|_|  \__,_| \_/\_/
+
+    pub fn draw<R>(&self,
+                   renderer: &mut R,
+                   view:     [f32; 2],
+                   fit:      &FitConfig) -> Result<(), Box<dyn Error>>
+    where R: Renderer<Device = D> + ?Sized
+    {
+        if let Background::Color(c) = self {
+            renderer.clear([c[0] * c[3],
+                            c[1] * c[3],
+                            c[2] * c[3],
+                            c[3]]);
+            return Ok(())
+        }
+
+        renderer.clear([0.; 4]);
+
+        match self {
+            Background::Image(p)       => p.draw(renderer,
+                                                 view,
+                                                 fit,
+                                                 1.),
+            Background::Slideshow(s)   => s.draw(renderer,
+                                                 view,
+                                                 fit),
+            Background::Gradient(p, v) => {
+                // Gradient is not a picture of anything, so it goes edge to
+                // edge
+                let fit = match v {
+                    GradientType::Vertical => FitConfig::Fill,
+                    GradientType::Radial   => FitConfig::Cover,
+                };
+
+                p.draw(renderer,
+                       view,
+                       &fit,
+                       1.)
+            }
+            Background::Color(_)       => Ok(()),
+        }
+    }
+}
+
+//  ____  _      _
+// |  _ \(_) ___| |_ _   _ _ __ ___   _ _
+// | |_) | |/ __| __| | | | '__/ _ \ (_|_)
+// |  __/| | (__| |_| |_| | | |  __/  _ _
+// |_|   |_|\___|\__|\__,_|_|  \___| (_|_)
+
+impl<D> Picture<D>
+where D: Device
+{
+
+    //  _ _    ___  _ __   ___ _ __
+    // (_|_)  / _ \| '_ \ / _ \ '_ \
+    //  _ _  | (_) | |_) |  __/ | | |
+    // (_|_)  \___/| .__/ \___|_| |_|
+    //             |_|
+
+    fn open(path:   &Path,
+            device: &D) -> Result<Self, Box<dyn Error>>
+    {
+        Self::new(frames(path)?,
+                  device)
+    }
+
+    //  _ _   _ __   _____      __
+    // (_|_) | '_ \ / _ \ \ /\ / /
+    //  _ _  | | | |  __/\ V  V /
+    // (_|_) |_| |_|\___| \_/\_/
+
+    fn new(frames: Frames,
+           device: &D) -> Result<Self, Box<dyn Error>>
+    {
+        let (x, y) = {
+            let first = &frames.first().ok_or("Image has no frames")?.0;
+            (first.width() as f32,
+             first.height() as f32)
+        };
+        let size = [x, y];
+        let (rx, ry) = (x/y, 1.);
+
+        let positions = [[-rx, -ry], [-rx,  ry], [ rx, -ry], [ rx,  ry]];
+        let uvs       = [[ 0.,  0.], [ 0.,  1.], [ 1.,  0.], [ 1.,  1.]];
+        let indices   = [0, 1, 2, 2, 1, 3];
+
+        let mesh = device.mesh(&positions,
+                               &uvs,
+                               &indices)?;
+
+        let length = frames.iter().map(|(_, d)| d).sum();
+        let frames =
+            Result::from_iter(frames.into_iter()
+                              .map(|(f, d)| device.texture(f)
+                                            .map(|t| (t, d))))?;
+
+        Ok(Self {
+            frames,
+            mesh,
+            size,
+            length,
+            elapsed: 0.,
+        })
+    }
+
+    //                        _       _
+    //  _ _   _   _ _ __   __| | __ _| |_ ___
+    // (_|_) | | | | '_ \ / _` |/ _` | __/ _ \
+    //  _ _  | |_| | |_) | (_| | (_| | ||  __/
+    // (_|_)  \__,_| .__/ \__,_|\__,_|\__\___|
+    //             |_|
+
+    fn update(&mut self,
+              dt: f32)
+    {
+        if self.frames.len() > 1 && self.length > 0. {
+            self.elapsed = (self.elapsed + dt) % self.length;
+        }
+    }
+
+    //            _
+    //  _ _    __| |_ __ __ ___      __
+    // (_|_)  / _` | '__/ _` \ \ /\ / /
+    //  _ _  | (_| | | | (_| |\ V  V /
+    // (_|_)  \__,_|_|  \__,_| \_/\_/
+
+    fn draw<R>(&self,
+               renderer: &mut R,
+               view:     [f32; 2],
+               fit:      &FitConfig,
+               opacity:  f32) -> Result<(), Box<dyn Error>>
+    where R: Renderer<Device = D> + ?Sized
+    {
+        let mut t = self.elapsed;
+        let texture =
+            self.frames.iter()
+            .find(|(_, d)| {t -= d; t < 0.})
+            .or(self.frames.last())
+            .map(|(f, _)| f)
+            .ok_or("Image has no frames")?;
+
+        let aspect = calc_aspect(self.size,
+                                 view,
+                                 fit);
+
+        renderer.draw(&self.mesh,
+                      texture,
+                      &Pass {
+                          opacity,
+                          ..Pass::new(Transform::flat(aspect))
+                      })
+    }
+}
+
+//  ____  _ _     _           _
+// / ___|| (_) __| | ___  ___| |__   _____      __  _ _
+// \___ \| | |/ _` |/ _ \/ __| '_ \ / _ \ \ /\ / / (_|_)
+//  ___) | | | (_| |  __/\__ \ | | | (_) \ V  V /   _ _
+// |____/|_|_|\__,_|\___||___/_| |_|\___/ \_/\_/   (_|_)
+
+impl<D> Slideshow<D>
+where D: Device
+{
+
+    //  _ _   _ __   _____      __
+    // (_|_) | '_ \ / _ \ \ /\ / /
+    //  _ _  | | | |  __/\ V  V /
+    // (_|_) |_| |_|\___| \_/\_/
+
+    fn new(config: &SlideshowConfig,
+           device: &D) -> Result<Self, Box<dyn Error>>
+    {
+        if config.seconds <= 0. {
+            return Err("Slideshow seconds have to be above 0".into());
+        }
+
+        let dir = expanduser::expanduser(&config.dir)?;
+
+        let mut files: Vec<_> =
+            fs::read_dir(&dir)?
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.is_file() && ImageFormat::from_path(p).is_ok())
+            .collect();
+        files.sort();
+
+        if config.shuffle {
+            fastrand::shuffle(&mut files);
+        }
+
+        let first =
+            files.first()
+            .ok_or(format!("No images in {}", dir.display()))?;
+
+        let current = Picture::open(first,
+                                    device)?;
+
+        let mut slideshow = Self {
+            next:     1 % files.len(),
+            files,
+            current,
+            previous: None,
+            coming:   None,
+            shown:    0.,
+            seconds:  config.seconds,
+            fade:     config.fade,
+        };
+        slideshow.decode();
+
+        Ok(slideshow)
+    }
+
+    //            _                    _
+    //  _ _    __| | ___  ___ ___   __| | ___
+    // (_|_)  / _` |/ _ \/ __/ _ \ / _` |/ _ \
+    //  _ _  | (_| |  __/ (_| (_) | (_| |  __/
+    // (_|_)  \__,_|\___|\___\___/ \__,_|\___|
+
+    // On a thread of its own, not to hold up drawing
+    fn decode(&mut self)
+    {
+        if self.files.len() < 2 {return}
+
+        let file = self.files[self.next].clone();
+        self.next = (self.next + 1) % self.files.len();
+
+        let (tx, rx) = mpsc::channel();
+        let path = file.clone();
+        thread::spawn(move || {
+            tx.send(frames(&path).map_err(|e| e.to_string()))
+            .unwrap_or(())
+        });
+
+        self.coming = Some((file, rx));
+    }
+
+    //                        _       _
+    //  _ _   _   _ _ __   __| | __ _| |_ ___
+    // (_|_) | | | | '_ \ / _` |/ _` | __/ _ \
+    //  _ _  | |_| | |_) | (_| | (_| | ||  __/
+    // (_|_)  \__,_| .__/ \__,_|\__,_|\__\___|
+    //             |_|
+
+    // Picture that fails to load is skipped, not to stop the show, and one
+    // that is still being decoded is waited for
+    fn update(&mut self,
+              dt:     f32,
+              device: &D)
+    {
+        self.shown += dt;
+        self.current.update(dt);
+
+        if let Some(p) = &mut self.previous {
+            p.update(dt);
+        }
+        if self.shown >= self.fade {
+            self.previous = None;
+        }
+
+        if self.shown < self.seconds {return}
+
+        let (file, rx) = match self.coming.take() {
+            Some(c) => c,
+            None    => return,
+        };
+
+        let frames = match rx.try_recv() {
+            Ok(f)                           => f,
+            Err(TryRecvError::Empty)        => {
+                self.coming = Some((file, rx));
+                return
+            }
+            Err(TryRecvError::Disconnected) => Err("Decoding stopped"
+                                                   .to_string()),
+        };
+        self.shown = 0.;
+
+        let picture =
+            frames
+            .map_err(Box::from)
+            .and_then(|f| Picture::new(f,
+                                       device));
+
+        match picture {
+            Ok(p)  => {
+                let old = mem::replace(&mut self.current, p);
+                self.previous = Some(old);
+            }
+            Err(e) => eprintln!("Failed to load {}: {e}", file.display()),
+        }
+
+        self.decode();
+    }
+
+    //            _
+    //  _ _    __| |_ __ __ ___      __
+    // (_|_)  / _` | '__/ _` \ \ /\ / /
+    //  _ _  | (_| | | | (_| |\ V  V /
+    // (_|_)  \__,_|_|  \__,_| \_/\_/
+
+    fn draw<R>(&self,
+               renderer: &mut R,
+               view:     [f32; 2],
+               fit:      &FitConfig) -> Result<(), Box<dyn Error>>
+    where R: Renderer<Device = D> + ?Sized
+    {
+        let opacity = match &self.previous {
+            Some(p) => {
+                p.draw(renderer,
+                       view,
+                       fit,
+                       1.)?;
+                (self.shown / self.fade).min(1.)
+            }
+            None    => 1.,
+        };
+
+        self.current.draw(renderer,
+                          view,
+                          fit,
+                          opacity)
+    }
+}
+
+//  _____
+// |  ___| __ __ _ _ __ ___   ___  ___
+// | |_ | '__/ _` | '_ ` _ \ / _ \/ __|
+// |  _|| | | (_| | | | | | |  __/\__ \
+// |_|  |_|  \__,_|_| |_| |_|\___||___/
+
+// Animated GIF, APNG and WebP come apart into frames, anything else is still
+fn frames(path: &Path) -> Result<Frames, Box<dyn Error>>
+{
+    let still = || Ok(vec![(framework::image(path)?, 0.)]);
+    let file = || File::open(path).map(BufReader::new);
+
+    let frames = match ImageFormat::from_path(path) {
+        Ok(ImageFormat::Gif)  => GifDecoder::new(file()?)?.into_frames(),
+        Ok(ImageFormat::Png)  => {
+            let png = PngDecoder::new(file()?)?;
+            if !png.is_apng() {return still()}
+            png.apng().into_frames()
+        }
+        Ok(ImageFormat::WebP) => {
+            let webp = WebPDecoder::new(file()?)?;
+            if !webp.has_animation() {return still()}
+            webp.into_frames()
+        }
+        _                     => return still(),
+    };
+
+    frames
+    .map(|f| {
+        let f = f?;
+        let (num, den) = f.delay().numer_denom_ms();
+        let delay = match num as f32 / den as f32 / 1000. {
+            d if d > 0. => d,
+            _           => FRAME,
+        };
+
+        let mut image = f.into_buffer();
+        framework::premultiply(&mut image);
+        Ok((image, delay))
+    })
+    .collect()
+}
+
+//   ____               _ _            _
+//  / ___|_ __ __ _  __| (_) ___ _ __ | |_
+// | |  _| '__/ _` |/ _` | |/ _ \ '_ \| __|
+// | |_| | | | (_| | (_| | |  __/ | | | |_
+//  \____|_|  \__,_|\__,_|_|\___|_| |_|\__|
+
+// Baked into a texture, first color on top or in the middle
+fn gradient(config: &GradientConfig) -> RgbaImage
+{
+    let [from, to] = config.colors;
+    let n = GRADIENT as f32;
+
+    RgbaImage::from_fn(GRADIENT, GRADIENT, |x, y| {
+        let [x, y] = [x, y].map(|c| (c as f32 + 0.5) / n * 2. - 1.);
+        let t = match config.variant {
+            GradientType::Vertical => (y + 1.) / 2.,
+            GradientType::Radial   => (x * x + y * y).sqrt().min(1.),
+        };
+
+        let c: [f32; 4] = [0, 1, 2, 3].map(|i| from[i] * (1. - t) + to[i] * t);
+        let a = c[3];
+        image::Rgba([c[0] * a, c[1] * a, c[2] * a, a]
+                    .map(|v| (v.clamp(0., 1.) * 255.).round() as u8))
+    })
+}
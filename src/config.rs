@@ -1,14 +1,24 @@
-use std::error::Error;
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+    collections::HashMap,
+};
 
 use serde::{Serialize, Deserialize};
+use cubism::id::param;
+
+use crate::message::{Backdrop, Effect};
 
 pub mod constant {
     pub const APP_NAME: &str = "liver";
     pub const CONFIG:   &str = "config";
+    pub const TOKENS:   &str = "tokens";
+    pub const VIEW:     &str = "view";
 }
 use constant::*;
 
 mod cli;
+pub use cli::color;
 
 //   ____             __ _
 //  / ___|___  _ __  / _(_) __ _
@@ -19,8 +29,26 @@ mod cli;
 
 #[derive(Serialize, Deserialize)]
 pub struct Config {
-    pub window: WindowConfig,
-    pub model:  ModelConfig,
+    pub window:   WindowConfig,
+    pub model:    ModelConfig,
+    // Older configs have no library, scene, tracking, vts or lipsync
+    #[serde(default)]
+    pub library:  LibraryConfig,
+    #[serde(default)]
+    pub scene:    SceneConfig,
+    #[serde(default)]
+    pub tracking: TrackingConfig,
+    #[serde(default)]
+    pub vts:      VtsConfig,
+    #[serde(default)]
+    pub lipsync:  LipSyncConfig,
+    #[serde(skip)]
+    pub mode:     Mode,
+    #[serde(skip)]
+    pub backend:  Backend,
+    // Zoom and pan as the config file has them, window ones go over them
+    #[serde(skip)]
+    pub file:     View,
 }
 
 // __        ___           _                ____             __ _
@@ -32,12 +60,24 @@ pub struct Config {
 
 #[derive(Serialize, Deserialize)]
 pub struct WindowConfig {
-    pub size:  [u32; 2],
-    pub title: String,
-    pub fit:   FitConfig,
-    pub bg:    BgConfig,
+    pub size:    [u32; 2],
+    pub title:   String,
+    pub fit:     FitConfig,
+    // Older configs have no zoom, pan or effects
+    #[serde(default = "unit")]
+    pub scale:   f32,
+    #[serde(default)]
+    pub offset:  [f32; 2],
+    #[serde(default)]
+    pub anchor:  Anchor,
+    pub bg:      BgConfig,
+    #[serde(default)]
+    pub effects: EffectsConfig,
 }
 
+fn unit() -> f32 {1.}
+fn yes() -> bool {true}
+
 //  _____ _ _    ____             __ _
 // |  ___(_) |_ / ___|___  _ __  / _(_) __ _
 // | |_  | | __| |   / _ \| '_ \| |_| |/ _` |
@@ -49,6 +89,24 @@ pub struct WindowConfig {
 pub enum FitConfig {
     Contain,
     Cover,
+    Fill,
+    None,
+}
+
+//     _               _
+//    / \   _ __   ___| |__   ___  _ __
+//   / _ \ | '_ \ / __| '_ \ / _ \| '__|
+//  / ___ \| | | | (__| | | | (_) | |
+// /_/   \_\_| |_|\___|_| |_|\___/|_|
+
+#[derive(Serialize, Deserialize, Default)]
+pub enum Anchor {
+    #[default]
+    Center,
+    Top,
+    Bottom,
+    Left,
+    Right,
 }
 
 //  ____         ____             __ _
@@ -58,11 +116,16 @@ pub enum FitConfig {
 // |____/ \__, |\____\___/|_| |_|_| |_|\__, |
 //        |___/                        |___/
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
 pub struct BgConfig {
-    pub variant: BgType,
-    pub color:   [f32; 4],
-    pub image:   String,
+    pub variant:   BgType,
+    pub color:     [f32; 4],
+    pub image:     String,
+    // Older configs have no slideshow or gradient
+    #[serde(default)]
+    pub slideshow: SlideshowConfig,
+    #[serde(default)]
+    pub gradient:  GradientConfig,
 }
 
 //  ____       _____
@@ -72,10 +135,133 @@ pub struct BgConfig {
 // |____/ \__, ||_| \__, | .__/ \___|
 //        |___/     |___/|_|
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 pub enum BgType {
     Color,
     Image,
+    Slideshow,
+    Gradient,
+}
+
+//  ____  _ _     _           _                    ____             __ _
+// / ___|| (_) __| | ___  ___| |__   _____      __/ ___|___  _ __  / _(_) __ _
+// \___ \| | |/ _` |/ _ \/ __| '_ \ / _ \ \ /\ / / |   / _ \| '_ \| |_| |/ _` |
+//  ___) | | | (_| |  __/\__ \ | | | (_) \ V  V /| |__| (_) | | | |  _| | (_| |
+// |____/|_|_|\__,_|\___||___/_| |_|\___/ \_/\_/  \____\___/|_| |_|_| |_|\__, |
+//                                                                       |___/
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct SlideshowConfig {
+    pub dir:     String,
+    pub seconds: f32,
+    pub fade:    f32,
+    pub shuffle: bool,
+}
+
+//   ____               _ _            _    ____             __ _
+//  / ___|_ __ __ _  __| (_) ___ _ __ | |_ / ___|___  _ __  / _(_) __ _
+// | |  _| '__/ _` |/ _` | |/ _ \ '_ \| __| |   / _ \| '_ \| |_| |/ _` |
+// | |_| | | | (_| | (_| | |  __/ | | | |_| |__| (_) | | | |  _| | (_| |
+//  \____|_|  \__,_|\__,_|_|\___|_| |_|\__|\____\___/|_| |_|_| |_|\__, |
+//                                                                |___/
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct GradientConfig {
+    pub variant: GradientType,
+    pub colors:  [[f32; 4]; 2],
+}
+
+//   ____               _ _            _  _____
+//  / ___|_ __ __ _  __| (_) ___ _ __ | ||_   _|   _ _ __   ___
+// | |  _| '__/ _` |/ _` | |/ _ \ '_ \| __|| || | | | '_ \ / _ \
+// | |_| | | | (_| | (_| | |  __/ | | | |_ | || |_| | |_) |  __/
+//  \____|_|  \__,_|\__,_|_|\___|_| |_|\__||_| \__, | .__/ \___|
+//                                             |___/|_|
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum GradientType {
+    Vertical,
+    Radial,
+}
+
+//  _____  __  __           _        ____             __ _
+// | ____|/ _|/ _| ___  ___| |_ ___ / ___|___  _ __  / _(_) __ _
+// |  _| | |_| |_ / _ \/ __| __/ __| |   / _ \| '_ \| |_| |/ _` |
+// | |___|  _|  _|  __/ (__| |_\__ \ |__| (_) | | | |  _| | (_| |
+// |_____|_| |_|  \___|\___|\__|___/\____\___/|_| |_|_| |_|\__, |
+//                                                         |___/
+
+// Each effect is off unless it is there
+#[derive(Serialize, Deserialize, Default, Clone, PartialEq)]
+#[serde(default)]
+pub struct EffectsConfig {
+    pub outline: Option<OutlineConfig>,
+    pub shadow:  Option<ShadowConfig>,
+    pub glow:    Option<GlowConfig>,
+    pub grade:   Option<GradeConfig>,
+}
+
+//   ___        _   _ _             ____             __ _
+//  / _ \ _   _| |_| (_)_ __   ___ / ___|___  _ __  / _(_) __ _
+// | | | | | | | __| | | '_ \ / _ \ |   / _ \| '_ \| |_| |/ _` |
+// | |_| | |_| | |_| | | | | |  __/ |__| (_) | | | |  _| | (_| |
+//  \___/ \__,_|\__|_|_|_| |_|\___|\____\___/|_| |_|_| |_|\__, |
+//                                                        |___/
+
+// Width is in pixels
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct OutlineConfig {
+    pub width: f32,
+    pub color: [f32; 4],
+}
+
+//  ____  _               _                ____             __ _
+// / ___|| |__   __ _  __| | _____      __/ ___|___  _ __  / _(_) __ _
+// \___ \| '_ \ / _` |/ _` |/ _ \ \ /\ / / |   / _ \| '_ \| |_| |/ _` |
+//  ___) | | | | (_| | (_| | (_) \ V  V /| |__| (_) | | | |  _| | (_| |
+// |____/|_| |_|\__,_|\__,_|\___/ \_/\_/  \____\___/|_| |_|_| |_|\__, |
+//                                                               |___/
+
+// Offset and blur are in pixels, y goes down
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct ShadowConfig {
+    pub offset: [f32; 2],
+    pub blur:   f32,
+    pub color:  [f32; 4],
+}
+
+//   ____ _                ____             __ _
+//  / ___| | _____      __/ ___|___  _ __  / _(_) __ _
+// | |  _| |/ _ \ \ /\ / / |   / _ \| '_ \| |_| |/ _` |
+// | |_| | | (_) \ V  V /| |__| (_) | | | |  _| | (_| |
+//  \____|_|\___/ \_/\_/  \____\___/|_| |_|_| |_|\__, |
+//                                               |___/
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct GlowConfig {
+    pub radius:   f32,
+    pub strength: f32,
+}
+
+//   ____               _       ____             __ _
+//  / ___|_ __ __ _  __| | ___ / ___|___  _ __  / _(_) __ _
+// | |  _| '__/ _` |/ _` |/ _ \ |   / _ \| '_ \| |_| |/ _` |
+// | |_| | | | (_| | (_| |  __/ |__| (_) | | | |  _| | (_| |
+//  \____|_|  \__,_|\__,_|\___|\____\___/|_| |_|_| |_|\__, |
+//                                                    |___/
+
+// Hue is in degrees, saturation and brightness are factors
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct GradeConfig {
+    pub hue:        f32,
+    pub saturation: f32,
+    pub brightness: f32,
 }
 
 //  __  __           _      _  ____             __ _
@@ -89,6 +275,51 @@ pub enum BgType {
 pub struct ModelConfig {
     pub file:    Option<String>,
     pub motions: MotionConfig,
+    #[serde(default = "yes")]
+    pub layout:  bool,
+    // Part or drawable to the colors it is drawn with
+    #[serde(default)]
+    pub colors:  HashMap<String, ColorConfig>,
+    // Part to its opacity, over whatever motions do with it
+    #[serde(default)]
+    pub parts:   HashMap<String, f32>,
+    #[serde(default)]
+    pub graph:   GraphConfig,
+    // Off unless there is a [model.blink]
+    #[serde(default)]
+    pub blink:   Option<BlinkConfig>,
+}
+
+//   ____      _             ____             __ _
+//  / ___|___ | | ___  _ __ / ___|___  _ __  / _(_) __ _
+// | |   / _ \| |/ _ \| '__| |   / _ \| '_ \| |_| |/ _` |
+// | |__| (_) | | (_) | |  | |__| (_) | | | |  _| | (_| |
+//  \____\___/|_|\___/|_|   \____\___/|_| |_|_| |_|\__, |
+//                                                 |___/
+
+// Model's own colors are used where there is nothing
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct ColorConfig {
+    pub multiply: Option<[f32; 4]>,
+    pub screen:   Option<[f32; 4]>,
+}
+
+//  ____  _ _       _     ____             __ _
+// | __ )| (_)_ __ | | __/ ___|___  _ __  / _(_) __ _
+// |  _ \| | | '_ \| |/ / |   / _ \| '_ \| |_| |/ _` |
+// | |_) | | | | | |   <| |__| (_) | | | |  _| | (_| |
+// |____/|_|_|_| |_|_|\_\\____\___/|_| |_|_| |_|\__, |
+//                                              |___/
+
+// Seconds between blinks and of each part of one
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct BlinkConfig {
+    pub interval: f32,
+    pub closing:  f32,
+    pub closed:   f32,
+    pub opening:  f32,
 }
 
 //  __  __       _   _              ____             __ _
@@ -104,6 +335,432 @@ pub struct MotionConfig {
     pub idle: Option<(String, String)>,
 }
 
+//   ____                 _      ____             __ _
+//  / ___|_ __ __ _ _ __ | |__  / ___|___  _ __  / _(_) __ _
+// | |  _| '__/ _` | '_ \| '_ \| |   / _ \| '_ \| |_| |/ _` |
+// | |_| | | | (_| | |_) | | | | |__| (_) | | | |  _| | (_| |
+//  \____|_|  \__,_| .__/|_| |_|\____\___/|_| |_|_| |_|\__, |
+//                 |_|                                 |___/
+
+// States of the model, each with motions to pick from, and what moves it from
+// one to another. Nothing of it is used without states
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct GraphConfig {
+    pub start:       String,
+    // Seconds motions fade into one another over
+    pub fade:        f32,
+    pub states:      HashMap<String, StateConfig>,
+    // First one that fits is taken
+    pub transitions: Vec<TransitionConfig>,
+}
+
+//  ____  _        _        ____             __ _
+// / ___|| |_ __ _| |_ ___ / ___|___  _ __  / _(_) __ _
+// \___ \| __/ _` | __/ _ \ |   / _ \| '_ \| |_| |/ _` |
+//  ___) | || (_| | ||  __/ |__| (_) | | | |  _| | (_| |
+// |____/ \__\__,_|\__\___|\____\___/|_| |_|_| |_|\__, |
+//                                                |___/
+
+#[derive(Serialize, Deserialize)]
+pub struct StateConfig {
+    pub motions: Vec<PoolConfig>,
+}
+
+//  ____             _  ____             __ _
+// |  _ \ ___   ___ | |/ ___|___  _ __  / _(_) __ _
+// | |_) / _ \ / _ \| | |   / _ \| '_ \| |_| |/ _` |
+// |  __/ (_) | (_) | | |__| (_) | | | |  _| | (_| |
+// |_|   \___/ \___/|_|\____\___/|_| |_|_| |_|\__, |
+//                                            |___/
+
+#[derive(Serialize, Deserialize)]
+pub struct PoolConfig {
+    pub motion: (String, String),
+    #[serde(default = "unit")]
+    pub weight: f32,
+}
+
+//  _____                    _ _   _              ____             __ _
+// |_   _| __ __ _ _ __  ___(_) |_(_) ___  _ __  / ___|___  _ __  / _(_) __ _
+//   | || '__/ _` | '_ \/ __| | __| |/ _ \| '_ \| |   / _ \| '_ \| |_| |/ _` |
+//   | || | | (_| | | | \__ \ | |_| | (_) | | | | |__| (_) | | | |  _| | (_| |
+//   |_||_|  \__,_|_| |_|___/_|\__|_|\___/|_| |_|\____\___/|_| |_|_| |_|\__, |
+//                                                                      |___/
+
+#[derive(Serialize, Deserialize)]
+pub struct TransitionConfig {
+    // From any state if there are none
+    #[serde(default)]
+    pub from: Vec<String>,
+    pub to:   String,
+    pub on:   EventConfig,
+    pub fade: Option<f32>,
+}
+
+//  _____                 _    ____             __ _
+// | ____|_   _____ _ __ | |_ / ___|___  _ __  / _(_) __ _
+// |  _| \ \ / / _ \ '_ \| __| |   / _ \| '_ \| |_| |/ _` |
+// | |___ \ V /  __/ | | | |_| |__| (_) | | | |  _| | (_| |
+// |_____| \_/ \___|_| |_|\__|\____\___/|_| |_|_| |_|\__, |
+//                                                   |___/
+
+// Timers and inactivity are in seconds, times of day are as in 22:00, and
+// end is for when the motion of the state ends
+#[derive(Serialize, Deserialize)]
+pub enum EventConfig {
+    Hit(String),
+    Trigger(String),
+    Timer(f32),
+    Inactive(f32),
+    Active,
+    Time([String; 2]),
+    End,
+}
+
+//  _     _ _                           ____             __ _
+// | |   (_) |__  _ __ __ _ _ __ _   _ / ___|___  _ __  / _(_) __ _
+// | |   | | '_ \| '__/ _` | '__| | | | |   / _ \| '_ \| |_| |/ _` |
+// | |___| | |_) | | | (_| | |  | |_| | |__| (_) | | | |  _| | (_| |
+// |_____|_|_.__/|_|  \__,_|_|   \__, |\____\___/|_| |_|_| |_|\__, |
+//                               |___/                        |___/
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct LibraryConfig {
+    pub paths:    Vec<String>,
+    pub rotation: RotationConfig,
+}
+
+//  ____       _        _   _              ____             __ _
+// |  _ \ ___ | |_ __ _| |_(_) ___  _ __  / ___|___  _ __  / _(_) __ _
+// | |_) / _ \| __/ _` | __| |/ _ \| '_ \| |   / _ \| '_ \| |_| |/ _` |
+// |  _ < (_) | || (_| | |_| | (_) | | | | |__| (_) | | | |  _| | (_| |
+// |_| \_\___/ \__\__,_|\__|_|\___/|_| |_|\____\___/|_| |_|_| |_|\__, |
+//                                                               |___/
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct RotationConfig {
+    pub variant: RotationType,
+    pub minutes: f64,
+    pub weights: HashMap<String, f64>,
+    pub exclude: Vec<String>,
+}
+
+//  ____       _        _   _           _____
+// |  _ \ ___ | |_ __ _| |_(_) ___  _ _|_   _|   _ _ __   ___
+// | |_) / _ \| __/ _` | __| |/ _ \| '_ \| || | | | '_ \ / _ \
+// |  _ < (_) | || (_| | |_| | (_) | | | | || |_| | |_) |  __/
+// |_| \_\___/ \__\__,_|\__|_|\___/|_| |_|_| \__, | .__/ \___|
+//                                           |___/|_|
+
+#[derive(Serialize, Deserialize, PartialEq, Eq)]
+pub enum RotationType {
+    Off,
+    Timer,
+    Lock,
+}
+
+//  ____                       ____             __ _
+// / ___|  ___ ___ _ __   ___ / ___|___  _ __  / _(_) __ _
+// \___ \ / __/ _ \ '_ \ / _ \ |   / _ \| '_ \| |_| |/ _` |
+//  ___) | (_|  __/ | | |  __/ |__| (_) | | | |  _| | (_| |
+// |____/ \___\___|_| |_|\___|\____\___/|_| |_|_| |_|\__, |
+//                                                   |___/
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct SceneConfig {
+    pub models: Vec<SceneModel>,
+}
+
+//  ____                      __  __           _      _
+// / ___|  ___ ___ _ __   ___|  \/  | ___   __| | ___| |
+// \___ \ / __/ _ \ '_ \ / _ \ |\/| |/ _ \ / _` |/ _ \ |
+//  ___) | (_|  __/ | | |  __/ |  | | (_) | (_| |  __/ |
+// |____/ \___\___|_| |_|\___|_|  |_|\___/ \__,_|\___|_|
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct SceneModel {
+    pub id:       String,
+    pub file:     String,
+    pub position: [f32; 2],
+    pub scale:    f32,
+    pub z:        i32,
+    pub open:     Vec<(String, String)>,
+    pub idle:     Option<(String, String)>,
+}
+
+//  _____               _    _              ____             __ _
+// |_   _| __ __ _  ___| | _(_)_ __   __ _ / ___|___  _ __  / _(_) __ _
+//   | || '__/ _` |/ __| |/ / | '_ \ / _` | |   / _ \| '_ \| |_| |/ _` |
+//   | || | | (_| | (__|   <| | | | | (_| | |__| (_) | | | |  _| | (_| |
+//   |_||_|  \__,_|\___|_|\_\_|_| |_|\__, |\____\___/|_| |_|_| |_|\__, |
+//                                   |___/                        |___/
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrackingConfig {
+    // Seconds without input before motions take over again
+    pub timeout:   f32,
+    // Seconds it takes to catch up with a tracked value
+    pub smoothing: f32,
+    // Tracked values the model is at rest with, and how far they go from it
+    pub neutral:   HashMap<String, f32>,
+    pub gain:      HashMap<String, f32>,
+    pub vmc:       VmcConfig,
+    pub osf:       OsfConfig,
+}
+
+// __     __               ____             __ _
+// \ \   / / __ ___   ___ / ___|___  _ __  / _(_) __ _
+//  \ \ / / '_ ` _ \ / __| |   / _ \| '_ \| |_| |/ _` |
+//   \ V /| | | | | | (__| |__| (_) | | | |  _| | (_| |
+//    \_/ |_| |_| |_|\___|\____\___/|_| |_|_| |_|\__, |
+//                                               |___/
+
+#[derive(Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct VmcConfig {
+    pub enabled: bool,
+    pub address: String,
+    // Blend shape, or bone and axis, to the parameter it moves
+    pub map:     HashMap<String, MapConfig>,
+}
+
+//   ___       __  ____             __ _
+//  / _ \ ___ / _|/ ___|___  _ __  / _(_) __ _
+// | | | / __| |_| |   / _ \| '_ \| |_| |/ _` |
+// | |_| \__ \  _| |__| (_) | | | |  _| | (_| |
+//  \___/|___/_|  \____\___/|_| |_|_| |_|\__, |
+//                                       |___/
+
+#[derive(Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct OsfConfig {
+    pub enabled: bool,
+    pub address: String,
+}
+
+//  __  __              ____             __ _
+// |  \/  | __ _ _ __  / ___|___  _ __  / _(_) __ _
+// | |\/| |/ _` | '_ \| |   / _ \| '_ \| |_| |/ _` |
+// | |  | | (_| | |_) | |__| (_) | | | |  _| | (_| |
+// |_|  |_|\__,_| .__/ \____\___/|_| |_|_| |_|\__, |
+//              |_|                           |___/
+
+// Parameter gets value * scale + offset
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct MapConfig {
+    pub param:  String,
+    #[serde(default = "unit")]
+    pub scale:  f32,
+    #[serde(default)]
+    pub offset: f32,
+}
+
+// __     ___        ____             __ _
+// \ \   / / |_ ___ / ___|___  _ __  / _(_) __ _
+//  \ \ / /| __/ __| |   / _ \| '_ \| |_| |/ _` |
+//   \ V / | |_\__ \ |__| (_) | | | |  _| | (_| |
+//    \_/   \__|___/\____\___/|_| |_|_| |_|\__, |
+//                                         |___/
+
+// Server for plugins written against the VTube Studio API
+#[derive(Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct VtsConfig {
+    pub enabled: bool,
+    pub address: String,
+    // Plugins let in without asking, by name
+    pub allow:   Vec<String>,
+}
+
+//  _____     _
+// |_   _|__ | | _____ _ __  ___
+//   | |/ _ \| |/ / _ \ '_ \/ __|
+//   | | (_) |   <  __/ | | \__ \
+//   |_|\___/|_|\_\___|_| |_|___/
+
+// Plugins that were let in, kept next to the config
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Tokens {
+    pub grants: Vec<Grant>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Grant {
+    pub plugin:    String,
+    pub developer: String,
+    pub token:     String,
+}
+
+// __     ___
+// \ \   / (_) _____      __
+//  \ \ / /| |/ _ \ \ /\ / /
+//   \ V / | |  __/\ V  V /
+//    \_/  |_|\___| \_/\_/
+
+// Zoom and pan from the window, kept next to the config and over it
+#[derive(Serialize, Deserialize, Default, PartialEq)]
+#[serde(default)]
+pub struct View {
+    pub scale:  Option<f32>,
+    pub offset: Option<[f32; 2]>,
+}
+
+//  _     _         ____                      ____             __ _
+// | |   (_)_ __   / ___| _   _ _ __   ___   / ___|___  _ __  / _(_) __ _
+// | |   | | '_ \  \___ \| | | | '_ \ / __| | |   / _ \| '_ \| |_| |/ _` |
+// | |___| | |_) |  ___) | |_| | | | | (__  | |__| (_) | | | |  _| | (_| |
+// |_____|_| .__/  |____/ \__, |_| |_|\___|  \____\___/|_| |_|_| |_|\__, |
+//         |_|            |___/                                     |___/
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct LipSyncConfig {
+    // Sounds of motions are played in a window, and only if built with audio
+    pub audio:     bool,
+    pub volume:    f32,
+    // How far the mouth opens for a loud sound
+    pub gain:      f32,
+    // Seconds it takes the mouth to catch up with the voice
+    pub smoothing: f32,
+    // Raw PCM voice from a FIFO or a file, or - for standard input
+    pub pipe:      Option<String>,
+    pub pcm:       PcmConfig,
+}
+
+//  ____                   ____             __ _
+// |  _ \ ___ _ __ ___    / ___|___  _ __  / _(_) __ _
+// | |_) / __| '_ ` _ \  | |   / _ \| '_ \| |_| |/ _` |
+// |  __/ (__| | | | | | | |__| (_) | | | |  _| | (_| |
+// |_|   \___|_| |_| |_|  \____\___/|_| |_|_| |_|\__, |
+//                                               |___/
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct PcmConfig {
+    pub rate:     u32,
+    pub channels: u16,
+    pub format:   PcmFormat,
+}
+
+//  ____                  _____                          _
+// |  _ \ ___ _ __ ___   |  ___|__  _ __ _ __ ___   __ _| |_
+// | |_) / __| '_ ` _ \  | |_ / _ \| '__| '_ ` _ \ / _` | __|
+// |  __/ (__| | | | | | |  _| (_) | |  | | | | | | (_| | |_
+// |_|   \___|_| |_| |_| |_|  \___/|_|  |_| |_| |_|\__,_|\__|
+
+// Little endian, interleaved if there are several channels
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum PcmFormat {
+    S16le,
+    F32le,
+    U8,
+}
+
+//  __  __           _
+// |  \/  | ___   __| | ___
+// | |\/| |/ _ \ / _` |/ _ \
+// | |  | | (_) | (_| |  __/
+// |_|  |_|\___/ \__,_|\___|
+
+#[derive(Default)]
+pub enum Mode {
+    #[default]
+    Window,
+    Render(RenderConfig),
+    Export(ExportConfig),
+    Pipe(PipeConfig),
+}
+
+//  ____                _            ____             __ _
+// |  _ \ ___ _ __   __| | ___ _ __ / ___|___  _ __  / _(_) __ _
+// | |_) / _ \ '_ \ / _` |/ _ \ '__| |   / _ \| '_ \| |_| |/ _` |
+// |  _ <  __/ | | | (_| |  __/ |  | |__| (_) | | | |  _| | (_| |
+// |_| \_\___|_| |_|\__,_|\___|_|   \____\___/|_| |_|_| |_|\__, |
+//                                                         |___/
+
+#[derive(Default)]
+pub struct RenderConfig {
+    pub motion: Option<(String, String)>,
+    pub time:   f64,
+    pub output: Option<String>,
+    // Voice the mouth moves to, from the start
+    pub speak:  Option<String>,
+}
+
+//  _____                       _    ____             __ _
+// | ____|_  ___ __   ___  _ __| |_ / ___|___  _ __  / _(_) __ _
+// |  _| \ \/ / '_ \ / _ \| '__| __| |   / _ \| '_ \| |_| |/ _` |
+// | |___ >  <| |_) | (_) | |  | |_| |__| (_) | | | |  _| | (_| |
+// |_____/_/\_\ .__/ \___/|_|   \__|\____\___/|_| |_|_| |_|\__, |
+//            |_|                                          |___/
+
+#[derive(Default)]
+pub struct ExportConfig {
+    pub motions: Vec<(String, String)>,
+    pub format:  Option<ExportFormat>,
+    pub output:  Option<String>,
+}
+
+//  _____                       _   _____                          _
+// | ____|_  ___ __   ___  _ __| |_|  ___|__  _ __ _ __ ___   __ _| |_
+// |  _| \ \/ / '_ \ / _ \| '__| __| |_ / _ \| '__| '_ ` _ \ / _` | __|
+// | |___ >  <| |_) | (_) | |  | |_|  _| (_) | |  | | | | | | (_| | |_
+// |_____/_/\_\ .__/ \___/|_|   \__|_|  \___/|_|  |_| |_| |_|\__,_|\__|
+//            |_|
+
+#[derive(Clone, Copy)]
+pub enum ExportFormat {
+    Apng,
+    Gif,
+    WebP,
+    Sequence,
+}
+
+//  ____  _             ____             __ _
+// |  _ \(_)_ __   ___ / ___|___  _ __  / _(_) __ _
+// | |_) | | '_ \ / _ \ |   / _ \| '_ \| |_| |/ _` |
+// |  __/| | |_) |  __/ |__| (_) | | | |  _| | (_| |
+// |_|   |_| .__/ \___|\____\___/|_| |_|_| |_|\__, |
+//         |_|                                |___/
+
+pub struct PipeConfig {
+    pub format: PipeFormat,
+    pub fps:    f64,
+}
+
+//  ____  _            _____                          _
+// |  _ \(_)_ __   ___|  ___|__  _ __ _ __ ___   __ _| |_
+// | |_) | | '_ \ / _ \ |_ / _ \| '__| '_ ` _ \ / _` | __|
+// |  __/| | |_) |  __/  _| (_) | |  | | | | | | (_| | |_
+// |_|   |_| .__/ \___|_|  \___/|_|  |_| |_| |_|\__,_|\__|
+//         |_|
+
+#[derive(Clone, Copy)]
+pub enum PipeFormat {
+    Rgba,
+    Y4m,
+}
+
+//  ____             _                  _
+// | __ )  __ _  ___| | _____ _ __   __| |
+// |  _ \ / _` |/ __| |/ / _ \ '_ \ / _` |
+// | |_) | (_| | (__|   <  __/ | | | (_| |
+// |____/ \__,_|\___|_|\_\___|_| |_|\__,_|
+
+#[derive(Default, Clone, Copy)]
+pub enum Backend {
+    #[default]
+    Gl,
+    Cpu,
+}
+
 //   ____             __ _
 //  / ___|___  _ __  / _(_) __ _   _ _
 // | |   / _ \| '_ \| |_| |/ _` | (_|_)
@@ -112,35 +769,411 @@ pub struct MotionConfig {
 //                         |___/
 
 impl Config {
+    // Zoom and pan saved from the window go over the config on start only,
+    // later the window has its own
     pub fn new() -> Result<Config, Box<dyn Error>>
+    {
+        let mut config = Self::load()?;
+
+        let view: View = confy::load(APP_NAME, VIEW)?;
+        if let Some(s) = view.scale {config.window.scale = s}
+        if let Some(o) = view.offset {config.window.offset = o}
+
+        Ok(config)
+    }
+
+    pub fn load() -> Result<Config, Box<dyn Error>>
     {
         let mut config: Config = confy::load(APP_NAME, CONFIG)?;
+        config.file = View {
+            scale:  Some(config.window.scale),
+            offset: Some(config.window.offset),
+        };
+
         let _program = cli::cli_args(&mut config)?;
+
+        // Rotation would be due on every frame
+        if config.library.rotation.minutes <= 0. {
+            return Err("Rotation minutes have to be above 0".into());
+        }
+        // Voice would have no length, or never fill a frame
+        if config.lipsync.pcm.rate == 0 || config.lipsync.pcm.channels == 0 {
+            return Err("PCM rate and channels have to be above 0".into());
+        }
+
         Ok(config)
     }
+
+    pub fn path() -> Result<PathBuf, Box<dyn Error>>
+    {
+        Ok(confy::get_configuration_file_path(APP_NAME, CONFIG)?)
+    }
+
+    // Zoom and pan from the window go to a file of their own, leaving the
+    // config and its comments alone
+    pub fn store_view(&self) -> Result<(), Box<dyn Error>>
+    {
+        let view = View {
+            scale:  Some(self.window.scale),
+            offset: Some(self.window.offset),
+        };
+        Ok(confy::store(APP_NAME, VIEW, view)?)
+    }
+}
+
+impl Tokens {
+    pub fn load() -> Result<Self, Box<dyn Error>>
+    {
+        Ok(confy::load(APP_NAME, TOKENS)?)
+    }
+
+    pub fn store(&self) -> Result<(), Box<dyn Error>>
+    {
+        Ok(confy::store(APP_NAME, TOKENS, self)?)
+    }
+}
+
+impl BgConfig {
+    // Config that bleed asks for, on top of the current one
+    pub fn change(&self,
+                  backdrop: &Backdrop) -> Result<Self, Box<dyn Error>>
+    {
+        let mut new = self.clone();
+        let path = |p: &Path| {
+            p.to_str()
+            .map(String::from)
+            .ok_or("Path is not UTF-8")
+        };
+
+        match backdrop {
+            Backdrop::Color(c)       => {
+                new.variant = BgType::Color;
+                new.color = color(c)?;
+            }
+            Backdrop::Image(p)       => {
+                new.variant = BgType::Image;
+                new.image = path(p)?;
+            }
+            Backdrop::Slideshow(p)   => {
+                new.variant = BgType::Slideshow;
+                new.slideshow.dir = path(p)?;
+            }
+            Backdrop::Gradient(v, c) => {
+                new.variant = BgType::Gradient;
+                new.gradient.variant = match v.as_str() {
+                    "vertical" => GradientType::Vertical,
+                    "radial"   => GradientType::Radial,
+                    _          => return Err(format!("Unknown gradient \
+                                                      type: {v}").into()),
+                };
+                new.gradient.colors = [color(&c[0])?,
+                                       color(&c[1])?];
+            }
+        }
+
+        Ok(new)
+    }
+}
+
+impl EffectsConfig {
+    pub fn is_off(&self) -> bool
+    {
+        self.outline.is_none()
+        && self.shadow.is_none()
+        && self.glow.is_none()
+        && self.grade.is_none()
+    }
+
+    // Config that bleed asks for, on top of the current one
+    pub fn change(&self,
+                  effect: &Effect) -> Result<Self, Box<dyn Error>>
+    {
+        let mut new = self.clone();
+
+        match effect {
+            Effect::Outline(w, c)    => {
+                new.outline = Some(OutlineConfig {
+                    width: *w,
+                    color: color(c)?,
+                });
+            }
+            Effect::Shadow(b, o, c)  => {
+                new.shadow = Some(ShadowConfig {
+                    offset: *o,
+                    blur:   *b,
+                    color:  color(c)?,
+                });
+            }
+            Effect::Glow(r, s)       => {
+                new.glow = Some(GlowConfig {
+                    radius:   *r,
+                    strength: *s,
+                });
+            }
+            Effect::Grade(h, s, b)   => {
+                new.grade = Some(GradeConfig {
+                    hue:        *h,
+                    saturation: *s,
+                    brightness: *b,
+                });
+            }
+            Effect::Off(e)           => match e.as_str() {
+                "outline" => new.outline = None,
+                "shadow"  => new.shadow = None,
+                "glow"    => new.glow = None,
+                "grade"   => new.grade = None,
+                "all"     => new = Self::default(),
+                _         => return Err(format!("Unknown effect: {e}").into()),
+            },
+        }
+
+        Ok(new)
+    }
 }
 
 impl Default for Config {
     fn default() -> Self
     {
         Self {
-            window: WindowConfig {
-                size:  [800, 600],
-                title: "Liver".to_string(),
-                fit:   FitConfig::Cover,
-                bg:    BgConfig {
-                    variant: BgType::Color,
-                    color:   [0., 0., 0., 0.],
-                    image:   "".to_string(),
+            window:   WindowConfig {
+                size:    [800, 600],
+                title:   "Liver".to_string(),
+                fit:     FitConfig::Cover,
+                scale:   1.,
+                offset:  [0., 0.],
+                anchor:  Anchor::Center,
+                bg:      BgConfig {
+                    variant:   BgType::Color,
+                    color:     [0., 0., 0., 0.],
+                    image:     "".to_string(),
+                    slideshow: SlideshowConfig::default(),
+                    gradient:  GradientConfig::default(),
                 },
+                effects: EffectsConfig::default(),
             },
-            model: ModelConfig {
+            model:    ModelConfig {
                 file:    None,
                 motions: MotionConfig {
                     open: Vec::new(),
                     idle: None,
                 },
+                layout:  true,
+                colors:  HashMap::new(),
+                parts:   HashMap::new(),
+                graph:   GraphConfig::default(),
+                blink:   None,
             },
+            library:  LibraryConfig::default(),
+            scene:    SceneConfig::default(),
+            tracking: TrackingConfig::default(),
+            vts:      VtsConfig::default(),
+            lipsync:  LipSyncConfig::default(),
+            mode:     Mode::Window,
+            backend:  Backend::Gl,
+            file:     View::default(),
+        }
+    }
+}
+
+impl Default for OutlineConfig {
+    fn default() -> Self
+    {
+        Self {
+            width: 2.,
+            color: [1., 1., 1., 1.],
+        }
+    }
+}
+
+impl Default for ShadowConfig {
+    fn default() -> Self
+    {
+        Self {
+            offset: [8., 8.],
+            blur:   8.,
+            color:  [0., 0., 0., 0.5],
+        }
+    }
+}
+
+impl Default for GlowConfig {
+    fn default() -> Self
+    {
+        Self {
+            radius:   8.,
+            strength: 0.5,
+        }
+    }
+}
+
+impl Default for GradeConfig {
+    fn default() -> Self
+    {
+        Self {
+            hue:        0.,
+            saturation: 1.,
+            brightness: 1.,
+        }
+    }
+}
+
+impl Default for SlideshowConfig {
+    fn default() -> Self
+    {
+        Self {
+            dir:     "".to_string(),
+            seconds: 60.,
+            fade:    1.,
+            shuffle: false,
+        }
+    }
+}
+
+impl Default for GradientConfig {
+    fn default() -> Self
+    {
+        Self {
+            variant: GradientType::Vertical,
+            colors:  [[0.2, 0.2, 0.3, 1.],
+                      [0.,  0.,  0.,  1.]],
+        }
+    }
+}
+
+impl Default for BlinkConfig {
+    fn default() -> Self
+    {
+        Self {
+            interval: 4.,
+            closing:  0.1,
+            closed:   0.05,
+            opening:  0.15,
+        }
+    }
+}
+
+impl Default for GraphConfig {
+    fn default() -> Self
+    {
+        Self {
+            start:       "idle".to_string(),
+            fade:        0.5,
+            states:      HashMap::new(),
+            transitions: Vec::new(),
+        }
+    }
+}
+
+impl Default for SceneModel {
+    fn default() -> Self
+    {
+        Self {
+            id:       "".to_string(),
+            file:     "".to_string(),
+            position: [0., 0.],
+            scale:    1.,
+            z:        0,
+            open:     Vec::new(),
+            idle:     None,
+        }
+    }
+}
+
+impl Default for TrackingConfig {
+    fn default() -> Self
+    {
+        Self {
+            timeout:   1.,
+            smoothing: 0.1,
+            neutral:   HashMap::new(),
+            gain:      HashMap::new(),
+            vmc:       VmcConfig::default(),
+            osf:       OsfConfig::default(),
+        }
+    }
+}
+
+// Blend shapes as VRM names them, head angles in degrees
+impl Default for VmcConfig {
+    fn default() -> Self
+    {
+        let map = |param: &str, scale, offset| MapConfig {
+            param: param.to_string(),
+            scale,
+            offset,
+        };
+
+        Self {
+            enabled: false,
+            address: "127.0.0.1:39539".to_string(),
+            map:     HashMap::from([
+                ("Head.x".to_string(),  map(param::ANGLE_Y,      -1., 0.)),
+                ("Head.y".to_string(),  map(param::ANGLE_X,       1., 0.)),
+                ("Head.z".to_string(),  map(param::ANGLE_Z,       1., 0.)),
+                ("Blink_L".to_string(), map(param::EYE_L_OPEN,   -1., 1.)),
+                ("Blink_R".to_string(), map(param::EYE_R_OPEN,   -1., 1.)),
+                ("A".to_string(),       map(param::MOUTH_OPEN_Y,  1., 0.)),
+                ("Joy".to_string(),     map(param::MOUTH_FORM,    1., 0.)),
+            ]),
+        }
+    }
+}
+
+impl Default for OsfConfig {
+    fn default() -> Self
+    {
+        Self {
+            enabled: false,
+            address: "127.0.0.1:11573".to_string(),
+        }
+    }
+}
+
+impl Default for VtsConfig {
+    fn default() -> Self
+    {
+        Self {
+            enabled: false,
+            address: "127.0.0.1:8001".to_string(),
+            allow:   Vec::new(),
+        }
+    }
+}
+
+impl Default for LipSyncConfig {
+    fn default() -> Self
+    {
+        Self {
+            audio:     false,
+            volume:    1.,
+            gain:      1.,
+            smoothing: 0.05,
+            pipe:      None,
+            pcm:       PcmConfig::default(),
+        }
+    }
+}
+
+// What speech synthesizers tend to put out
+impl Default for PcmConfig {
+    fn default() -> Self
+    {
+        Self {
+            rate:     16000,
+            channels: 1,
+            format:   PcmFormat::S16le,
+        }
+    }
+}
+
+impl Default for RotationConfig {
+    fn default() -> Self
+    {
+        Self {
+            variant: RotationType::Off,
+            minutes: 30.,
+            weights: HashMap::new(),
+            exclude: Vec::new(),
         }
     }
 }
@@ -5,7 +5,21 @@ use std::{
     fmt::{Display, Formatter, Error as FE},
 };
 
-use super::{Config, FitConfig, BgType, constant::APP_NAME};
+use super::{
+    Mode,
+    Config,
+    BgType,
+    GradientType,
+    Anchor,
+    FitConfig,
+    Backend,
+    PipeConfig,
+    PipeFormat,
+    ExportConfig,
+    ExportFormat,
+    RenderConfig,
+    constant::APP_NAME,
+};
 
 //   ____ _     ___      _    ____   ____ ____
 //  / ___| |   |_ _|    / \  |  _ \ / ___/ ___|
@@ -29,13 +43,19 @@ const CLI_ARGS: &[Cli] = &[
     Cli {
         name:  "-fit",
         help:  "Set model fit type",
-        usage: "{cover|contain}",
+        usage: "{cover|contain|fill|none}",
         act:   cli_fit,
     },
+    Cli {
+        name:  "-anchor",
+        help:  "Set which edge of the window model sticks to",
+        usage: "{center|top|bottom|left|right}",
+        act:   cli_anchor,
+    },
     Cli {
         name:  "-bg-variant",
         help:  "Set variant of background",
-        usage: "{color|image}",
+        usage: "{color|image|slideshow|gradient}",
         act:   cli_bg_variant,
     },
     Cli {
@@ -50,6 +70,18 @@ const CLI_ARGS: &[Cli] = &[
         usage: "<path>",
         act:   cli_bg_image_path,
     },
+    Cli {
+        name:  "-bg-slideshow",
+        help:  "Directory of background images to go through",
+        usage: "<path>",
+        act:   cli_bg_slideshow,
+    },
+    Cli {
+        name:  "-bg-gradient",
+        help:  "Set background gradient",
+        usage: "{vertical|radial} RRGGBB[AA] RRGGBB[AA]",
+        act:   cli_bg_gradient,
+    },
     Cli {
         name:  "-motions-open",
         help:  "IDs of model motions to play on launch",
@@ -62,6 +94,60 @@ const CLI_ARGS: &[Cli] = &[
         usage: "<[class:]motion>",
         act:   cli_motion_idle,
     },
+    Cli {
+        name:  "-motion",
+        help:  "ID of model motion to render, or motions to export",
+        usage: "<[class1:]motion1>,..",
+        act:   cli_motion,
+    },
+    Cli {
+        name:  "-time",
+        help:  "Time of the motion to render, in seconds",
+        usage: "<seconds>",
+        act:   cli_time,
+    },
+    Cli {
+        name:  "-speak",
+        help:  "Voice to move the mouth to while rendering",
+        usage: "<file>",
+        act:   cli_speak,
+    },
+    Cli {
+        name:  "-format",
+        help:  "Format of exported motions",
+        usage: "{apng|gif|webp|png}",
+        act:   cli_format,
+    },
+    Cli {
+        name:  "-o",
+        help:  "Path to the rendered image or exported motions",
+        usage: "<path>",
+        act:   cli_o,
+    },
+    Cli {
+        name:  "-output-pipe",
+        help:  "Write frames to stdout instead of a window",
+        usage: "{rgba|y4m}",
+        act:   cli_output_pipe,
+    },
+    Cli {
+        name:  "-fps",
+        help:  "Frame rate of the output pipe",
+        usage: "<fps>",
+        act:   cli_fps,
+    },
+    Cli {
+        name:  "-lipsync-pipe",
+        help:  "Raw PCM voice to move the mouth to, - for stdin",
+        usage: "<path>",
+        act:   cli_lipsync_pipe,
+    },
+    Cli {
+        name:  "-backend",
+        help:  "Draw render, export and pipe frames on GPU or CPU",
+        usage: "{gl|cpu}",
+        act:   cli_backend,
+    },
     Cli {
         name:  "-help",
         help:  "Show this help message and exit",
@@ -108,6 +194,12 @@ pub fn cli_args(toml: &mut Config) -> Result<String, Box<dyn Error>>
     let program = args.next().expect("program");
 
     while let Some(arg) = args.next() {
+        // Both -flag and --flag are fine
+        let arg = match arg.strip_prefix("--") {
+            Some(a) => format!("-{a}"),
+            None    => arg,
+        };
+
         match CLI_ARGS.iter().find(|cli| cli.name == arg) {
             Some(cli) => match (cli.act)(toml, &mut args) {
                 Ok(a)  => a,
@@ -120,6 +212,10 @@ pub fn cli_args(toml: &mut Config) -> Result<String, Box<dyn Error>>
             None => {
                 if arg.starts_with('-') {
                     eprintln!("Argument {arg} not supported");
+                } else if arg == "render" {
+                    toml.mode = Mode::Render(RenderConfig::default());
+                } else if arg == "export" {
+                    toml.mode = Mode::Export(ExportConfig::default());
                 } else {
                     toml.model.file = Some(arg);
                 }
@@ -143,6 +239,8 @@ fn cli_help(_: &mut Config,
     println!();
     println!("Usage:");
     println!("    {} [flags] [file.model3.json]", APP_NAME);
+    println!("    {} render [flags] [file.model3.json] -o <file.png>", APP_NAME);
+    println!("    {} export [flags] [file.model3.json] -o <file>", APP_NAME);
     println!();
     println!("Flags:");
     CLI_ARGS.iter()
@@ -210,12 +308,39 @@ fn cli_fit(c: &mut Config,
     c.window.fit = match fit.to_lowercase().as_str() {
         "cover"   => FitConfig::Cover,
         "contain" => FitConfig::Contain,
+        "fill"    => FitConfig::Fill,
+        "none"    => FitConfig::None,
         _         => return Err(format!("Unknown fit type: {fit}").into())
     };
 
     Ok(())
 }
 
+//                         _
+//  _ _    __ _ _ __   ___| |__   ___  _ __
+// (_|_)  / _` | '_ \ / __| '_ \ / _ \| '__|
+//  _ _  | (_| | | | | (__| | | | (_) | |
+// (_|_)  \__,_|_| |_|\___|_| |_|\___/|_|
+
+fn cli_anchor(c: &mut Config,
+              a: &mut Args) -> Result<(), Box<dyn Error>>
+{
+    let anchor =
+        a.next()
+        .ok_or("No anchor provided")?;
+
+    c.window.anchor = match anchor.to_lowercase().as_str() {
+        "center" => Anchor::Center,
+        "top"    => Anchor::Top,
+        "bottom" => Anchor::Bottom,
+        "left"   => Anchor::Left,
+        "right"  => Anchor::Right,
+        _        => return Err(format!("Unknown anchor: {anchor}").into())
+    };
+
+    Ok(())
+}
+
 //        _                             _             _
 //  _ _  | |__   __ _  __   ____ _ _ __(_) __ _ _ __ | |_
 // (_|_) | '_ \ / _` | \ \ / / _` | '__| |/ _` | '_ \| __|
@@ -231,9 +356,12 @@ fn cli_bg_variant(c: &mut Config,
         .ok_or("No background variant provided")?;
 
     c.window.bg.variant = match var.to_lowercase().as_str() {
-        "color" => BgType::Color,
-        "image" => BgType::Image,
-        _       => return Err(format!("Unknown background type: {var}").into())
+        "color"     => BgType::Color,
+        "image"     => BgType::Image,
+        "slideshow" => BgType::Slideshow,
+        "gradient"  => BgType::Gradient,
+        _           => return Err(format!("Unknown background type: \
+                                           {var}").into())
     };
 
     Ok(())
@@ -249,16 +377,30 @@ fn cli_bg_variant(c: &mut Config,
 fn cli_bg_color(c: &mut Config,
                 a: &mut Args) -> Result<(), Box<dyn Error>>
 {
-    let color =
+    let hex =
         a.next()
         .ok_or("No background color provided")?;
 
-    let color = color.to_uppercase();
+    c.window.bg.color = color(&hex)?;
+
+    Ok(())
+}
+
+//                  _
+//  _ _    ___ ___ | | ___  _ __
+// (_|_)  / __/ _ \| |/ _ \| '__|
+//  _ _  | (_| (_) | | (_) | |
+// (_|_)  \___\___/|_|\___/|_|
+
+// Also what bleed sends, so that colors are the same everywhere
+pub fn color(hex: &str) -> Result<[f32; 4], Box<dyn Error>>
+{
+    let color = hex.to_uppercase();
 
     let max =
         u16::from_str_radix("FF", 16)
         .expect("FF to be valid hex number");
-    match color.len() {
+    let rgba = match color.len() {
         6 => [u16::from_str_radix(&color[0..2], 16)?,
               u16::from_str_radix(&color[2..4], 16)?,
               u16::from_str_radix(&color[4..6], 16)?,
@@ -268,14 +410,9 @@ fn cli_bg_color(c: &mut Config,
               u16::from_str_radix(&color[4..6], 16)?,
               u16::from_str_radix(&color[6..8], 16)?],
         _ => return Err(format!("Incorrect color: {color}").into())
-    }.into_iter()
-    .map(|c| c as f32 / max as f32)
-    .enumerate()
-    .for_each(|(i, comp)| {
-        c.window.bg.color[i] = comp
-    });
+    };
 
-    Ok(())
+    Ok(rgba.map(|c| c as f32 / max as f32))
 }
 
 //        _             _                                          _   _
@@ -295,6 +432,54 @@ fn cli_bg_image_path(c: &mut Config,
     Ok(())
 }
 
+//        _                 _ _     _           _
+//  _ _  | |__   __ _   ___| (_) __| | ___  ___| |__   _____      __
+// (_|_) | '_ \ / _` | / __| | |/ _` |/ _ \/ __| '_ \ / _ \ \ /\ / /
+//  _ _  | |_) | (_| | \__ \ | | (_| |  __/\__ \ | | | (_) \ V  V /
+// (_|_) |_.__/ \__, | |___/_|_|\__,_|\___||___/_| |_|\___/ \_/\_/
+//              |___/
+
+fn cli_bg_slideshow(c: &mut Config,
+                    a: &mut Args) -> Result<(), Box<dyn Error>>
+{
+    c.window.bg.slideshow.dir =
+        a.next()
+        .ok_or("No slideshow directory provided")?;
+
+    Ok(())
+}
+
+//        _                                 _ _            _
+//  _ _  | |__   __ _    __ _ _ __ __ _  __| (_) ___ _ __ | |_
+// (_|_) | '_ \ / _` |  / _` | '__/ _` |/ _` | |/ _ \ '_ \| __|
+//  _ _  | |_) | (_| | | (_| | | | (_| | (_| | |  __/ | | | |_
+// (_|_) |_.__/ \__, |  \__, |_|  \__,_|\__,_|_|\___|_| |_|\__|
+//              |___/   |___/
+
+fn cli_bg_gradient(c: &mut Config,
+                   a: &mut Args) -> Result<(), Box<dyn Error>>
+{
+    let var =
+        a.next()
+        .ok_or("No gradient variant provided")?;
+
+    let gradient = &mut c.window.bg.gradient;
+    gradient.variant = match var.to_lowercase().as_str() {
+        "vertical" => GradientType::Vertical,
+        "radial"   => GradientType::Radial,
+        _          => return Err(format!("Unknown gradient type: {var}").into())
+    };
+
+    for i in 0..2 {
+        let hex =
+            a.next()
+            .ok_or("Gradient needs two colors")?;
+        gradient.colors[i] = color(&hex)?;
+    }
+
+    Ok(())
+}
+
 //                        _   _
 //  _ _   _ __ ___   ___ | |_(_) ___  _ __  ___    ___  _ __   ___ _ __
 // (_|_) | '_ ` _ \ / _ \| __| |/ _ \| '_ \/ __|  / _ \| '_ \ / _ \ '_ \
@@ -357,3 +542,233 @@ fn cli_motion_idle(c: &mut Config,
     Ok(())
 }
 
+//                        _   _
+//  _ _   _ __ ___   ___ | |_(_) ___  _ __
+// (_|_) | '_ ` _ \ / _ \| __| |/ _ \| '_ \
+//  _ _  | | | | | | (_) | |_| | (_) | | | |
+// (_|_) |_| |_| |_|\___/ \__|_|\___/|_| |_|
+
+fn cli_motion(c: &mut Config,
+              a: &mut Args) -> Result<(), Box<dyn Error>>
+{
+    let motions =
+        a.next()
+        .ok_or("No motion provided")?;
+
+    let mut motions: Vec<_> =
+        motions.split(',')
+        .map(|t| {
+            let mut s = t.rsplit(':');
+            let m =
+                s.next()
+                .expect("motion")
+                .to_string();
+            let c =
+                s.next()
+                .unwrap_or("")
+                .to_string();
+
+            (c, m)
+        })
+        .collect();
+
+    match &mut c.mode {
+        Mode::Render(r) => {
+            if motions.len() > 1 {
+                return Err("Only one motion can be rendered".into())
+            }
+            r.motion = motions.pop();
+        }
+        Mode::Export(e) => e.motions = motions,
+        _               => return Err("-motion is only used by render and \
+                                       export".into())
+    }
+
+    Ok(())
+}
+
+//        _   _
+//  _ _  | |_(_)_ __ ___   ___
+// (_|_) | __| | '_ ` _ \ / _ \
+//  _ _  | |_| | | | | | |  __/
+// (_|_)  \__|_|_| |_| |_|\___|
+
+fn cli_time(c: &mut Config,
+            a: &mut Args) -> Result<(), Box<dyn Error>>
+{
+    let time =
+        a.next()
+        .ok_or("No motion time provided")?;
+
+    match &mut c.mode {
+        Mode::Render(r) => r.time = time.parse()?,
+        _               => return Err("-time is only used by render".into())
+    }
+
+    Ok(())
+}
+
+//                             _
+//  _ _   ___ _ __   ___  __ _| | __
+// (_|_) / __| '_ \ / _ \/ _` | |/ /
+//  _ _  \__ \ |_) |  __/ (_| |   <
+// (_|_) |___/ .__/ \___|\__,_|_|\_\
+//           |_|
+
+fn cli_speak(c: &mut Config,
+             a: &mut Args) -> Result<(), Box<dyn Error>>
+{
+    let file =
+        a.next()
+        .ok_or("No voice file provided")?;
+
+    match &mut c.mode {
+        Mode::Render(r) => r.speak = Some(file),
+        _               => return Err("-speak is only used by render, bleed \
+                                       speak does it otherwise".into())
+    }
+
+    Ok(())
+}
+
+//         __                            _
+//  _ _   / _| ___  _ __ _ __ ___   __ _| |_
+// (_|_) | |_ / _ \| '__| '_ ` _ \ / _` | __|
+//  _ _  |  _| (_) | |  | | | | | | (_| | |_
+// (_|_) |_|  \___/|_|  |_| |_| |_|\__,_|\__|
+
+fn cli_format(c: &mut Config,
+              a: &mut Args) -> Result<(), Box<dyn Error>>
+{
+    let format =
+        a.next()
+        .ok_or("No export format provided")?;
+
+    let format = match format.to_lowercase().as_str() {
+        "apng" => ExportFormat::Apng,
+        "gif"  => ExportFormat::Gif,
+        "webp" => ExportFormat::WebP,
+        "png"  => ExportFormat::Sequence,
+        _      => return Err(format!("Unknown format: {format}").into())
+    };
+
+    match &mut c.mode {
+        Mode::Export(e) => e.format = Some(format),
+        _               => return Err("-format is only used by export".into())
+    }
+
+    Ok(())
+}
+
+//  _ _    ___
+// (_|_)  / _ \
+//  _ _  | (_) |
+// (_|_)  \___/
+
+fn cli_o(c: &mut Config,
+         a: &mut Args) -> Result<(), Box<dyn Error>>
+{
+    let output =
+        a.next()
+        .ok_or("No output path provided")?;
+
+    match &mut c.mode {
+        Mode::Render(r) => r.output = Some(output),
+        Mode::Export(e) => e.output = Some(output),
+        _               => return Err("-o is only used by render and \
+                                       export".into())
+    }
+
+    Ok(())
+}
+
+//                    _               _           _
+//  _ _    ___  _   _| |_ _ __  _   _| |_   _ __ (_)_ __   ___
+// (_|_)  / _ \| | | | __| '_ \| | | | __| | '_ \| | '_ \ / _ \
+//  _ _  | (_) | |_| | |_| |_) | |_| | |_  | |_) | | |_) |  __/
+// (_|_)  \___/ \__,_|\__| .__/ \__,_|\__| | .__/|_| .__/ \___|
+//                       |_|               |_|     |_|
+
+fn cli_output_pipe(c: &mut Config,
+                   a: &mut Args) -> Result<(), Box<dyn Error>>
+{
+    let format =
+        a.next()
+        .ok_or("No pipe format provided")?;
+
+    let format = match format.to_lowercase().as_str() {
+        "rgba" => PipeFormat::Rgba,
+        "y4m"  => PipeFormat::Y4m,
+        _      => return Err(format!("Unknown pipe format: {format}").into())
+    };
+
+    c.mode = Mode::Pipe(PipeConfig {
+        format,
+        fps: 30.,
+    });
+
+    Ok(())
+}
+
+//         __
+//  _ _   / _|_ __  ___
+// (_|_) | |_| '_ \/ __|
+//  _ _  |  _| |_) \__ \
+// (_|_) |_| | .__/|___/
+//           |_|
+
+fn cli_fps(c: &mut Config,
+           a: &mut Args) -> Result<(), Box<dyn Error>>
+{
+    let fps =
+        a.next()
+        .ok_or("No frame rate provided")?;
+
+    match &mut c.mode {
+        Mode::Pipe(p) => p.fps = fps.parse()?,
+        _             => return Err("-fps is only used with -output-pipe".into())
+    }
+
+    Ok(())
+}
+
+//        _ _                                    _
+//  _ _  | (_)_ __  ___ _   _ _ __   ___   _ __ (_)_ __   ___
+// (_|_) | | | '_ \/ __| | | | '_ \ / __| | '_ \| | '_ \ / _ \
+//  _ _  | | | |_) \__ \ |_| | | | | (__  | |_) | | |_) |  __/
+// (_|_) |_|_| .__/|___/\__, |_| |_|\___| | .__/|_| .__/ \___|
+//           |_|        |___/             |_|     |_|
+
+fn cli_lipsync_pipe(c: &mut Config,
+                    a: &mut Args) -> Result<(), Box<dyn Error>>
+{
+    let pipe =
+        a.next()
+        .ok_or("No lip sync pipe provided")?;
+
+    c.lipsync.pipe = Some(pipe);
+
+    Ok(())
+}
+
+//        _                _                  _
+//  _ _  | |__   __ _  ___| | _____ _ __   __| |
+// (_|_) | '_ \ / _` |/ __| |/ / _ \ '_ \ / _` |
+//  _ _  | |_) | (_| | (__|   <  __/ | | | (_| |
+// (_|_) |_.__/ \__,_|\___|_|\_\___|_| |_|\__,_|
+
+fn cli_backend(c: &mut Config,
+               a: &mut Args) -> Result<(), Box<dyn Error>>
+{
+    let backend =
+        a.next()
+        .ok_or("No backend provided")?;
+
+    c.backend = match backend.to_lowercase().as_str() {
+        "gl"  => Backend::Gl,
+        "cpu" => Backend::Cpu,
+        _     => return Err(format!("Unknown backend: {backend}").into())
+    };
+
+    Ok(())
+}
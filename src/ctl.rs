@@ -1,12 +1,30 @@
 use std::{
+    fs,
     env,
-    io::Write,
+    net::Shutdown,
+    io::{Read, Write},
     error::Error,
     os::unix::net::UnixStream,
 };
 
 mod message;
-use message::{Message, SOCKET_ADDR};
+use message::{
+    Message,
+    Load,
+    Shelf,
+    Reply,
+    Backdrop,
+    Effect,
+    Recolor,
+    Tint,
+    Outfit,
+    Puppet,
+    Ease,
+    Mood,
+    Cue,
+    Access,
+    SOCKET_ADDR,
+};
 
 fn main() -> Result<(), Box<dyn Error>>
 {
@@ -17,13 +35,307 @@ fn main() -> Result<(), Box<dyn Error>>
         UnixStream::connect(SOCKET_ADDR)
         .map_err(|e| format!("Failed to connect to socket: {e}"))?;
 
-    if let Some(arg) = args.next() {
-        match arg.as_str() {
-            "toggle" => write!(&mut stream, "{}", Message::Toggle)?,
-            "pause"  => write!(&mut stream, "{}", Message::Pause)?,
-            "play"   => write!(&mut stream, "{}", Message::Play)?,
-            "exit"   => write!(&mut stream, "{}", Message::Exit)?,
-            "set"    => {
+    let mut arg = args.next();
+
+    // Model of the scene to talk to
+    let target = match arg.as_deref() {
+        Some("-m") => {
+            let id =
+                args.next()
+                .ok_or("What model to talk to?".to_string())?;
+            arg = args.next();
+            Some(id)
+        }
+        _          => None,
+    };
+
+    if let Some(arg) = arg {
+        let message = match arg.as_str() {
+            "toggle"     => Message::Toggle,
+            "pause"      => Message::Pause,
+            "play"       => Message::Play,
+            "exit"       => Message::Exit,
+            "reload"     => Message::Reload,
+            "load"       => {
+                let file =
+                    args.next()
+                    .ok_or("What model to load?".to_string())?;
+
+                // Liver is running somewhere else
+                let file = fs::canonicalize(file)?;
+
+                let open = match args.next().as_deref() {
+                    Some("-open" | "--open") => {
+                        args.next()
+                        .ok_or("What motions to open with?".to_string())?
+                        .split(',')
+                        .map(|t| {
+                            let mut s = t.rsplit(':');
+                            let m = s.next().unwrap_or("").to_string();
+                            let c = s.next().unwrap_or("").to_string();
+                            (c, m)
+                        })
+                        .collect()
+                    }
+                    Some(a) => return Err(format!("Unexpected `{a}`").into()),
+                    None    => Vec::new(),
+                };
+
+                Message::Load(Load {
+                    file,
+                    open,
+                    reply: Reply::default(),
+                })
+            },
+            "library"    => {
+                let shelf = match args.next().as_deref() {
+                    Some("list")   => Shelf::List,
+                    Some("models") => Shelf::Models,
+                    Some("random") => Shelf::Random,
+                    Some("next")   => Shelf::Next,
+                    _              => return Err("Library can list, models, \
+                                                  random or next".into()),
+                };
+
+                Message::Library(shelf,
+                                 Reply::default())
+            },
+            "background" => {
+                let variant =
+                    args.next()
+                    .ok_or("What background to change to?".to_string())?;
+
+                let mut next = |what: &str| {
+                    args.next()
+                    .ok_or(format!("Background {variant} needs {what}"))
+                };
+
+                let backdrop = match variant.as_str() {
+                    "color"     => Backdrop::Color(next("a color")?),
+                    // Liver is running somewhere else
+                    "image"     => Backdrop::Image(
+                        fs::canonicalize(next("a file")?)?),
+                    "slideshow" => Backdrop::Slideshow(
+                        fs::canonicalize(next("a directory")?)?),
+                    "gradient"  => Backdrop::Gradient(
+                        next("a variant")?,
+                        [next("two colors")?,
+                         next("two colors")?]),
+                    _           => return Err(format!("Unknown background \
+                                                       `{variant}`").into()),
+                };
+
+                Message::Background(backdrop,
+                                    Reply::default())
+            },
+            "effects"    => {
+                let name =
+                    args.next()
+                    .ok_or("What effect to change?".to_string())?;
+
+                let mut next = |what: &str| {
+                    args.next()
+                    .ok_or(format!("Effect {name} needs {what}"))
+                };
+                let number = |n: String| {
+                    n.parse::<f32>()
+                    .map_err(|_| format!("Not a number: {n}"))
+                };
+
+                let effect = match name.as_str() {
+                    "outline" => Effect::Outline(
+                        number(next("a width")?)?,
+                        next("a color")?),
+                    "shadow"  => Effect::Shadow(
+                        number(next("a blur")?)?,
+                        [number(next("an offset")?)?,
+                         number(next("an offset")?)?],
+                        next("a color")?),
+                    "glow"    => Effect::Glow(
+                        number(next("a radius")?)?,
+                        number(next("a strength")?)?),
+                    "grade"   => Effect::Grade(
+                        number(next("a hue")?)?,
+                        number(next("a saturation")?)?,
+                        number(next("a brightness")?)?),
+                    "off"     => Effect::Off(
+                        args.next().unwrap_or("all".to_string())),
+                    _         => return Err(format!("Unknown effect \
+                                                     `{name}`").into()),
+                };
+
+                Message::Effects(effect,
+                                 Reply::default())
+            },
+            "color"      => {
+                let target =
+                    args.next()
+                    .ok_or("What part or drawable to recolor?".to_string())?;
+
+                let color = match args.next().as_deref() {
+                    Some("clear") => None,
+                    Some(c)       => Some(c.to_string()),
+                    None          => return Err("What color to use?".into()),
+                };
+
+                let tint = match args.next().as_deref() {
+                    Some("-screen" | "--screen")     => Tint::Screen,
+                    Some("-multiply" | "--multiply") => Tint::Multiply,
+                    Some(a) => return Err(format!("Unexpected `{a}`").into()),
+                    None    => Tint::Multiply,
+                };
+
+                Message::Color(Recolor {
+                    target,
+                    tint,
+                    color,
+                    reply: Reply::default(),
+                })
+            },
+            "part"       => {
+                let id =
+                    args.next()
+                    .ok_or("What part to change?".to_string())?;
+
+                let outfit = match (id.as_str(), args.next().as_deref()) {
+                    ("list", None)       => Outfit::List,
+                    (_, Some("show"))    => Outfit::Show(id),
+                    (_, Some("hide"))    => Outfit::Hide(id),
+                    (_, Some("reset"))   => Outfit::Reset(id),
+                    (_, Some("opacity")) => {
+                        let value =
+                            args.next()
+                            .ok_or("What opacity to set?".to_string())?;
+                        let value =
+                            value.parse::<f32>()
+                            .map_err(|_| format!("Not a number: {value}"))?;
+
+                        Outfit::Opacity(value, id)
+                    }
+                    _                    => return Err("Part can show, hide, \
+                                                        reset or set opacity"
+                                                       .into()),
+                };
+
+                Message::Part(outfit,
+                              Reply::default())
+            },
+            "param"      => {
+                let id =
+                    args.next()
+                    .ok_or("What parameter to drive?".to_string())?;
+
+                let mut puppet = Puppet {
+                    id,
+                    value: None,
+                    over:  0.,
+                    ease:  Ease::Linear,
+                    hold:  false,
+                    reply: Reply::default(),
+                };
+
+                let mut release = false;
+                while let Some(arg) = args.next() {
+                    let mut next = |what: &str| {
+                        args.next()
+                        .ok_or(format!("{arg} needs {what}"))
+                    };
+
+                    match arg.as_str() {
+                        "-over" | "--over"       => {
+                            puppet.over = seconds(&next("a time")?)?
+                        }
+                        "-ease" | "--ease"       => {
+                            let ease = next("an ease")?;
+                            puppet.ease =
+                                Ease::parse(&ease)
+                                .ok_or(format!("Unknown ease `{ease}`"))?;
+                        }
+                        "-hold" | "--hold"       => puppet.hold = true,
+                        "-release" | "--release" => release = true,
+                        v                        => {
+                            let value =
+                                v.parse::<f32>()
+                                .map_err(|_| format!("Unexpected `{v}`"))?;
+                            puppet.value = Some(value);
+                        }
+                    }
+                }
+
+                match (release, puppet.value) {
+                    (true, Some(_))  => return Err("Release takes no value"
+                                                   .into()),
+                    (false, None)    => return Err("What value to set?"
+                                                   .into()),
+                    _                => {}
+                }
+
+                Message::Param(puppet)
+            },
+            "expression" => {
+                let name =
+                    args.next()
+                    .ok_or("What expression to set?".to_string())?;
+
+                let mood = match (name.as_str(), args.next().as_deref()) {
+                    ("list", None)                    => Mood::List,
+                    ("clear", None)                   => Mood::Clear,
+                    (_, None)                         => Mood::Set(name),
+                    (_, Some("-toggle" | "--toggle")) => Mood::Toggle(name),
+                    (_, Some(a))                      => {
+                        return Err(format!("Unexpected `{a}`").into())
+                    }
+                };
+
+                Message::Expression(mood,
+                                    Reply::default())
+            },
+            "info"       => Message::Info(Reply::default()),
+            "calibrate"  => Message::Calibrate(Reply::default()),
+            "speak"      => {
+                let file =
+                    args.next()
+                    .ok_or("What to speak?".to_string())?;
+
+                // Liver is running somewhere else
+                Message::Speak(fs::canonicalize(file)?,
+                               Reply::default())
+            },
+            "state"      => {
+                let cue = match (args.next().as_deref(), args.next()) {
+                    (None, _)                       => Cue::Get,
+                    (Some("list"), None)            => Cue::List,
+                    (Some("set"), Some(n))          => Cue::Set(n),
+                    (Some("trigger"), Some(n))      => Cue::Trigger(n),
+                    (Some("set" | "trigger"), None) => {
+                        return Err("What state or trigger?".into())
+                    }
+                    (Some(a), _)                    => {
+                        return Err(format!("Unexpected `{a}`").into())
+                    }
+                };
+
+                Message::State(cue,
+                               Reply::default())
+            },
+            "vts"        => {
+                let access = match (args.next().as_deref(), args.next()) {
+                    (None, _)                      => Access::List,
+                    (Some("list"), None)           => Access::List,
+                    (Some("allow"), Some(p))       => Access::Allow(p),
+                    (Some("deny"), Some(p))        => Access::Deny(p),
+                    (Some("allow" | "deny"), None) => {
+                        return Err("What plugin?".into())
+                    }
+                    (Some(a), _)                   => {
+                        return Err(format!("Unexpected `{a}`").into())
+                    }
+                };
+
+                Message::Vts(access,
+                             Reply::default())
+            },
+            "set"        => {
                 let first =
                     args.next()
                     .ok_or("What motion to set?".to_string())?;
@@ -35,9 +347,9 @@ fn main() -> Result<(), Box<dyn Error>>
                                      first)
                 };
 
-                write!(&mut stream, "{}", Message::SetMotion(result))?;
+                Message::SetMotion(result)
             },
-            "queue"  => {
+            "queue"      => {
                 let first =
                     args.next()
                     .ok_or("What motion to set?".to_string())?;
@@ -49,13 +361,45 @@ fn main() -> Result<(), Box<dyn Error>>
                                      first)
                 };
 
-                write!(&mut stream, "{}", Message::QueueMotion(result))?;
+                Message::QueueMotion(result)
             },
-            "help"   => println!("{}", message::USAGE),
-            _        => eprintln!("Command `{}` is not recognised", arg)
-        }
+            "help"       => {println!("{}", message::USAGE); return Ok(())}
+            _            => {
+                eprintln!("Command `{}` is not recognised", arg);
+                return Ok(())
+            }
+        };
+
+        write!(&mut stream, "{}", message.to(target.as_deref()))?;
     } else {eprintln!("{}", message::USAGE)};
 
+    // Liver reads till the end, and then answers if there is anything to say
+    stream.shutdown(Shutdown::Write)?;
+
+    let mut reply = String::new();
+    stream.read_to_string(&mut reply)?;
+
+    if let Some(r) = reply.strip_prefix("ok:") {
+        print!("{r}");
+    } else if let Some(r) = reply.strip_prefix("error:") {
+        return Err(r.into())
+    }
+
     Ok(())
 }
 
+// Seconds, or milliseconds with ms after them
+fn seconds(time: &str) -> Result<f32, Box<dyn Error>>
+{
+    let (number, scale) = match time.strip_suffix("ms") {
+        Some(ms) => (ms, 1000.),
+        None     => (time.strip_suffix('s').unwrap_or(time), 1.),
+    };
+
+    let seconds =
+        number.parse::<f32>()
+        .map_err(|_| format!("Not a time: {time}"))?;
+
+    Ok(seconds / scale)
+}
+
@@ -0,0 +1,102 @@
+use std::error::Error;
+use crate::{
+    config::EffectsConfig,
+    renderer::{Gl, Pass, Post, Device, Renderer, Transform},
+};
+
+//  _____  __  __           _
+// | ____|/ _|/ _| ___  ___| |_ ___
+// |  _| | |_| |_ / _ \/ __| __/ __|
+// | |___|  _|  _|  __/ (__| |_\__ \
+// |_____|_| |_|  \___|\___|\__|___/
+
+// Model is drawn on a layer of its own, which goes over the background with
+// the effects on
+pub struct Effects<D = Gl>
+where D: Device
+{
+    device: D,
+    post:   Option<Post>,
+    quad:   D::Mesh,
+    layer:  Option<(D::Layer, [u32; 2])>,
+}
+
+impl<D> Effects<D>
+where D: Device + Clone
+{
+
+    //  _ _   _ __   _____      __
+    // (_|_) | '_ \ / _ \ \ /\ / /
+    //  _ _  | | | |  __/\ V  V /
+    // (_|_) |_| |_|\___| \_/\_/
+
+    pub fn new(config: &EffectsConfig,
+               device: &D) -> Result<Self, Box<dyn Error>>
+    {
+        let quad = device.mesh(&[[-1., -1.], [-1.,  1.], [ 1., -1.], [ 1.,  1.]],
+                               &[[ 0.,  0.], [ 0.,  1.], [ 1.,  0.], [ 1.,  1.]],
+                               &[0, 1, 2, 2, 1, 3])?;
+
+        let mut effects = Self {
+            device: device.clone(),
+            post:   None,
+            quad,
+            layer:  None,
+        };
+        effects.configure(config);
+
+        Ok(effects)
+    }
+
+    //                         __ _
+    //  _ _    ___ ___  _ __  / _(_) __ _ _   _ _ __ ___
+    // (_|_)  / __/ _ \| '_ \| |_| |/ _` | | | | '__/ _ \
+    //  _ _  | (_| (_) | | | |  _| | (_| | |_| | | |  __/
+    // (_|_)  \___\___/|_| |_|_| |_|\__, |\__,_|_|  \___|
+    //                              |___/
+
+    // Layer is kept, only the window size changes it
+    pub fn configure(&mut self,
+                     config: &EffectsConfig)
+    {
+        self.post = match config.is_off() {
+            true  => None,
+            false => Some(Post::new(config)),
+        };
+    }
+
+    //            _
+    //  _ _    __| |_ __ __ ___      __
+    // (_|_)  / _` | '__/ _` \ \ /\ / /
+    //  _ _  | (_| | | | (_| |\ V  V /
+    // (_|_)  \__,_|_|  \__,_| \_/\_/
+
+    // Whatever f draws gets the effects, with nothing on it is drawn as is
+    pub fn draw<F>(&mut self,
+                   renderer: &mut dyn Renderer<Device = D>,
+                   view:     [f32; 2],
+                   f:        F) -> Result<(), Box<dyn Error>>
+    where F: FnOnce(&mut dyn Renderer<Device = D>)
+                    -> Result<(), Box<dyn Error>>
+    {
+        let post = match &self.post {
+            Some(p) => p,
+            None    => return f(renderer),
+        };
+
+        let size = view.map(|v| (v as u32).max(1));
+        let layer = match self.layer.take() {
+            Some((l, s)) if s == size => l,
+            _                         => self.device.layer(size)?,
+        };
+        let (layer, _) = self.layer.insert((layer, size));
+
+        let texture = self.device.develop(layer,
+                                          post,
+                                          f)?;
+
+        renderer.draw(&self.quad,
+                      texture,
+                      &Pass::new(Transform::flat([1., 1.])))
+    }
+}
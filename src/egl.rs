@@ -0,0 +1,221 @@
+use std::{
+    ptr,
+    rc::Rc,
+    error::Error,
+    ffi::CString,
+    os::raw::{c_char, c_void},
+};
+use glium::{
+    SwapBuffersError as SwapErr,
+    debug::DebugCallbackBehavior,
+    backend::{Backend, Context},
+};
+use libloading::Library;
+
+type Display = *mut c_void;
+type Config = *mut c_void;
+type Handle = *mut c_void;
+
+const PLATFORM_SURFACELESS: u32 = 0x31dd;
+const OPENGL_API: u32 = 0x30a2;
+const SURFACE_TYPE: i32 = 0x3033;
+const PBUFFER_BIT: i32 = 0x0001;
+const RENDERABLE_TYPE: i32 = 0x3040;
+const OPENGL_BIT: i32 = 0x0008;
+const NONE: i32 = 0x3038;
+
+//                        __                _
+//  _ _   ___ _   _ _ __ / _| __ _  ___ ___| | ___  ___ ___
+// (_|_) / __| | | | '__| |_ / _` |/ __/ _ \ |/ _ \/ __/ __|
+//  _ _  \__ \ |_| | |  |  _| (_| | (_|  __/ |  __/\__ \__ \
+// (_|_) |___/\__,_|_|  |_|  \__,_|\___\___|_|\___||___/___/
+
+// EGL context on Mesa's surfaceless platform, made without winit, which
+// wants X11 or Wayland even when no window is ever opened
+pub fn surfaceless(size: [u32; 2]) -> Result<Rc<Context>, Box<dyn Error>>
+{
+    let backend = SurfacelessBack::new(size)?;
+    let context = unsafe {
+        Context::new(backend,
+                     false,
+                     DebugCallbackBehavior::Ignore)?
+    };
+
+    Ok(context)
+}
+
+//  _____      _
+// | ____|__ _| |
+// |  _| / _` | |
+// | |__| (_| | |
+// |_____\__, |_|
+//       |___/
+
+// Loaded at run time rather than linked, same as glutin does, so liver still
+// starts where there is no EGL at all
+struct Egl {
+    get_proc_address: unsafe extern "C" fn(*const c_char) -> *const c_void,
+    initialize:       unsafe extern "C" fn(Display, *mut i32, *mut i32) -> u32,
+    bind_api:         unsafe extern "C" fn(u32) -> u32,
+    choose_config:    unsafe extern "C" fn(Display, *const i32, *mut Config,
+                                           i32, *mut i32) -> u32,
+    create_context:   unsafe extern "C" fn(Display, Config, Handle,
+                                           *const i32) -> Handle,
+    destroy_context:  unsafe extern "C" fn(Display, Handle) -> u32,
+    make_current:     unsafe extern "C" fn(Display, Handle, Handle,
+                                           Handle) -> u32,
+    current_context:  unsafe extern "C" fn() -> Handle,
+    get_error:        unsafe extern "C" fn() -> i32,
+    _library:         Library,
+}
+
+impl Egl {
+    fn load() -> Result<Self, Box<dyn Error>>
+    {
+        unsafe {
+            let library = Library::new("libEGL.so.1")?;
+
+            Ok(Self {
+                get_proc_address: *library.get(b"eglGetProcAddress\0")?,
+                initialize:       *library.get(b"eglInitialize\0")?,
+                bind_api:         *library.get(b"eglBindAPI\0")?,
+                choose_config:    *library.get(b"eglChooseConfig\0")?,
+                create_context:   *library.get(b"eglCreateContext\0")?,
+                destroy_context:  *library.get(b"eglDestroyContext\0")?,
+                make_current:     *library.get(b"eglMakeCurrent\0")?,
+                current_context:  *library.get(b"eglGetCurrentContext\0")?,
+                get_error:        *library.get(b"eglGetError\0")?,
+                _library:         library,
+            })
+        }
+    }
+
+    fn fail(&self,
+            what: &str) -> Box<dyn Error>
+    {
+        let code = unsafe {(self.get_error)()};
+        format!("Failed to {what}, EGL error {code:#x}").into()
+    }
+}
+
+struct SurfacelessBack {
+    egl:     Egl,
+    display: Display,
+    context: Handle,
+    size:    [u32; 2],
+}
+
+impl SurfacelessBack {
+    fn new(size: [u32; 2]) -> Result<Self, Box<dyn Error>>
+    {
+        let egl = Egl::load()?;
+
+        unsafe {
+            let name = b"eglGetPlatformDisplayEXT\0";
+            let address = (egl.get_proc_address)(name.as_ptr().cast());
+            if address.is_null() {
+                return Err("No EGL platform displays".into())
+            }
+            let platform_display: unsafe extern "C" fn(u32,
+                                                       *mut c_void,
+                                                       *const i32) -> Display
+                = std::mem::transmute(address);
+
+            let display = platform_display(PLATFORM_SURFACELESS,
+                                           ptr::null_mut(),
+                                           ptr::null());
+            if display.is_null() {
+                return Err("No surfaceless EGL display".into())
+            }
+
+            let [mut major, mut minor] = [0, 0];
+            if (egl.initialize)(display, &mut major, &mut minor) == 0 {
+                return Err(egl.fail("initialize EGL"))
+            }
+            if (egl.bind_api)(OPENGL_API) == 0 {
+                return Err(egl.fail("bind OpenGL"))
+            }
+
+            // Surfaceless has no window configs, which are the default
+            let attributes = [SURFACE_TYPE,    PBUFFER_BIT,
+                              RENDERABLE_TYPE, OPENGL_BIT,
+                              NONE];
+            let mut config = ptr::null_mut();
+            let mut found = 0;
+            let chosen = (egl.choose_config)(display,
+                                             attributes.as_ptr(),
+                                             &mut config,
+                                             1,
+                                             &mut found);
+            if chosen == 0 || found == 0 {
+                return Err(egl.fail("find an OpenGL config"))
+            }
+
+            let context = (egl.create_context)(display,
+                                               config,
+                                               ptr::null_mut(),
+                                               [NONE].as_ptr());
+            if context.is_null() {
+                return Err(egl.fail("create EGL context"))
+            }
+
+            Ok(Self {
+                egl,
+                display,
+                context,
+                size,
+            })
+        }
+    }
+}
+
+unsafe impl Backend for SurfacelessBack {
+    // Nothing to swap, frames are read from textures
+    fn swap_buffers(&self) -> Result<(), SwapErr> {Ok(())}
+
+    unsafe fn get_proc_address(&self,
+                               symbol: &str) -> *const c_void
+    {
+        match CString::new(symbol) {
+            Ok(s)  => (self.egl.get_proc_address)(s.as_ptr()),
+            Err(_) => ptr::null(),
+        }
+    }
+
+    fn get_framebuffer_dimensions(&self) -> (u32, u32)
+    {
+        let [width, height] = self.size;
+        (width, height)
+    }
+
+    fn is_current(&self) -> bool
+    {
+        unsafe {(self.egl.current_context)() == self.context}
+    }
+
+    unsafe fn make_current(&self)
+    {
+        let made = (self.egl.make_current)(self.display,
+                                           ptr::null_mut(),
+                                           ptr::null_mut(),
+                                           self.context);
+        if made == 0 {eprintln!("{}", self.egl.fail("make EGL context current"))}
+    }
+}
+
+// Display stays initialized, other contexts may still be on it
+impl Drop for SurfacelessBack {
+    fn drop(&mut self)
+    {
+        unsafe {
+            if self.is_current() {
+                (self.egl.make_current)(self.display,
+                                        ptr::null_mut(),
+                                        ptr::null_mut(),
+                                        ptr::null_mut());
+            }
+            (self.egl.destroy_context)(self.display,
+                                       self.context);
+        }
+    }
+}
@@ -0,0 +1,317 @@
+use std::{
+    fs::{self, File},
+    path::Path,
+    error::Error,
+    io::{Write, Seek, SeekFrom, BufWriter},
+};
+use image::{
+    Delay,
+    Frame,
+    RgbaImage,
+    ColorType,
+    codecs::{
+        webp::WebPEncoder,
+        gif::{GifEncoder, Repeat},
+    },
+};
+use crate::{
+    renderer::Raster,
+    render::{Stage, Canvas, Offscreen},
+    config::{Config, Backend, ExportConfig, ExportFormat},
+};
+
+//                                   _
+//  _ _    _____  ___ __   ___  _ __| |_
+// (_|_)  / _ \ \/ / '_ \ / _ \| '__| __|
+//  _ _  |  __/>  <| |_) | (_) | |  | |_
+// (_|_)  \___/_/\_\ .__/ \___/|_|   \__|
+//                 |_|
+
+pub fn export(config: &Config,
+              export: &ExportConfig) -> Result<(), Box<dyn Error>>
+{
+    match config.backend {
+        Backend::Gl  => run::<Offscreen>(config,
+                                         export),
+        Backend::Cpu => run::<Raster>(config,
+                                      export),
+    }
+}
+
+//  _ _   _ __ _   _ _ __
+// (_|_) | '__| | | | '_ \
+//  _ _  | |  | |_| | | | |
+// (_|_) |_|   \__,_|_| |_|
+
+fn run<C>(config: &Config,
+          export: &ExportConfig) -> Result<(), Box<dyn Error>>
+where C: Canvas
+{
+    let output =
+        export.output.as_ref()
+        .ok_or("No output file provided")?;
+
+    let format = match export.format {
+        Some(f) => f,
+        None    => guess(output),
+    };
+
+    let mut stage = Stage::<C>::new(config)?;
+    stage.model.clear();
+
+    //                    _   _
+    //    _ __ ___   ___ | |_(_) ___  _ __  ___
+    //   | '_ ` _ \ / _ \| __| |/ _ \| '_ \/ __|
+    //  _| | | | | | (_) | |_| | (_) | | | \__ \
+    // (_)_| |_| |_|\___/ \__|_|\___/|_| |_|___/
+
+    let mut motions = export.motions.iter();
+    let (c, m) =
+        motions.next()
+        .ok_or("No motions to export")?;
+
+    let (fps, mut duration) =
+        stage.model
+        .meta((c, m))
+        .map(|meta| (meta.fps, meta.duration))
+        .ok_or(format!("No motion {m} in {c}"))?;
+    // Timestep would be endless
+    if fps <= 0. {
+        return Err(format!("Motion {m} in {c} has {fps} FPS").into());
+    }
+    stage.model.set((c, m));
+
+    for (c, m) in motions {
+        duration +=
+            stage.model
+            .meta((c, m))
+            .map(|meta| meta.duration)
+            .ok_or(format!("No motion {m} in {c}"))?;
+        stage.model.queue((c, m));
+    }
+
+    //     __
+    //    / _|_ __ __ _ _ __ ___   ___  ___
+    //   | |_| '__/ _` | '_ ` _ \ / _ \/ __|
+    //  _|  _| | | (_| | | | | | |  __/\__ \
+    // (_)_| |_|  \__,_|_| |_| |_|\___||___/
+
+    // Timestep is fixed, so that the same motions give the same frames. They
+    // are drawn as the encoder takes them, not all kept at once
+    let dt = 1. / fps as f64;
+    let count = (duration * fps).ceil().max(1.) as usize;
+    let size = config.window.size;
+
+    let frames = (0..count).map(|i| {
+        stage.update(if i == 0 {0.} else {dt});
+        stage.snapshot(config)
+    });
+
+    //                              _
+    //    _____  ___ __   ___  _ __| |_
+    //   / _ \ \/ / '_ \ / _ \| '__| __|
+    //  |  __/>  <| |_) | (_) | |  | |_
+    // (_)___/_/\_\ .__/ \___/|_|   \__|
+    //            |_|
+
+    let delay = 1000. / fps;
+
+    match format {
+        ExportFormat::Apng     => apng(output, size, count, frames, delay)?,
+        ExportFormat::Gif      => gif(output, frames, delay)?,
+        ExportFormat::WebP     => webp(output, size, frames, delay)?,
+        ExportFormat::Sequence => sequence(output, frames)?,
+    }
+
+    eprintln!("Exported {count} frames to {output}");
+    Ok(())
+}
+
+//  _ _    __ _ _   _  ___  ___ ___
+// (_|_)  / _` | | | |/ _ \/ __/ __|
+//  _ _  | (_| | |_| |  __/\__ \__ \
+// (_|_)  \__, |\__,_|\___||___/___/
+//        |___/
+
+fn guess(output: &str) -> ExportFormat
+{
+    let extension =
+        Path::new(output)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase());
+
+    match extension.as_deref() {
+        Some("gif")          => ExportFormat::Gif,
+        Some("webp")         => ExportFormat::WebP,
+        Some("png" | "apng") => ExportFormat::Apng,
+        _                    => ExportFormat::Sequence,
+    }
+}
+
+//  _ _    __ _ _ __  _ __   __ _
+// (_|_)  / _` | '_ \| '_ \ / _` |
+//  _ _  | (_| | |_) | | | | (_| |
+// (_|_)  \__,_| .__/|_| |_|\__, |
+//             |_|          |___/
+
+fn apng(output: &str,
+        size:   [u32; 2],
+        count:  usize,
+        frames: impl Iterator<Item = Result<RgbaImage, Box<dyn Error>>>,
+        delay:  f32) -> Result<(), Box<dyn Error>>
+{
+    let [width, height] = size;
+    let file = BufWriter::new(File::create(output)?);
+
+    let mut encoder = png::Encoder::new(file,
+                                        width,
+                                        height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_animated(count as u32, 0)?;
+    encoder.set_frame_delay(delay.round() as u16, 1000)?;
+
+    let mut writer = encoder.write_header()?;
+    for frame in frames {
+        writer.write_image_data(frame?.as_raw())?;
+    }
+    writer.finish()?;
+
+    Ok(())
+}
+
+//              _  __
+//  _ _    __ _(_)/ _|
+// (_|_)  / _` | | |_
+//  _ _  | (_| | |  _|
+// (_|_)  \__, |_|_|
+//        |___/
+
+fn gif(output: &str,
+       frames: impl Iterator<Item = Result<RgbaImage, Box<dyn Error>>>,
+       delay:  f32) -> Result<(), Box<dyn Error>>
+{
+    let file = BufWriter::new(File::create(output)?);
+    let delay = Delay::from_numer_denom_ms(delay.round() as u32, 1);
+
+    let mut encoder = GifEncoder::new(file);
+    encoder.set_repeat(Repeat::Infinite)?;
+    for frame in frames {
+        encoder.encode_frame(Frame::from_parts(frame?, 0, 0, delay))?;
+    }
+
+    Ok(())
+}
+
+//                     _
+//  _ _  __      _____| |__  _ __
+// (_|_) \ \ /\ / / _ \ '_ \| '_ \
+//  _ _   \ V  V /  __/ |_) | |_) |
+// (_|_)   \_/\_/ \___|_.__/| .__/
+//                          |_|
+
+// image crate only encodes still WebP, so every frame is encoded on its own,
+// and then the lossless bitstreams are put into an animation container
+fn webp(output: &str,
+        size:   [u32; 2],
+        frames: impl Iterator<Item = Result<RgbaImage, Box<dyn Error>>>,
+        delay:  f32) -> Result<(), Box<dyn Error>>
+{
+    let [width, height] = size;
+    let u24 = |v: u32| {let b = v.to_le_bytes(); [b[0], b[1], b[2]]};
+
+    let chunk = |file: &mut BufWriter<File>, id: &[u8; 4], data: &[u8]| {
+        file.write_all(id)?;
+        file.write_all(&(data.len() as u32).to_le_bytes())?;
+        file.write_all(data)?;
+        match data.len() % 2 {
+            1 => file.write_all(&[0]),
+            _ => Ok(()),
+        }
+    };
+
+    // RIFF size is only known at the end, and written over then
+    let mut file = BufWriter::new(File::create(output)?);
+    file.write_all(b"RIFF\0\0\0\0WEBP")?;
+
+    let mut vp8x = vec![0b0001_0010, 0, 0, 0]; // alpha and animation
+    vp8x.extend_from_slice(&u24(width - 1));
+    vp8x.extend_from_slice(&u24(height - 1));
+    chunk(&mut file, b"VP8X", &vp8x)?;
+
+    // Transparent background, looped forever
+    chunk(&mut file, b"ANIM", &[0, 0, 0, 0, 0, 0])?;
+
+    for frame in frames {
+        let mut still = Vec::new();
+        WebPEncoder::new_lossless(&mut still)
+        .encode(frame?.as_raw(),
+                width,
+                height,
+                ColorType::Rgba8)?;
+
+        let mut anmf = Vec::new();
+        anmf.extend_from_slice(&u24(0));
+        anmf.extend_from_slice(&u24(0));
+        anmf.extend_from_slice(&u24(width - 1));
+        anmf.extend_from_slice(&u24(height - 1));
+        anmf.extend_from_slice(&u24(delay.round() as u32));
+        anmf.push(0b10); // no blending, frames are complete
+        anmf.extend_from_slice(vp8l(&still)?);
+        chunk(&mut file, b"ANMF", &anmf)?;
+    }
+
+    let size = file.stream_position()? - 8;
+    file.seek(SeekFrom::Start(4))?;
+    file.write_all(&(size as u32).to_le_bytes())?;
+    file.flush()?;
+
+    Ok(())
+}
+
+//                     ___  _
+//  _ _  __   ___ __  ( _ )| |
+// (_|_) \ \ / / '_ \ / _ \| |
+//  _ _   \ V /| |_) | (_) | |
+// (_|_)   \_/ | .__/ \___/|_|
+//             |_|
+
+// Bitstream chunk of a still WebP, whichever chunks come before it
+fn vp8l(still: &[u8]) -> Result<&[u8], Box<dyn Error>>
+{
+    // Chunks follow the 12 byte RIFF header, padded to even sizes
+    let mut at = 12;
+    while at + 8 <= still.len() {
+        let id = &still[at..at + 4];
+        let len = u32::from_le_bytes(still[at + 4..at + 8].try_into()?) as usize;
+        let end = (at + 8 + len).min(still.len());
+
+        if id == b"VP8L" {
+            return Ok(&still[at..end])
+        }
+        at = end + len % 2;
+    }
+
+    Err("No VP8L chunk in encoded WebP".into())
+}
+
+//  _ _   ___  ___  __ _ _   _  ___ _ __   ___ ___
+// (_|_) / __|/ _ \/ _` | | | |/ _ \ '_ \ / __/ _ \
+//  _ _  \__ \  __/ (_| | |_| |  __/ | | | (_|  __/
+// (_|_) |___/\___|\__, |\__,_|\___|_| |_|\___\___|
+//                    |_|
+
+fn sequence(output: &str,
+            frames: impl Iterator<Item = Result<RgbaImage, Box<dyn Error>>>)
+            -> Result<(), Box<dyn Error>>
+{
+    let dir = Path::new(output);
+    fs::create_dir_all(dir)?;
+
+    for (i, frame) in frames.enumerate() {
+        frame?.save(dir.join(format!("{i:04}.png")))?;
+    }
+
+    Ok(())
+}
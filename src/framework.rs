@@ -1,41 +1,53 @@
 use std::{
+    fs::{self, File},
     rc::Rc,
-    fs::File,
     iter::zip,
+    path::{Path, PathBuf},
     error::Error,
     collections::{HashMap, VecDeque},
 };
-use glium::{
-    Blend,
-    Surface,
-    uniform,
-    backend::Facade,
-    BlendingFunction,
-    implement_vertex,
-    program::Program,
-    vertex::VertexBuffer,
-    LinearBlendingFactor as F,
-    index::{IndexBuffer, PrimitiveType},
-    texture::{SrgbTexture2d, RawImage2d},
-    draw_parameters::{DrawParameters, Stencil, StencilTest, StencilOperation},
-};
+use image::RgbaImage;
 use cubism::{
     motion::Motion,
     model::UserModel,
+    controller::{Controller, ExpressionController, EyeBlink},
     core::{self, ConstantFlags, DynamicFlags},
     json::{
-        model::Model3,
-        motion::Motion3,
+        cdi::Cdi3,
+        model::{Model3, Layout, HitArea},
+        motion::{Motion3, Meta},
     },
 };
 use crate::{
-    config::Config,
-    ProgramVariant as PV,
+    message::{Tint, Ease, Puppet, Mood, Cue, Info},
+    config::{
+        Config,
+        ColorConfig,
+        TrackingConfig,
+        LipSyncConfig,
+        GraphConfig,
+        BlinkConfig,
+        PcmConfig,
+        Mode,
+    },
+    tracking::Tracking,
+    lipsync::{Clip, LipSync, Speaker},
+    graph::{Graph, Event, Step},
+    renderer::{
+        Gl,
+        Pass,
+        Mask,
+        Device,
+        Renderer,
+        Transform,
+        BlendMode,
+    },
 };
 
 const BLEND_ADD:  ConstantFlags = ConstantFlags::BLEND_ADDITIVE;
 const BLEND_MULT: ConstantFlags = ConstantFlags::BLEND_MULTIPLICATIVE;
 const MASK_INV:   ConstantFlags = ConstantFlags::IS_INVERTED_MASK;
+const TWO_SIDED:  ConstantFlags = ConstantFlags::IS_DOUBLE_SIDED;
 
 const VIS:           DynamicFlags = DynamicFlags::IS_VISIBLE;
 const VIS_CHANGED:   DynamicFlags = DynamicFlags::VISIBILITY_CHANGED;
@@ -43,19 +55,138 @@ const ORDER_CHANGED: DynamicFlags = DynamicFlags::RENDER_ORDER_CHANGED;
 const VERTS_CHANGED: DynamicFlags = DynamicFlags::VERTEX_POSITIONS_CHANGED;
 const BLEND_CHANGED: DynamicFlags = DynamicFlags::BLEND_COLOR_CHANGED;
 
+// Seconds it takes for a part to be shown or hidden
+const PART_FADE: f32 = 0.3;
+
 //  __  __           _      _
 // |  \/  | ___   __| | ___| |
 // | |\/| |/ _ \ / _` |/ _ \ |
 // | |  | | (_) | (_| |  __/ |
 // |_|  |_|\___/ \__,_|\___|_|
 
-pub struct Model {
+pub struct Model<D = Gl>
+where D: Device
+{
     model:     UserModel,
     motions:   Motions,
     queue:     Queue,
     canvas:    CanvasInfo,
-    textures:  Vec<SrgbTexture2d>,
-    drawables: Vec<Drawable>,
+    textures:  Vec<D::Texture>,
+    drawables: Vec<Drawable<D>>,
+    order:     Vec<usize>,
+    path:      PathBuf,
+    files:     HashMap<PathBuf, Asset>,
+    offset:    [f32; 2],
+    zoom:      f32,
+    layout:    Option<Layout>,
+    colors:    HashMap<String, ColorConfig>,
+    parts:     Parts,
+    names:     HashMap<String, String>,
+    params:    Params,
+    tracking:  Tracking,
+    moods:     Moods,
+    blink:     Blink,
+    lipsync:   LipSync,
+    speaker:   Option<Speaker>,
+    hits:      Vec<HitArea>,
+    graph:     Option<Graph>,
+    blend:     Option<Blend>,
+    // Parameter values and part opacities as the motions leave them
+    base:      [Vec<f32>; 2],
+}
+
+//  ____            _
+// |  _ \ __ _ _ __| |_ ___
+// | |_) / _` | '__| __/ __|
+// |  __/ (_| | |  | |_\__ \
+// |_|   \__,_|_|   \__|___/
+
+// Opacities set from outside, over what motions leave the parts with
+struct Parts {
+    targets: HashMap<String, f32>,
+    // Opacity being faded to, and how much of it is shown
+    shown:   Vec<[f32; 2]>,
+}
+
+//  ____
+// |  _ \ __ _ _ __ __ _ _ __ ___  ___
+// | |_) / _` | '__/ _` | '_ ` _ \/ __|
+// |  __/ (_| | | | (_| | | | | | \__ \
+// |_|   \__,_|_|  \__,_|_| |_| |_|___/
+
+// Parameters driven from outside, over or on top of what motions do
+#[derive(Default)]
+struct Params {
+    drives: HashMap<String, Drive>,
+}
+
+//  __  __                 _
+// |  \/  | ___   ___   __| |___
+// | |\/| |/ _ \ / _ \ / _` / __|
+// | |  | | (_) | (_) | (_| \__ \
+// |_|  |_|\___/ \___/ \__,_|___/
+
+// Expressions of the model, one of them set at a time
+struct Moods {
+    controller: ExpressionController,
+    names:      Vec<String>,
+    current:    Option<String>,
+}
+
+//  ____  _ _       _
+// | __ )| (_)_ __ | | __
+// |  _ \| | | '_ \| |/ /
+// | |_) | | | | | |   <
+// |____/|_|_|_| |_|_|\_\
+
+// Eyes of the model's EyeBlink group, blinking on their own if configured
+struct Blink {
+    controller: Option<EyeBlink>,
+    on:         bool,
+}
+
+//  ____       _
+// |  _ \ _ __(_)_   _____
+// | | | | '__| \ \ / / _ \
+// | |_| | |  | |\ V /  __/
+// |____/|_|  |_| \_/ \___|
+
+// Value and how much of it goes in, tweened from one pair to the other
+struct Drive {
+    from:     [f32; 2],
+    to:       [f32; 2],
+    now:      [f32; 2],
+    elapsed:  f32,
+    over:     f32,
+    ease:     Ease,
+    hold:     bool,
+    released: bool,
+}
+
+//     _                 _
+//    / \   ___ ___  ___| |_
+//   / _ \ / __/ __|/ _ \ __|
+//  / ___ \\__ \__ \  __/ |_
+// /_/   \_\___/___/\___|\__|
+
+// What has to be reloaded when a file changes
+enum Asset {
+    Model,
+    Texture(usize),
+    Motion(String, String),
+}
+
+//  ____  _                _
+// | __ )| | ___ _ __   __| |
+// |  _ \| |/ _ \ '_ \ / _` |
+// | |_) | |  __/ | | | (_| |
+// |____/|_|\___|_| |_|\__,_|
+
+// Parameters as the last motion left them, faded out of over seconds
+struct Blend {
+    from:    Vec<f32>,
+    elapsed: f32,
+    over:    f32,
 }
 
 //  __  __       _   _             ____        _
@@ -71,6 +202,9 @@ struct MotionData {
     motion:   Motion,
     looped:   bool,
     duration: f32,
+    sound:    Option<PathBuf>,
+    // Sound decoded the first time the motion starts
+    clip:     Option<Clip>,
 }
 
 //   ___
@@ -108,14 +242,18 @@ struct CanvasInfo {
 // | |_| | | | (_| |\ V  V / (_| | |_) | |  __/
 // |____/|_|  \__,_| \_/\_/ \__,_|_.__/|_|\___|
 
-struct Drawable {
+struct Drawable<D>
+where D: Device
+{
     index:         usize,
-    vertex_buffer: VertexBuffer<Vert>,
-    index_buffer:  IndexBuffer<u16>,
+    mesh:          D::Mesh,
     visible:       bool,
     compose:       Composition,
     order:         i32,
     mask_inverted: bool,
+    two_sided:     bool,
+    // Overrides from the drawable itself or the closest part that has any
+    colors:        ColorConfig,
 }
 
 //   ____                                _ _   _
@@ -126,7 +264,7 @@ struct Drawable {
 //                      |_|
 
 struct Composition {
-    blend:  Blend,
+    blend:  BlendMode,
     mult:   [f32; 4],
     screen: [f32; 4],
 }
@@ -137,30 +275,51 @@ struct Composition {
 // | |  | | (_) | (_| |  __/ |  _ _
 // |_|  |_|\___/ \__,_|\___|_| (_|_)
 
-impl Model {
+impl<D> Model<D>
+where D: Device
+{
 
     //  _ _   _ __   _____      __
     // (_|_) | '_ \ / _ \ \ /\ / /
     //  _ _  | | | |  __/\ V  V /
     // (_|_) |_| |_|\___| \_/\_/
 
-    pub fn new<T>(config:  &Config,
-                  display: &T) -> Result<Self, Box<dyn Error>>
-    where T: Facade + ?Sized
+    pub fn new(config: &Config,
+               device: &D) -> Result<Self, Box<dyn Error>>
+    {
+        let path = {
+            let file =
+                config.model.file.as_ref()
+                .ok_or("No model provided")?;
+
+            expanduser::expanduser(file)?
+        };
+
+        Self::load(config,
+                   &path,
+                   &config.model.motions.open,
+                   device)
+    }
+
+    //        _                 _
+    //  _ _  | | ___   __ _  __| |
+    // (_|_) | |/ _ \ / _` |/ _` |
+    //  _ _  | | (_) | (_| | (_| |
+    // (_|_) |_|\___/ \__,_|\__,_|
+
+    // Any model, not only the configured one
+    pub fn load(config: &Config,
+                file:   &Path,
+                open:   &[(String, String)],
+                device: &D) -> Result<Self, Box<dyn Error>>
     {
         let mut model = Self::init(config,
-                                   display)?;
+                                   file,
+                                   device)?;
 
-        config.model.motions.open.iter()
+        open.iter()
         .for_each(|(c, m)| model.queue((c, m)).unwrap_or(()));
 
-        if let Some(effect) =
-            model.motions
-            .get_mut("")
-            .and_then(|c| c.get_mut("effect")) {
-            effect.motion.play();
-        }
-
         Ok(model)
     }
 
@@ -170,9 +329,9 @@ impl Model {
     //  _ _  | | | | | | |_
     // (_|_) |_|_| |_|_|\__|
 
-    fn init<T>(config:  &Config,
-               display: &T) -> Result<Self, Box<dyn Error>>
-    where T: Facade + ?Sized
+    fn init(config: &Config,
+            file:   &Path,
+            device: &D) -> Result<Self, Box<dyn Error>>
     {
 
         //                _   _
@@ -182,13 +341,9 @@ impl Model {
         // (_) .__/ \__,_|\__|_| |_|
         //   |_|
 
-        let mut path = {
-            let file =
-                config.model.file.as_ref()
-                .ok_or("No model provided")?;
-
-            expanduser::expanduser(file)?
-        };
+        // Absolute, so that changed files can be told by their paths
+        let mut path = fs::canonicalize(file)?;
+        let file = path.clone();
 
         //                        _      _ _____
         //    _ __ ___   ___   __| | ___| |___ /
@@ -203,14 +358,60 @@ impl Model {
 
         if !path.pop() {panic!("How")};
 
+        // References may go through ./, ../ or links, while changes come with
+        // real paths. Missing ones are kept as they are
+        let real = |f: PathBuf| fs::canonicalize(&f).unwrap_or(f);
+
+        let mut files = HashMap::from([(file.clone(), Asset::Model)]);
+        let refs = &model3.file_references;
+        refs.moc.iter()
+        .chain(refs.pose.iter())
+        .chain(refs.physics.iter())
+        .chain(refs.user_data.iter())
+        .chain(refs.expressions.iter().map(|e| &e.file))
+        .for_each(|f| {files.insert(real(path.join(f)), Asset::Model);});
+
+        // Display names are only nice to have
+        let names = match &refs.display_info {
+            Some(f) => {
+                let file = real(path.join(f));
+                files.insert(file.clone(), Asset::Model);
+                names(&file)
+                .unwrap_or_else(|e| {
+                    eprintln!("Failed to read display names: {e}");
+                    HashMap::new()
+                })
+            }
+            None    => HashMap::new(),
+        };
+
         //                        _      _
         //    _ __ ___   ___   __| | ___| |
         //   | '_ ` _ \ / _ \ / _` |/ _ \ |
         //  _| | | | | | (_) | (_| |  __/ |
         // (_)_| |_| |_|\___/ \__,_|\___|_|
 
-        let model = UserModel::from_model3(&path,
-                                           &model3)?;
+        let mut model = UserModel::from_model3(&path,
+                                               &model3)?;
+        let moods = Moods::new(&mut model,
+                               &model3);
+        let blink = Blink::new(&mut model,
+                               &config.model.blink);
+        let lipsync = LipSync::new(&model3,
+                                   &config.lipsync);
+
+        // Rendering and piping stay silent
+        let speaker = match (config.lipsync.audio, &config.mode) {
+            (true, Mode::Window) => {
+                Speaker::new(&config.lipsync)
+                .map_err(|e| eprintln!("Failed to open audio: {e}"))
+                .ok()
+            }
+            _                    => None,
+        };
+
+        let parts = Parts::new(model.model(),
+                               &config.model.parts);
 
         //                    _   _
         //    _ __ ___   ___ | |_(_) ___  _ __  ___
@@ -241,20 +442,14 @@ impl Model {
                     continue;
                 }
 
-                let motion3 = {
-                    let file = File::open(path.join(&m.file))?;
-                    Motion3::from_reader(file)?
-                };
-
-                let looped   = motion3.meta.looped;
-                let duration = motion3.meta.duration;
-                let motion   = Motion::new(motion3);
+                let file = real(path.join(&m.file));
+                let sound = m.sound.as_ref().map(|s| path.join(s));
+                let m = MotionData::load(&file,
+                                         sound)?;
 
-                let m = MotionData {
-                    looped,
-                    duration,
-                    motion,
-                };
+                files.insert(file,
+                             Asset::Motion(class_name.to_string(),
+                                           n.clone()));
 
                 eprintln!("    Added motion {n}");
                 class.insert(n, m);
@@ -268,12 +463,7 @@ impl Model {
         // (_)__, |\__,_|\___|\__,_|\___|
         //      |_|
 
-        let idle = match config.model.motions.idle.clone() {
-            Some((c, m)) => (Rc::new(c),
-                             Rc::new(m)),
-            None         => (Rc::new("".to_string()),
-                             Rc::new("idle".to_string()))
-        };
+        let idle = idle(&config.model.motions.idle);
 
         let queue = Queue {
             lineup:    VecDeque::new(),
@@ -307,18 +497,12 @@ impl Model {
 
         let mut textures = Vec::new();
 
-        for r in model3.file_references.textures {
-            let t_path = path.join(r);
-            let image =
-                image::open(&t_path)?
-                .to_rgba8();
-            let image_dimensions = image.dimensions();
-            let image_raw =
-                RawImage2d::from_raw_rgba_reversed(&image.into_raw(),
-                                                   image_dimensions);
-            let texture = SrgbTexture2d::new(display,
-                                             image_raw)?;
+        for (i, r) in model3.file_references.textures.iter().enumerate() {
+            let file = real(path.join(r));
+            let texture = texture(&file,
+                                  device)?;
             textures.push(texture);
+            files.insert(file, Asset::Texture(i));
         };
 
         //       _                         _     _
@@ -329,7 +513,7 @@ impl Model {
 
         let drawables: Vec<_> =
             Result::from_iter(model.drawables()
-                              .map(|d| Drawable::new(d, display)))?;
+                              .map(|d| Drawable::new(d, device)))?;
 
         //           _
         //  _ __ ___| |_ _   _ _ __ _ __
@@ -337,14 +521,44 @@ impl Model {
         // | | |  __/ |_| |_| | |  | | | |
         // |_|  \___|\__|\__,_|_|  |_| |_|
 
-        Ok(Self {
+        let mut model = Self {
             model,
             motions,
             queue,
             canvas,
             textures,
             drawables,
-        })
+            order:    Vec::new(),
+            path:     file,
+            files,
+            offset:   [0., 0.],
+            zoom:     1.,
+            layout:   model3.layout,
+            colors:   config.model.colors.clone(),
+            parts,
+            names,
+            params:   Params::default(),
+            tracking: Tracking::new(&config.tracking),
+            moods,
+            blink,
+            lipsync,
+            speaker,
+            hits:     model3.hit_areas,
+            graph:    Graph::new(&config.model.graph),
+            blend:    None,
+            base:     [Vec::new(), Vec::new()],
+        };
+        model.sort();
+        model.recolor();
+
+        if let Some(effect) =
+            model.motions
+            .get_mut("")
+            .and_then(|c| c.get_mut("effect")) {
+            effect.motion.play();
+        }
+
+        Ok(model)
     }
 
     //            _
@@ -353,15 +567,16 @@ impl Model {
     //  _ _  | (_| | | | (_| |\ V  V /
     // (_|_)  \__,_|_|  \__,_| \_/\_/
 
-    pub fn draw<T>(&self,
-                   frame:    &mut T,
-                   programs: &[Rc<Program>],
-                   aspect:   [f32; 2]) -> Result<(), Box<dyn Error>>
-    where T: Surface
+    pub fn draw<R>(&self,
+                   renderer: &mut R,
+                   aspect:   [f32; 2],
+                   offset:   [f32; 2]) -> Result<(), Box<dyn Error>>
+    where R: Renderer<Device = D> + ?Sized
     {
-        let drawables = self.ordered();
+        let transform = self.transform(aspect,
+                                       offset);
 
-        for d in drawables.iter() {
+        for d in self.order.iter().map(|i| &self.drawables[*i]) {
             if !d.visible {continue}
 
             let md = self.model.drawable_at(d.index);
@@ -372,52 +587,24 @@ impl Model {
             // | | | | | | (_| \__ \   <
             // |_| |_| |_|\__,_|___/_|\_\
 
-            frame.clear_stencil(0);
+            renderer.clear_mask();
             let masks = md.masks;
-            if !masks.is_empty() {
-                for m in masks {
-                    let find =
-                        drawables.iter()
-                        .find(|d| d.index as i32 == *m);
-
-                    let d = match find {
-                        Some(s) => s,
-                        None    => {
-                            eprintln!("Failed to look up mask {m}");
-                            continue
-                        }
-                    };
-
-                    let uniforms = uniform!{
-                        size:    self.canvas.size,
-                        origin:  self.canvas.origin,
-                        scale:   self.canvas.scale,
-                        aspect:  aspect,
-                    };
-
-                    let op = StencilOperation::Replace;
-                    let params = DrawParameters {
-                        color_mask: (false, false, false, false),
-                        stencil: Stencil {
-                            fail_operation_clockwise:                    op,
-                            pass_depth_fail_operation_clockwise:         op,
-                            depth_pass_operation_clockwise:              op,
-                            fail_operation_counter_clockwise:            op,
-                            pass_depth_fail_operation_counter_clockwise: op,
-                            depth_pass_operation_counter_clockwise:      op,
-                            reference_value_clockwise:                   1,
-                            reference_value_counter_clockwise:           1,
-                            .. Default::default()
-                        },
-                        .. Default::default()
-                    };
-
-                    frame.draw(&d.vertex_buffer,
-                               &d.index_buffer,
-                               &programs[PV::Mask as usize],
-                               &uniforms,
-                               &params)?;
-                }
+            for m in masks {
+                let find = self.drawables.get(*m as usize);
+
+                let d = match find {
+                    Some(s) => s,
+                    None    => {
+                        eprintln!("Failed to look up mask {m}");
+                        continue
+                    }
+                };
+
+                renderer.mask(&d.mesh,
+                              &Pass {
+                                  two_sided: d.two_sided,
+                                  .. Pass::new(transform)
+                              })?;
             }
 
             //      _
@@ -426,45 +613,50 @@ impl Model {
             // | (_| | | | (_| |\ V  V /
             //  \__,_|_|  \__,_| \_/\_/
 
-            let uniforms = uniform!{
-                size:    self.canvas.size,
-                origin:  self.canvas.origin,
-                scale:   self.canvas.scale,
-                opacity: md.opacity,
-                tex:     &self.textures[md.texture_index as usize],
-                aspect:  aspect,
-                screen:  d.compose.screen,
-                mult:    d.compose.mult,
+            let mask = match (masks.is_empty(), d.mask_inverted) {
+                (true, _)      => Mask::None,
+                (false, false) => Mask::Inside,
+                (false, true)  => Mask::Outside,
             };
 
-            let stencil_test = if masks.is_empty() {
-                StencilTest::AlwaysPass
-            } else {
-                let mask = if d.mask_inverted {0} else {1};
-                StencilTest::IfEqual{mask}
-            };
-            let params = &DrawParameters {
-                blend: d.compose.blend,
-                stencil: Stencil {
-                    test_clockwise:                    stencil_test,
-                    test_counter_clockwise:            stencil_test,
-                    reference_value_clockwise:         1,
-                    reference_value_counter_clockwise: 1,
-                    .. Default::default()
-                },
-                .. Default::default()
+            let pass = Pass {
+                transform,
+                opacity:   md.opacity,
+                blend:     d.compose.blend,
+                mult:      d.colors.multiply.unwrap_or(d.compose.mult),
+                screen:    d.colors.screen.unwrap_or(d.compose.screen),
+                mask,
+                two_sided: d.two_sided,
             };
 
-            frame.draw(&d.vertex_buffer,
-                       &d.index_buffer,
-                       &programs[PV::BlendNormal as usize],
-                       &uniforms,
-                       params)?;
+            renderer.draw(&d.mesh,
+                          &self.textures[md.texture_index as usize],
+                          &pass)?;
         }
 
         Ok(())
     }
 
+    //        _                        __
+    //  _ _  | |_ _ __ __ _ _ __  ___ / _| ___  _ __ _ __ ___
+    // (_|_) | __| '__/ _` | '_ \/ __| |_ / _ \| '__| '_ ` _ \
+    //  _ _  | |_| | | (_| | | | \__ \  _| (_) | |  | | | | | |
+    // (_|_)  \__|_|  \__,_|_| |_|___/_|  \___/|_|  |_| |_| |_|
+
+    fn transform(&self,
+                 aspect: [f32; 2],
+                 offset: [f32; 2]) -> Transform
+    {
+        Transform {
+            size:   self.canvas.size,
+            origin: self.canvas.origin,
+            scale:  self.canvas.scale,
+            aspect: aspect.map(|a| a * self.zoom),
+            offset: [offset[0] + self.offset[0],
+                     offset[1] + self.offset[1]],
+        }
+    }
+
     //                        _       _
     //  _ _   _   _ _ __   __| | __ _| |_ ___
     // (_|_) | | | | '_ \ / _` |/ _` | __/ _ \
@@ -475,11 +667,15 @@ impl Model {
     pub fn update(&mut self,
                   dt: f64) -> Result<(), Box<dyn Error>>
     {
-        let queue = &mut self.queue;
-        if queue.is_paused {return Ok(())}
-        queue.elapsed += dt as f32;
+        if self.queue.is_paused {return Ok(())}
+        self.queue.elapsed += dt as f32;
 
-        if queue.elapsed >= queue.duration {self.next();}
+        let step =
+            self.graph.as_mut()
+            .and_then(|g| g.tick(dt as f32));
+        if let Some(s) = step {self.step(s);}
+
+        if self.queue.elapsed >= self.queue.duration {self.next();}
 
         let current =
             self.queue.current.as_ref()
@@ -491,6 +687,25 @@ impl Model {
             .and_then(|class| class.get_mut(current.1.as_str()))
             .ok_or(format!("No motion {} in {}", current.1, current.0))?;
 
+        let level =
+            motion_data.clip.as_ref()
+            .map(|c| c.level(self.queue.elapsed))
+            .unwrap_or(0.);
+        self.lipsync.sound(level);
+
+        // Motions go on from what the model had, not from the overrides
+        let [values, opacities] = &self.base;
+        if !values.is_empty() {
+            let model = self.model.model_mut();
+            model.parameter_values_mut().copy_from_slice(values);
+            model.part_opacities_mut().copy_from_slice(opacities);
+        }
+
+        // Fading from there, now that the motion has changed
+        if let Some(b) = self.blend.as_mut().filter(|b| b.from.is_empty()) {
+            b.from = self.model.parameter_values().to_vec();
+        }
+
         let motion = &mut motion_data.motion;
         motion.tick(dt);
         motion
@@ -507,12 +722,48 @@ impl Model {
                 .update(self.model.model_mut())?;
         }
 
+        if let Some(b) = &mut self.blend {
+            b.elapsed += dt as f32;
+            let t = ease(Ease::InOut,
+                         (b.elapsed / b.over).min(1.));
+
+            zip(self.model.model_mut().parameter_values_mut(),
+                &b.from)
+            .for_each(|(v, f)| *v = f + (*v - f) * t);
+
+            if t >= 1. {self.blend = None}
+        }
+
+        let [values, opacities] = &mut self.base;
+        values.clear();
+        values.extend_from_slice(self.model.parameter_values());
+        opacities.clear();
+        opacities.extend_from_slice(self.model.part_opacities());
+
+        self.moods.apply(self.model.model_mut(),
+                         dt as f32);
+        self.blink.apply(self.model.model_mut(),
+                         dt as f32);
+        self.lipsync.update_parameters(self.model.model_mut(),
+                                       dt as f32);
+        self.tracking.update_parameters(self.model.model_mut(),
+                                        dt as f32);
+        self.params.apply(self.model.model_mut(),
+                          dt as f32);
+        self.parts.apply(self.model.model_mut(),
+                         &self.base[1],
+                         dt as f32);
         self.model.model_mut().update();
 
         zip(&mut self.drawables,
             self.model.drawables())
         .for_each(|(d, s)| d.update(s));
 
+        let reorder =
+            self.model.drawable_dynamic_flags().iter()
+            .any(|f| f.contains(ORDER_CHANGED));
+        if reorder {self.sort()}
+
         Ok(())
     }
 
@@ -526,6 +777,7 @@ impl Model {
     pub fn play(&mut self) -> Option<()>
     {
         self.queue.is_paused = false;
+        if let Some(s) = &self.speaker {s.resume()}
         let current = self.queue.current.as_ref()?;
 
         self.motions
@@ -543,6 +795,7 @@ impl Model {
     pub fn pause(&mut self) -> Option<()>
     {
         self.queue.is_paused = true;
+        if let Some(s) = &self.speaker {s.pause()}
         let current = self.queue.current.as_ref()?;
 
         self.motions
@@ -574,6 +827,7 @@ impl Model {
     {
         self.queue.is_paused = true;
         self.queue.elapsed = 0.;
+        if let Some(s) = &mut self.speaker {s.stop()}
         let current = self.queue.current.as_ref()?;
 
         self.motions
@@ -605,12 +859,14 @@ impl Model {
             .and_then(|c| c.get_mut(new.1))?;
 
         motion_data.motion.set_looped(motion_data.looped);
+        motion_data.decode();
 
         self.queue.current = Some((Rc::new(new.0.to_string()),
                                    Rc::new(new.1.to_string())));
         self.queue.duration = motion_data.duration;
         self.queue.elapsed = 0.;
         self.restart();
+        self.speak(0.);
         eprintln!("Set motion {} from {}", new.1, new.0);
         Some(())
     }
@@ -660,6 +916,29 @@ impl Model {
         Some(())
     }
 
+    //             _
+    //  _ _    ___| | ___  __ _ _ __
+    // (_|_)  / __| |/ _ \/ _` | '__|
+    //  _ _  | (__| |  __/ (_| | |
+    // (_|_)  \___|_|\___|\__,_|_|
+
+    pub fn clear(&mut self) {self.queue.lineup.clear()}
+
+    //                       _
+    //  _ _   _ __ ___   ___| |_ __ _
+    // (_|_) | '_ ` _ \ / _ \ __/ _` |
+    //  _ _  | | | | | |  __/ || (_| |
+    // (_|_) |_| |_| |_|\___|\__\__,_|
+
+    pub fn meta(&self,
+                motion: (&str, &str)) -> Option<&Meta>
+    {
+        self.motions
+        .get(motion.0)
+        .and_then(|c| c.get(motion.1))
+        .map(|m| &m.motion.meta)
+    }
+
     //                        _
     //  _ _   _ __   _____  _| |_
     // (_|_) | '_ \ / _ \ \/ / __|
@@ -671,6 +950,14 @@ impl Model {
         let next = match self.queue.lineup.pop_front() {
             Some(m) => m,
             None    => {
+                // Idle is for when the graph has nothing to play
+                let step =
+                    self.graph.as_mut()
+                    .and_then(Graph::next);
+                if step.and_then(|s| self.step(s)).is_some() {
+                    return Some(())
+                }
+
                 let t = &self.queue.idle;
                 (t.0.clone(),
                  t.1.clone())
@@ -681,17 +968,246 @@ impl Model {
                          next.1.as_str()))
     }
 
-    //                      _                   _
-    //  _ _    ___  _ __ __| | ___ _ __ ___  __| |
-    // (_|_)  / _ \| '__/ _` |/ _ \ '__/ _ \/ _` |
-    //  _ _  | (_) | | | (_| |  __/ | |  __/ (_| |
-    // (_|_)  \___/|_|  \__,_|\___|_|  \___|\__,_|
+    //            _
+    //  _ _   ___| |_ ___ _ __
+    // (_|_) / __| __/ _ \ '_ \
+    //  _ _  \__ \ ||  __/ |_) |
+    // (_|_) |___/\__\___| .__/
+    //                   |_|
+
+    // Motion the graph went to, faded into from where the last one left off
+    fn step(&mut self,
+            step: Step) -> Option<()>
+    {
+        let (c, m) = &step.motion;
+        if self.set((c, m)).is_none() {
+            eprintln!("No motion {m} in {c}");
+            return None
+        }
+
+        self.blend = match step.fade > 0. {
+            true  => Some(Blend {
+                from:    Vec::new(),
+                elapsed: 0.,
+                over:    step.fade,
+            }),
+            false => None,
+        };
+        Some(())
+    }
+
+    //                       _
+    //  _ _   ___  ___  _ __| |_
+    // (_|_) / __|/ _ \| '__| __|
+    //  _ _  \__ \ (_) | |  | |_
+    // (_|_) |___/\___/|_|   \__|
+
+    fn sort(&mut self)
+    {
+        let drawables = &self.drawables;
+        self.order = (0..drawables.len()).collect();
+        self.order.sort_unstable_by_key(|i| drawables[*i].order);
+    }
+
+    //                 _                 _
+    //  _ _   _ __ ___| | ___   __ _  __| |
+    // (_|_) | '__/ _ \ |/ _ \ / _` |/ _` |
+    //  _ _  | | |  __/ | (_) | (_| | (_| |
+    // (_|_) |_|  \___|_|\___/ \__,_|\__,_|
+
+    // Returns whether the model was loaded anew, so its files are to be
+    // watched anew as well
+    pub fn reload(&mut self,
+                  config: &Config,
+                  device: &D,
+                  file:   &Path) -> Result<bool, Box<dyn Error>>
+    {
+        match self.files.get(file) {
+            None                    => Ok(false),
+            Some(Asset::Texture(i)) => {
+                let i = *i;
+                self.textures[i] = texture(file,
+                                           device)?;
+
+                eprintln!("Reloaded texture {}", file.display());
+                Ok(false)
+            }
+            Some(Asset::Motion(c, m)) => {
+                let (c, m) = (c.clone(),
+                              m.clone());
+
+                let old =
+                    self.motions
+                    .get_mut(&c)
+                    .and_then(|class| class.get_mut(&m))
+                    .ok_or(format!("No motion {m} in {c}"))?;
+                let new = MotionData::load(file,
+                                           old.sound.clone())?;
+                let playing = old.motion.is_playing();
+                *old = new;
+
+                let current =
+                    self.queue.current.as_ref()
+                    .map(|(qc, qm)| **qc == c && **qm == m)
+                    .unwrap_or(false);
+
+                if current {
+                    self.resume(self.queue.elapsed);
+                } else if playing {
+                    old.motion.play();
+                }
+
+                eprintln!("Reloaded motion {m} from {c}");
+                Ok(false)
+            }
+            Some(Asset::Model) => {
+                let mut model = Self::init(config,
+                                           &self.path,
+                                           device)?;
+                model.inherit(self);
+                *self = model;
+
+                eprintln!("Reloaded model {}", self.path.display());
+                Ok(true)
+            }
+        }
+    }
+
+    //        _       _               _ _
+    //  _ _  (_)_ __ | |__   ___ _ __(_) |_
+    // (_|_) | | '_ \| '_ \ / _ \ '__| | __|
+    //  _ _  | | | | | | | |  __/ |  | | |_
+    // (_|_) |_|_| |_|_| |_|\___|_|  |_|\__|
+
+    // Keeps the motions going through a full reload, where they still exist
+    fn inherit(&mut self,
+               old: &Self)
+    {
+        self.offset = old.offset;
+        self.zoom = old.zoom;
+        self.queue.idle = old.queue.idle.clone();
+        self.set_colors(&old.colors);
+        self.parts = Parts::new(self.model.model(),
+                                &old.parts.targets);
+        self.params.drives =
+            old.params.drives.iter()
+            .map(|(id, d)| (id.clone(), d.settled()))
+            .collect();
+        if let Some(name) = &old.moods.current {
+            self.moods.set(Some(name));
+        }
+        if let (Some(new), Some(old)) = (&mut self.graph, &old.graph) {
+            new.inherit(old);
+        }
+
+        let motions = &self.motions;
+        self.queue.lineup =
+            old.queue.lineup.iter()
+            .filter(|(c, m)| {
+                motions.get(c.as_str())
+                .map(|class| class.contains_key(m.as_str()))
+                .unwrap_or(false)
+            })
+            .cloned()
+            .collect();
+
+        let current = match &old.queue.current {
+            Some(c) => c,
+            None    => return,
+        };
+
+        if self.set((current.0.as_str(),
+                     current.1.as_str())).is_some() {
+            self.queue.is_paused = old.queue.is_paused;
+            self.resume(old.queue.elapsed);
+        }
+    }
+
+    //  _ _   _ __ ___  ___ _   _ _ __ ___   ___
+    // (_|_) | '__/ _ \/ __| | | | '_ ` _ \ / _ \
+    //  _ _  | | |  __/\__ \ |_| | | | | | |  __/
+    // (_|_) |_|  \___||___/\__,_|_| |_| |_|\___|
+
+    // Puts the current motion where it would be after playing for a while
+    fn resume(&mut self,
+              elapsed: f32)
+    {
+        let queue = &mut self.queue;
+        let current = match &queue.current {
+            Some(c) => c,
+            None    => return,
+        };
+
+        let data =
+            self.motions
+            .get_mut(current.0.as_str())
+            .and_then(|c| c.get_mut(current.1.as_str()));
+
+        if let Some(data) = data {
+            data.decode();
+            let motion = &mut data.motion;
+            motion.set_looped(data.looped);
+            motion.stop();
+            motion.play();
+            motion.tick(elapsed as f64);
+            if queue.is_paused {motion.pause()}
+
+            queue.duration = data.duration;
+            queue.elapsed = elapsed;
+        }
+
+        self.speak(elapsed);
+    }
+
+    //                             _
+    //  _ _   ___ _ __   ___  __ _| | __
+    // (_|_) / __| '_ \ / _ \/ _` | |/ /
+    //  _ _  \__ \ |_) |  __/ (_| |   <
+    // (_|_) |___/ .__/ \___|\__,_|_|\_\
+    //           |_|
 
-    fn ordered(&self) -> Vec<&Drawable>
+    // Sound of the current motion, played from where the motion is
+    fn speak(&mut self,
+             from: f32)
     {
-        let mut result = Vec::from_iter(self.drawables.iter());
-        result.sort_unstable_by_key(|d| d.order);
-        result
+        let speaker = match &mut self.speaker {
+            Some(s) => s,
+            None    => return,
+        };
+
+        let clip =
+            self.queue.current.as_ref()
+            .and_then(|(c, m)| self.motions.get(c.as_str())?.get(m.as_str()))
+            .and_then(|d| d.clip.as_ref());
+
+        match clip {
+            Some(c) => speaker.play(c, from),
+            None    => speaker.stop(),
+        }
+        if self.queue.is_paused {speaker.pause()}
+    }
+
+    //                 _     _     _ _
+    //  _ _   ___  ___| |_  (_) __| | | ___
+    // (_|_) / __|/ _ \ __| | |/ _` | |/ _ \
+    //  _ _  \__ \  __/ |_  | | (_| | |  __/
+    // (_|_) |___/\___|\__| |_|\__,_|_|\___|
+
+    pub fn set_idle(&mut self,
+                    motion: &Option<(String, String)>)
+    {
+        self.queue.idle = idle(motion);
+    }
+
+    //         __ _ _
+    //  _ _   / _(_) | ___  ___
+    // (_|_) | |_| | |/ _ \/ __|
+    //  _ _  |  _| | |  __/\__ \
+    // (_|_) |_| |_|_|\___||___/
+
+    pub fn files(&self) -> impl Iterator<Item = &Path>
+    {
+        self.files.keys().map(PathBuf::as_path)
     }
 
     //            _
@@ -701,6 +1217,500 @@ impl Model {
     // (_|_) |___/_/___\___|
 
     pub fn size(&self) -> [f32; 2] {self.canvas.size}
+
+    //         __
+    //  _ _   / _|_ __ __ _ _ __ ___   ___
+    // (_|_) | |_| '__/ _` | '_ ` _ \ / _ \
+    //  _ _  |  _| | | (_| | | | | | |  __/
+    // (_|_) |_| |_|  \__,_|_| |_| |_|\___|
+
+    // Part of the canvas to show, as its size and the offset of its middle
+    // from the middle of the canvas, in pixels with y going up
+    pub fn frame(&self,
+                 layout: bool) -> [[f32; 2]; 2]
+    {
+        let [w, h] = self.canvas.size;
+        let l = match (layout, &self.layout) {
+            (true, Some(l)) => l,
+            _               => return [[w, h], [0., 0.]],
+        };
+
+        // Layout places the canvas in a view of [-1; 1] both ways, where it
+        // is 2 high unless said otherwise
+        let k =
+            l.height.map(|v| v / h)
+            .or(l.width.map(|v| v / w))
+            .filter(|k| *k > 0.)
+            .unwrap_or(2. / h);
+
+        let x = l.center_x.or(l.x.map(|x| x + w * k / 2.)).unwrap_or(0.);
+        let y = l.center_y.or(l.y.map(|y| y - h * k / 2.)).unwrap_or(0.);
+
+        [[2. / k; 2],
+         [-x / k, -y / k]]
+    }
+
+    //                    _   _
+    //  _ _   _ __   __ _| |_| |__
+    // (_|_) | '_ \ / _` | __| '_ \
+    //  _ _  | |_) | (_| | |_| | | |
+    // (_|_) | .__/ \__,_|\__|_| |_|
+    //       |_|
+
+    pub fn path(&self) -> &Path {&self.path}
+
+    //                 _           _                                     _
+    //  _ _   ___  ___| |_   _ __ | | __ _  ___ ___ _ __ ___   ___ _ __ | |_
+    // (_|_) / __|/ _ \ __| | '_ \| |/ _` |/ __/ _ \ '_ ` _ \ / _ \ '_ \| __|
+    //  _ _  \__ \  __/ |_  | |_) | | (_| | (_|  __/ | | | | |  __/ | | | |_
+    // (_|_) |___/\___|\__| | .__/|_|\__,_|\___\___|_| |_| |_|\___|_| |_|\__|
+    //                      |_|
+
+    // Where the center of the model goes in the view, and how much bigger it
+    // is than it would be alone
+    pub fn set_placement(&mut self,
+                         offset: [f32; 2],
+                         zoom:   f32)
+    {
+        self.offset = offset;
+        self.zoom = zoom;
+    }
+
+    //                 _               _
+    //  _ _   ___  ___| |_    ___ ___ | | ___  _ __
+    // (_|_) / __|/ _ \ __|  / __/ _ \| |/ _ \| '__|
+    //  _ _  \__ \  __/ |_  | (_| (_) | | (_) | |
+    // (_|_) |___/\___|\__|  \___\___/|_|\___/|_|
+
+    // Color of a part goes to everything in it, parts inside of it included
+    pub fn set_color(&mut self,
+                     target: &str,
+                     tint:   Tint,
+                     color:  Option<[f32; 4]>) -> Result<(), String>
+    {
+        let core = self.model.model();
+        if !core.drawable_ids().contains(&target)
+           && !core.part_ids().contains(&target) {
+            return Err(format!("No part or drawable {target}"))
+        }
+
+        let colors = self.colors.entry(target.to_string()).or_default();
+        match tint {
+            Tint::Multiply => colors.multiply = color,
+            Tint::Screen   => colors.screen = color,
+        }
+        if *colors == ColorConfig::default() {self.colors.remove(target);}
+
+        self.recolor();
+        Ok(())
+    }
+
+    //                 _               _
+    //  _ _   ___  ___| |_    ___ ___ | | ___  _ __ ___
+    // (_|_) / __|/ _ \ __|  / __/ _ \| |/ _ \| '__/ __|
+    //  _ _  \__ \  __/ |_  | (_| (_) | | (_) | |  \__ \
+    // (_|_) |___/\___|\__|  \___\___/|_|\___/|_|  |___/
+
+    // Ones that are not in the model do nothing
+    pub fn set_colors(&mut self,
+                      colors: &HashMap<String, ColorConfig>)
+    {
+        self.colors = colors.clone();
+        self.recolor();
+    }
+
+    //                           _
+    //  _ _   _ __ ___  ___ ___ | | ___  _ __
+    // (_|_) | '__/ _ \/ __/ _ \| |/ _ \| '__|
+    //  _ _  | | |  __/ (_| (_) | | (_) | |
+    // (_|_) |_|  \___|\___\___/|_|\___/|_|
+
+    fn recolor(&mut self)
+    {
+        let core = self.model.model();
+        let drawable_ids = core.drawable_ids();
+        let drawable_parts = core.drawable_parent_parts();
+        let part_ids = core.part_ids();
+        let part_parents = core.part_parents();
+
+        for d in &mut self.drawables {
+            // Drawable first, then up its parts
+            let mut ids = vec![drawable_ids[d.index]];
+            let mut part = drawable_parts[d.index];
+            while part >= 0 {
+                ids.push(part_ids[part as usize]);
+                part = part_parents[part as usize];
+            }
+
+            let colors: Vec<_> =
+                ids.iter()
+                .filter_map(|id| self.colors.get(*id))
+                .collect();
+
+            d.colors = ColorConfig {
+                multiply: colors.iter().find_map(|c| c.multiply),
+                screen:   colors.iter().find_map(|c| c.screen),
+            };
+        }
+    }
+
+    //                 _                      _
+    //  _ _   ___  ___| |_   _ __   __ _ _ __| |_
+    // (_|_) / __|/ _ \ __| | '_ \ / _` | '__| __|
+    //  _ _  \__ \  __/ |_  | |_) | (_| | |  | |_
+    // (_|_) |___/\___|\__| | .__/ \__,_|_|   \__|
+    //                      |_|
+
+    // None goes back to whatever the model does with the part
+    pub fn set_part(&mut self,
+                    id:      &str,
+                    opacity: Option<f32>) -> Result<(), String>
+    {
+        if !self.model.model().part_ids().contains(&id) {
+            return Err(format!("No part {id}"))
+        }
+
+        match opacity {
+            Some(o) => self.parts.targets.insert(id.to_string(),
+                                                 o.clamp(0., 1.)),
+            None    => self.parts.targets.remove(id),
+        };
+
+        Ok(())
+    }
+
+    //                 _                      _
+    //  _ _   ___  ___| |_   _ __   __ _ _ __| |_ ___
+    // (_|_) / __|/ _ \ __| | '_ \ / _` | '__| __/ __|
+    //  _ _  \__ \  __/ |_  | |_) | (_| | |  | |_\__ \
+    // (_|_) |___/\___|\__| | .__/ \__,_|_|   \__|___/
+    //                      |_|
+
+    // Ones that are not in the model do nothing
+    pub fn set_parts(&mut self,
+                     parts: &HashMap<String, f32>)
+    {
+        self.parts.targets = parts.clone();
+    }
+
+    //                         _
+    //  _ _   _ __   __ _ _ __| |_ ___
+    // (_|_) | '_ \ / _` | '__| __/ __|
+    //  _ _  | |_) | (_| | |  | |_\__ \
+    // (_|_) | .__/ \__,_|_|   \__|___/
+    //       |_|
+
+    // Id, display name and opacity of every part, overridden ones marked
+    pub fn parts(&self) -> String
+    {
+        let model = self.model.model();
+
+        zip(model.part_ids(),
+            model.part_opacities())
+        .map(|(id, o)| {
+            let mark = match self.parts.targets.contains_key(*id) {
+                true  => "*",
+                false => " ",
+            };
+            let name =
+                self.names.get(*id)
+                .map(String::as_str)
+                .unwrap_or("-");
+
+            format!("{mark} {id}\t{name}\t{o:.2}\n")
+        })
+        .collect()
+    }
+
+    //            _      _
+    //  _ _    __| |_ __(_)_   _____
+    // (_|_)  / _` | '__| \ \ / / _ \
+    //  _ _  | (_| | |  | |\ V /  __/
+    // (_|_)  \__,_|_|  |_| \_/ \___|
+
+    // Held values are clamped right away, added ones once they are added
+    pub fn drive(&mut self,
+                 puppet: &Puppet) -> Result<(), String>
+    {
+        let model = self.model.model();
+        let i =
+            model.parameter_ids().iter()
+            .position(|id| *id == puppet.id)
+            .ok_or(format!("No parameter {}", puppet.id))?;
+
+        let value = match puppet.hold {
+            true  => puppet.value.map(|v| v.clamp(model.parameter_min()[i],
+                                                  model.parameter_max()[i])),
+            false => puppet.value,
+        };
+
+        let drives = &mut self.params.drives;
+        match (value, drives.get_mut(&puppet.id)) {
+            (Some(v), Some(d)) => d.set(v, puppet),
+            (Some(v), None)    => {
+                drives.insert(puppet.id.clone(),
+                              Drive::new(v, puppet));
+            }
+            (None, Some(d))    => d.release(puppet),
+            (None, None)       => return Err(format!("Parameter {} is not \
+                                                      driven",
+                                                     puppet.id)),
+        }
+
+        Ok(())
+    }
+
+    //        _                  _
+    //  _ _  | |_ _ __ __ _  ___| | __
+    // (_|_) | __| '__/ _` |/ __| |/ /
+    //  _ _  | |_| | | (_| | (__|   <
+    // (_|_)  \__|_|  \__,_|\___|_|\_\
+
+    pub fn track(&mut self,
+                 values: &[(String, f32)])
+    {
+        self.tracking.feed(values);
+    }
+
+    //                 _     _                  _    _
+    //  _ _   ___  ___| |_  | |_ _ __ __ _  ___| | _(_)_ __   __ _
+    // (_|_) / __|/ _ \ __| | __| '__/ _` |/ __| |/ / | '_ \ / _` |
+    //  _ _  \__ \  __/ |_  | |_| | | (_| | (__|   <| | | | | (_| |
+    // (_|_) |___/\___|\__|  \__|_|  \__,_|\___|_|\_\_|_| |_|\__, |
+    //                                                       |___/
+
+    pub fn set_tracking(&mut self,
+                        config: &TrackingConfig)
+    {
+        self.tracking.configure(config);
+    }
+
+    //                 _     _ _
+    //  _ _   ___  ___| |_  | (_)_ __  ___ _   _ _ __   ___
+    // (_|_) / __|/ _ \ __| | | | '_ \/ __| | | | '_ \ / __|
+    //  _ _  \__ \  __/ |_  | | | |_) \__ \ |_| | | | | (__
+    // (_|_) |___/\___|\__| |_|_| .__/|___/\__, |_| |_|\___|
+    //                          |_|        |___/
+
+    pub fn set_lipsync(&mut self,
+                       config: &LipSyncConfig)
+    {
+        self.lipsync.configure(config);
+        if let Some(s) = &mut self.speaker {s.configure(config)}
+    }
+
+    //                 _     _     _ _       _
+    //  _ _   ___  ___| |_  | |__ | (_)_ __ | | __
+    // (_|_) / __|/ _ \ __| | '_ \| | | '_ \| |/ /
+    //  _ _  \__ \  __/ |_  | |_) | | | | | |   <
+    // (_|_) |___/\___|\__| |_.__/|_|_|_| |_|_|\_\
+
+    pub fn set_blink(&mut self,
+                     config: &Option<BlinkConfig>)
+    {
+        self.blink.configure(config);
+    }
+
+    //                 _                           _
+    //  _ _   ___  ___| |_    __ _ _ __ __ _ _ __ | |__
+    // (_|_) / __|/ _ \ __|  / _` | '__/ _` | '_ \| '_ \
+    //  _ _  \__ \  __/ |_  | (_| | | | (_| | |_) | | | |
+    // (_|_) |___/\___|\__|  \__, |_|  \__,_| .__/|_| |_|
+    //                       |___/          |_|
+
+    // State stays through the config being reloaded, if it is still there
+    pub fn set_graph(&mut self,
+                     config: &GraphConfig)
+    {
+        let mut graph = Graph::new(config);
+        if let (Some(new), Some(old)) = (&mut graph, &self.graph) {
+            new.inherit(old);
+        }
+        self.graph = graph;
+    }
+
+    //        _                   _
+    //  _ _  | |_ ___  _   _  ___| |__
+    // (_|_) | __/ _ \| | | |/ __| '_ \
+    //  _ _  | || (_) | |_| | (__| | | |
+    // (_|_)  \__\___/ \__,_|\___|_| |_|
+
+    // Hit areas are told by the bounds of their drawables, in clip space same
+    // as the point. Returns whether any was hit
+    pub fn touch(&mut self,
+                 point:  [f32; 2],
+                 aspect: [f32; 2],
+                 offset: [f32; 2]) -> bool
+    {
+        let transform = self.transform(aspect,
+                                       offset);
+
+        let hit =
+            self.hits.iter()
+            .find(|h| {
+                let d = match self.model.drawable(&h.id) {
+                    Some(d) => d,
+                    None    => return false,
+                };
+
+                let (lo, hi) =
+                    d.vertex_positions.iter()
+                    .map(|p| transform.apply(*p))
+                    .fold(([f32::MAX; 2], [f32::MIN; 2]),
+                          |(lo, hi), p| ([lo[0].min(p[0]), lo[1].min(p[1])],
+                                         [hi[0].max(p[0]), hi[1].max(p[1])]));
+
+                (0..2).all(|i| lo[i] <= point[i] && point[i] <= hi[i])
+            })
+            .map(|h| h.name.clone());
+
+        let name = match hit {
+            Some(n) => n,
+            None    => return false,
+        };
+        eprintln!("Touched {name}");
+
+        let step =
+            self.graph.as_mut()
+            .and_then(|g| g.cue(Event::Hit(&name)));
+        if let Some(s) = step {self.step(s);}
+
+        true
+    }
+
+    //                    _
+    //  _ _   _ __   ___ | | _____
+    // (_|_) | '_ \ / _ \| |/ / _ \
+    //  _ _  | |_) | (_) |   <  __/
+    // (_|_) | .__/ \___/|_|\_\___|
+    //       |_|
+
+    // Anyone doing anything in the window
+    pub fn poke(&mut self)
+    {
+        let step =
+            self.graph.as_mut()
+            .and_then(|g| g.cue(Event::Active));
+        if let Some(s) = step {self.step(s);}
+    }
+
+    //  _ _    ___ _   _  ___
+    // (_|_)  / __| | | |/ _ \
+    //  _ _  | (__| |_| |  __/
+    // (_|_)  \___|\__,_|\___|
+
+    pub fn cue(&mut self,
+               cue: &Cue) -> Result<String, String>
+    {
+        let graph =
+            self.graph.as_mut()
+            .ok_or("No motion graph".to_string())?;
+
+        let step = match cue {
+            Cue::Get        => return Ok(format!("{}\n", graph.state())),
+            Cue::List       => return Ok(graph.list()),
+            Cue::Set(n)     => graph.set(n)?,
+            Cue::Trigger(n) => graph.cue(Event::Trigger(n)),
+        };
+        if let Some(s) = step {self.step(s);}
+
+        Ok(String::new())
+    }
+
+    //  _ _   ___  __ _ _   _
+    // (_|_) / __|/ _` | | | |
+    //  _ _  \__ \ (_| | |_| |
+    // (_|_) |___/\__,_|\__, |
+    //                  |___/
+
+    // Moves the mouth only, the clip is not played
+    pub fn say(&mut self,
+               clip: Clip)
+    {
+        self.lipsync.say(clip);
+    }
+
+    //        _
+    //  _ _  | |__   ___  __ _ _ __
+    // (_|_) | '_ \ / _ \/ _` | '__|
+    //  _ _  | | | |  __/ (_| | |
+    // (_|_) |_| |_|\___|\__,_|_|
+
+    pub fn hear(&mut self,
+                level: f32,
+                form:  f32)
+    {
+        self.lipsync.hear(level, form);
+    }
+
+    //                  _ _ _               _
+    //  _ _    ___ __ _| (_) |__  _ __ __ _| |_ ___
+    // (_|_)  / __/ _` | | | '_ \| '__/ _` | __/ _ \
+    //  _ _  | (_| (_| | | | |_) | | | (_| | ||  __/
+    // (_|_)  \___\__,_|_|_|_.__/|_|  \__,_|\__\___|
+
+    pub fn calibrate(&mut self) -> Result<String, String>
+    {
+        self.tracking.calibrate()
+    }
+
+    //  _ _    _____  ___ __  _ __ ___  ___ ___
+    // (_|_)  / _ \ \/ / '_ \| '__/ _ \/ __/ __|
+    //  _ _  |  __/>  <| |_) | | |  __/\__ \__ \
+    // (_|_)  \___/_/\_\ .__/|_|  \___||___/___/
+    //                 |_|
+
+    // Names of the expressions when listed, the one set marked
+    pub fn express(&mut self,
+                   mood: &Mood) -> Result<String, String>
+    {
+        let moods = &mut self.moods;
+        let name = match mood {
+            Mood::List      => return Ok(moods.list()),
+            Mood::Clear     => None,
+            Mood::Set(n)    => Some(n),
+            Mood::Toggle(n) => match moods.current.as_ref() == Some(n) {
+                true  => None,
+                false => Some(n),
+            },
+        };
+
+        if let Some(n) = name.filter(|n| !moods.names.contains(n)) {
+            return Err(format!("No expression {n}"))
+        }
+        moods.set(name.map(String::as_str));
+
+        Ok(String::new())
+    }
+
+    //        _        __
+    //  _ _  (_)_ __  / _| ___
+    // (_|_) | | '_ \| |_ / _ \
+    //  _ _  | | | | |  _| (_) |
+    // (_|_) |_|_| |_|_|  \___/
+
+    pub fn info(&self) -> Info
+    {
+        // Effect plays along with everything, it is no motion of its own
+        let mut motions: Vec<_> =
+            self.motions.iter()
+            .flat_map(|(c, class)| class.keys().map(move |m| (c, m)))
+            .filter(|(c, m)| !(c.is_empty() && *m == "effect"))
+            .map(|(c, m)| (c.clone(), m.clone()))
+            .collect();
+        motions.sort();
+
+        Info {
+            file:        self.path.clone(),
+            parameters:  self.model.model().parameter_ids().len(),
+            drawables:   self.drawables.len(),
+            textures:    self.textures.len(),
+            motions,
+            expressions: self.moods.names.clone(),
+            expression:  self.moods.current.clone(),
+            state:       self.graph.as_ref().map(|g| g.state().to_string()),
+        }
+    }
 }
 
 //  ____                          _     _
@@ -709,16 +1719,17 @@ impl Model {
 // | |_| | | | (_| |\ V  V / (_| | |_) | |  __/  _ _
 // |____/|_|  \__,_| \_/\_/ \__,_|_.__/|_|\___| (_|_)
 
-impl Drawable {
+impl<D> Drawable<D>
+where D: Device
+{
 
     //  _ _   _ __   _____      __
     // (_|_) | '_ \ / _ \ \ /\ / /
     //  _ _  | | | |  __/\ V  V /
     // (_|_) |_| |_|\___| \_/\_/
 
-    fn new<T>(drawable: core::Drawable,
-              display:  &T) -> Result<Self, Box<dyn Error>>
-    where T: Facade + ?Sized
+    fn new(drawable: core::Drawable,
+           device:   &D) -> Result<Self, Box<dyn Error>>
     {
         let constant_flags = drawable.constant_flags;
         let dynamic_flags = drawable.dynamic_flags;
@@ -731,33 +1742,15 @@ impl Drawable {
 
         let index = drawable.index;
 
-        //                 _              _            __  __
-        // __   _____ _ __| |_ _____  __ | |__  _   _ / _|/ _| ___ _ __
-        // \ \ / / _ \ '__| __/ _ \ \/ / | '_ \| | | | |_| |_ / _ \ '__|
-        //  \ V /  __/ |  | ||  __/>  <  | |_) | |_| |  _|  _|  __/ |
-        // (_)_/ \___|_|   \__\___/_/\_\ |_.__/ \__,_|_| |_|  \___|_|
-
-        let vertices: Vec<_> =
-            zip(drawable.vertex_positions,
-                drawable.vertex_uvs)
-            .map(|(pos, uv)| Vert{
-                position:   *pos,
-                texture_uv: *uv,
-            }).collect();
-        let vertex_buffer =
-            VertexBuffer::dynamic(display,
-                                  &vertices)?;
-
-        //    _           _             _            __  __
-        //   (_)_ __   __| | _____  __ | |__  _   _ / _|/ _| ___ _ __
-        //   | | '_ \ / _` |/ _ \ \/ / | '_ \| | | | |_| |_ / _ \ '__|
-        //  _| | | | | (_| |  __/>  <  | |_) | |_| |  _|  _|  __/ |
-        // (_)_|_| |_|\__,_|\___/_/\_\ |_.__/ \__,_|_| |_|  \___|_|
-
-        let index_buffer =
-            IndexBuffer::new(display,
-                             PrimitiveType::TrianglesList,
-                             drawable.indices)?;
+        //                        _
+        //    _ __ ___   ___  ___| |__
+        //   | '_ ` _ \ / _ \/ __| '_ \
+        //  _| | | | | |  __/\__ \ | | |
+        // (_)_| |_| |_|\___||___/_| |_|
+
+        let mesh = device.mesh(drawable.vertex_positions,
+                               drawable.vertex_uvs,
+                               drawable.indices)?;
 
         //        _     _ _     _
         // __   _(_)___(_) |__ | | ___
@@ -773,74 +1766,19 @@ impl Drawable {
         //  _| |_) | |  __/ | | | (_| |
         // (_)_.__/|_|\___|_| |_|\__,_|
 
-        //    ___ ___  _ __ ___  _ __   ___  ___  ___
-        //   / __/ _ \| '_ ` _ \| '_ \ / _ \/ __|/ _ \
-        //  | (_| (_) | | | | | | |_) | (_) \__ \  __/
-        // (_)___\___/|_| |_| |_| .__/ \___/|___/\___|
-        //                      |_|
-        let compose =
-            if constant_flags.contains(BLEND_ADD) {
-                Composition {
-                    mult:   [1.0, 1.0, 1.0, 1.0],
-                    screen: *drawable.screen_color,
-                    blend: Blend {
-                        color: BlendingFunction::Addition {
-                            source:      F::SourceAlpha,
-                            destination: F::One,
-                        },
-                        alpha: BlendingFunction::Addition {
-                            source:      F::Zero,
-                            destination: F::One,
-                        },
-                        .. Default::default()
-                    }
-                }
-            } else if constant_flags.contains(BLEND_MULT) {
-                Composition {
-                    mult:   *drawable.multiply_color,
-                    screen: [0.0, 0.0, 0.0, 1.0],
-                    // I still don't know what the issue is with the blending
-                    // I take from the original framework :(
-                    //blend: Blend {
-                    //    color: BlendingFunction::Addition {
-                    //        source:      F::DestinationColor,
-                    //        destination: F::OneMinusSourceAlpha,
-                    //    },
-                    //    alpha: BlendingFunction::Addition {
-                    //        source:      F::Zero,
-                    //        destination: F::One,
-                    //    },
-                    //    .. Default::default()
-                    //}
-                    blend: Blend {
-                        color: BlendingFunction::Addition {
-                            source:      F::SourceAlpha,
-                            destination: F::OneMinusSourceAlpha,
-                        },
-                        alpha: BlendingFunction::Addition {
-                            source:      F::One,
-                            destination: F::OneMinusSourceAlpha,
-                        },
-                        .. Default::default()
-                    }
-                }
-            } else {
-                Composition {
-                    mult:   [1.0, 1.0, 1.0, 1.0],
-                    screen: [0.0, 0.0, 0.0, 1.0],
-                    blend: Blend {
-                        color: BlendingFunction::Addition {
-                            source:      F::SourceAlpha,
-                            destination: F::OneMinusSourceAlpha,
-                        },
-                        alpha: BlendingFunction::Addition {
-                            source:      F::One,
-                            destination: F::OneMinusSourceAlpha,
-                        },
-                        .. Default::default()
-                    }
-                }
-            };
+        let blend = if constant_flags.contains(BLEND_ADD) {
+            BlendMode::Add
+        } else if constant_flags.contains(BLEND_MULT) {
+            BlendMode::Mult
+        } else {
+            BlendMode::Normal
+        };
+
+        let compose = Composition {
+            blend,
+            mult:   *drawable.multiply_color,
+            screen: *drawable.screen_color,
+        };
 
         //                       _                           _
         //    _ __ ___ _ __   __| | ___ _ __    ___  _ __ __| | ___ _ __
@@ -858,6 +1796,15 @@ impl Drawable {
 
         let mask_inverted = constant_flags.contains(MASK_INV);
 
+        //              _ _ _
+        //    ___ _   _| | (_)_ __   __ _
+        //   / __| | | | | | | '_ \ / _` |
+        //  | (__| |_| | | | | | | | (_| |
+        // (_)___|\__,_|_|_|_|_| |_|\__, |
+        //                          |___/
+
+        let two_sided = constant_flags.contains(TWO_SIDED);
+
         //           _
         //  _ __ ___| |_ _   _ _ __ _ __
         // | '__/ _ \ __| | | | '__| '_ \
@@ -866,12 +1813,13 @@ impl Drawable {
 
         Ok(Drawable {
             index,
-            vertex_buffer,
-            index_buffer,
+            mesh,
             visible,
             compose,
             order,
             mask_inverted,
+            two_sided,
+            colors: ColorConfig::default(),
         })
     }
 
@@ -888,14 +1836,9 @@ impl Drawable {
         let flags = drawable.dynamic_flags;
 
         if flags.contains(VERTS_CHANGED) {
-            let vertices: Vec<_> =
-                zip(drawable.vertex_positions,
-                    drawable.vertex_uvs)
-                .map(|(pos, uv)| Vert{
-                    position:   *pos,
-                    texture_uv: *uv,
-                }).collect();
-            self.vertex_buffer.write(&vertices);
+            D::write(&mut self.mesh,
+                     drawable.vertex_positions,
+                     drawable.vertex_uvs);
         }
 
         if flags.contains(ORDER_CHANGED) {self.order = drawable.render_order;}
@@ -907,27 +1850,584 @@ impl Drawable {
     }
 }
 
-// __     __        _
-// \ \   / /__ _ __| |_ _____  __
-//  \ \ / / _ \ '__| __/ _ \ \/ /
-//   \ V /  __/ |  | ||  __/>  <
-//    \_/ \___|_|   \__\___/_/\_\
+//  ___    _ _
+// |_ _|__| | | ___
+//  | |/ _` | |/ _ \
+//  | | (_| | |  __/
+// |___\__,_|_|\___|
+
+fn idle(motion: &Option<(String, String)>) -> (Name, Name)
+{
+    match motion.clone() {
+        Some((c, m)) => (Rc::new(c),
+                         Rc::new(m)),
+        None         => (Rc::new("".to_string()),
+                         Rc::new("idle".to_string()))
+    }
+}
+
+//  ____            _
+// |  _ \ __ _ _ __| |_ ___   _ _
+// | |_) / _` | '__| __/ __| (_|_)
+// |  __/ (_| | |  | |_\__ \  _ _
+// |_|   \__,_|_|   \__|___/ (_|_)
+
+impl Parts {
+
+    //  _ _   _ __   _____      __
+    // (_|_) | '_ \ / _ \ \ /\ / /
+    //  _ _  | | | |  __/\ V  V /
+    // (_|_) |_| |_|\___| \_/\_/
+
+    // Configured opacities are there from the start, not faded in
+    fn new(model:   &core::Model,
+           targets: &HashMap<String, f32>) -> Self
+    {
+        let shown =
+            model.part_ids().iter()
+            .map(|id| match targets.get(*id) {
+                Some(o) => [o.clamp(0., 1.), 1.],
+                None    => [0., 0.],
+            })
+            .collect();
+
+        Self {
+            targets: targets.clone(),
+            shown,
+        }
+    }
+
+    //                          _
+    //  _ _    __ _ _ __  _ __ | |_   _
+    // (_|_)  / _` | '_ \| '_ \| | | | |
+    //  _ _  | (_| | |_) | |_) | | |_| |
+    // (_|_)  \__,_| .__/| .__/|_|\__, |
+    //             |_|   |_|      |___/
+
+    // Fades towards the overrides, or back to the model where there are none
+    fn apply(&mut self,
+             model: &mut core::Model,
+             base:  &[f32],
+             dt:    f32)
+    {
+        let step = dt / PART_FADE;
+        let targets: Vec<_> =
+            model.part_ids().iter()
+            .map(|id| self.targets.get(*id).copied())
+            .collect();
+
+        let parts = zip(&mut self.shown, base).zip(targets);
+        for (o, ((shown, base), target)) in zip(model.part_opacities_mut(),
+                                                parts) {
+            let [value, weight] = shown;
+            if *weight == 0. {*value = *base}
+
+            match target {
+                Some(t) => {
+                    *value = approach(*value, t, step);
+                    *weight = approach(*weight, 1., step);
+                }
+                None    => *weight = approach(*weight, 0., step),
+            }
+
+            *o = *base + (*value - *base) * *weight;
+        }
+    }
+}
+
+//  ____
+// |  _ \ __ _ _ __ __ _ _ __ ___  ___   _ _
+// | |_) / _` | '__/ _` | '_ ` _ \/ __| (_|_)
+// |  __/ (_| | | | (_| | | | | | \__ \  _ _
+// |_|   \__,_|_|  \__,_|_| |_| |_|___/ (_|_)
+
+impl Params {
+
+    //                          _
+    //  _ _    __ _ _ __  _ __ | |_   _
+    // (_|_)  / _` | '_ \| '_ \| | | | |
+    //  _ _  | (_| | |_) | |_) | | |_| |
+    // (_|_)  \__,_| .__/| .__/|_|\__, |
+    //             |_|   |_|      |___/
+
+    fn apply(&mut self,
+             model: &mut core::Model,
+             dt:    f32)
+    {
+        if self.drives.is_empty() {return}
+
+        let indices: Vec<_> =
+            self.drives.keys()
+            .map(|id| model.parameter_ids().iter().position(|p| p == id))
+            .collect();
+        let [min, max] = [model.parameter_min().to_vec(),
+                          model.parameter_max().to_vec()];
+        let values = model.parameter_values_mut();
+
+        for (drive, i) in zip(self.drives.values_mut(), indices) {
+            drive.tick(dt);
+
+            let i = match i {
+                Some(i) => i,
+                None    => continue,
+            };
+            let [value, weight] = drive.now;
+            let motion = values[i];
+
+            let driven = match drive.hold {
+                true  => motion + (value - motion) * weight,
+                false => motion + value * weight,
+            };
+            values[i] = driven.clamp(min[i], max[i]);
+        }
+
+        self.drives.retain(|_, d| !d.is_done());
+    }
+}
+
+//  __  __                 _
+// |  \/  | ___   ___   __| |___   _ _
+// | |\/| |/ _ \ / _ \ / _` / __| (_|_)
+// | |  | | (_) | (_) | (_| \__ \  _ _
+// |_|  |_|\___/ \___/ \__,_|___/ (_|_)
+
+impl Moods {
+
+    //  _ _   _ __   _____      __
+    // (_|_) | '_ \ / _ \ \ /\ / /
+    //  _ _  | | | |  __/\ V  V /
+    // (_|_) |_| |_|\___| \_/\_/
+
+    // Taken over from the model, which would apply them with its own update
+    fn new(model:  &mut UserModel,
+           model3: &Model3) -> Self
+    {
+        let controller =
+            model.controllers_map_mut()
+            .remove::<ExpressionController>()
+            .map(|c| *c)
+            .unwrap_or_default();
+
+        Self {
+            controller,
+            names:   model3.file_references.expressions.iter()
+                     .map(|e| e.name.clone())
+                     .collect(),
+            current: None,
+        }
+    }
+
+    //                 _
+    //  _ _   ___  ___| |_
+    // (_|_) / __|/ _ \ __|
+    //  _ _  \__ \  __/ |_
+    // (_|_) |___/\___|\__|
+
+    fn set(&mut self,
+           name: Option<&str>)
+    {
+        // Names that are not there set nothing
+        self.controller.set_expression(name.unwrap_or(""));
+        self.current = name.map(str::to_string);
+    }
+
+    //        _ _     _
+    //  _ _  | (_)___| |_
+    // (_|_) | | / __| __|
+    //  _ _  | | \__ \ |_
+    // (_|_) |_|_|___/\__|
+
+    fn list(&self) -> String
+    {
+        self.names.iter()
+        .map(|n| {
+            let mark = match self.current.as_ref() == Some(n) {
+                true  => "*",
+                false => " ",
+            };
+
+            format!("{mark} {n}\n")
+        })
+        .collect()
+    }
+
+    //                          _
+    //  _ _    __ _ _ __  _ __ | |_   _
+    // (_|_)  / _` | '_ \| '_ \| | | | |
+    //  _ _  | (_| | |_) | |_) | | |_| |
+    // (_|_)  \__,_| .__/| .__/|_|\__, |
+    //             |_|   |_|      |___/
+
+    fn apply(&mut self,
+             model: &mut core::Model,
+             dt:    f32)
+    {
+        if self.current.is_none() {return}
+
+        self.controller.update_parameters(model,
+                                          dt);
+    }
+}
+
+//  ____  _ _       _
+// | __ )| (_)_ __ | | __  _ _
+// |  _ \| | | '_ \| |/ / (_|_)
+// | |_) | | | | | |   <   _ _
+// |____/|_|_|_| |_|_|\_\ (_|_)
+
+impl Blink {
+
+    //  _ _   _ __   _____      __
+    // (_|_) | '_ \ / _ \ \ /\ / /
+    //  _ _  | | | |  __/\ V  V /
+    // (_|_) |_| |_|\___| \_/\_/
+
+    // Taken over from the model, which would run it with its own update
+    fn new(model:  &mut UserModel,
+           config: &Option<BlinkConfig>) -> Self
+    {
+        let controller =
+            model.controllers_map_mut()
+            .remove::<EyeBlink>()
+            .map(|c| *c);
+
+        let mut blink = Self {
+            controller,
+            on: false,
+        };
+        blink.configure(config);
+        blink
+    }
 
-#[derive(Copy, Clone, Debug)]
-pub struct Vert {
-    position:   [f32; 2],
-    texture_uv: [f32; 2],
+    //                         __ _
+    //  _ _    ___ ___  _ __  / _(_) __ _ _   _ _ __ ___
+    // (_|_)  / __/ _ \| '_ \| |_| |/ _` | | | | '__/ _ \
+    //  _ _  | (_| (_) | | | |  _| | (_| | |_| | | |  __/
+    // (_|_)  \___\___/|_| |_|_| |_|\__, |\__,_|_|  \___|
+    //                              |___/
+
+    fn configure(&mut self,
+                 config: &Option<BlinkConfig>)
+    {
+        self.on = config.is_some();
+
+        if let (Some(c), Some(b)) = (config, &mut self.controller) {
+            b.set_timings(c.interval.max(0.),
+                          c.closed.max(0.),
+                          c.opening.max(0.),
+                          c.closing.max(0.));
+        }
+    }
+
+    //                          _
+    //  _ _    __ _ _ __  _ __ | |_   _
+    // (_|_)  / _` | '_ \| '_ \| | | | |
+    //  _ _  | (_| | |_) | |_) | | |_| |
+    // (_|_)  \__,_| .__/| .__/|_|\__, |
+    //             |_|   |_|      |___/
+
+    fn apply(&mut self,
+             model: &mut core::Model,
+             dt:    f32)
+    {
+        if !self.on {return}
+
+        if let Some(b) = &mut self.controller {
+            b.update_parameters(model,
+                                dt);
+        }
+    }
 }
 
-implement_vertex!(Vert, position, texture_uv);
+//  ____       _
+// |  _ \ _ __(_)_   _____   _ _
+// | | | | '__| \ \ / / _ \ (_|_)
+// | |_| | |  | |\ V /  __/  _ _
+// |____/|_|  |_| \_/ \___| (_|_)
+
+impl Drive {
 
-impl Vert {
-    pub fn new(position:   [f32; 2],
-               texture_uv: [f32; 2]) -> Self
+    //  _ _   _ __   _____      __
+    // (_|_) | '_ \ / _ \ \ /\ / /
+    //  _ _  | | | |  __/\ V  V /
+    // (_|_) |_| |_|\___| \_/\_/
+
+    fn new(value:  f32,
+           puppet: &Puppet) -> Self
+    {
+        let mut drive = Self {
+            from:     [value, 0.],
+            to:       [value, 0.],
+            now:      [value, 0.],
+            elapsed:  0.,
+            over:     0.,
+            ease:     puppet.ease,
+            hold:     puppet.hold,
+            released: false,
+        };
+        drive.set(value, puppet);
+
+        drive
+    }
+
+    //                 _
+    //  _ _   ___  ___| |_
+    // (_|_) / __|/ _ \ __|
+    //  _ _  \__ \  __/ |_
+    // (_|_) |___/\___|\__|
+
+    // Weight fades in from nothing, so there is no jump between held and
+    // added values either
+    fn set(&mut self,
+           value:  f32,
+           puppet: &Puppet)
+    {
+        if self.hold != puppet.hold {self.now = [value, 0.]}
+        if self.now[1] == 0. {self.now[0] = value}
+
+        *self = Self {
+            from:     self.now,
+            to:       [value, 1.],
+            elapsed:  0.,
+            over:     puppet.over,
+            ease:     puppet.ease,
+            hold:     puppet.hold,
+            released: false,
+            ..*self
+        };
+    }
+
+    //                 _
+    //  _ _   _ __ ___| | ___  __ _ ___  ___
+    // (_|_) | '__/ _ \ |/ _ \/ _` / __|/ _ \
+    //  _ _  | | |  __/ |  __/ (_| \__ \  __/
+    // (_|_) |_|  \___|_|\___|\__,_|___/\___|
+
+    fn release(&mut self,
+               puppet: &Puppet)
+    {
+        *self = Self {
+            from:     self.now,
+            to:       [self.now[0], 0.],
+            elapsed:  0.,
+            over:     puppet.over,
+            ease:     puppet.ease,
+            released: true,
+            ..*self
+        };
+    }
+
+    //        _   _      _
+    //  _ _  | |_(_) ___| | __
+    // (_|_) | __| |/ __| |/ /
+    //  _ _  | |_| | (__|   <
+    // (_|_)  \__|_|\___|_|\_\
+
+    fn tick(&mut self,
+            dt: f32)
+    {
+        self.elapsed += dt;
+
+        let t = ease(self.ease,
+                     self.progress());
+        self.now = [0, 1].map(|i| {
+            self.from[i] + (self.to[i] - self.from[i]) * t
+        });
+    }
+
+    //  _ _   _ __  _ __ ___   __ _ _ __ ___  ___ ___
+    // (_|_) | '_ \| '__/ _ \ / _` | '__/ _ \/ __/ __|
+    //  _ _  | |_) | | | (_) | (_| | | |  __/\__ \__ \
+    // (_|_) | .__/|_|  \___/ \__, |_|  \___||___/___/
+    //       |_|              |___/
+
+    fn progress(&self) -> f32
+    {
+        match self.over > 0. {
+            true  => (self.elapsed / self.over).min(1.),
+            false => 1.,
+        }
+    }
+
+    fn is_done(&self) -> bool {self.released && self.progress() >= 1.}
+
+    //                 _   _   _          _
+    //  _ _   ___  ___| |_| |_| | ___  __| |
+    // (_|_) / __|/ _ \ __| __| |/ _ \/ _` |
+    //  _ _  \__ \  __/ |_| |_| |  __/ (_| |
+    // (_|_) |___/\___|\__|\__|_|\___|\__,_|
+
+    // Where the tween ends, for a model loaded anew
+    fn settled(&self) -> Self
     {
         Self {
-            position,
-            texture_uv,
+            from:    self.to,
+            now:     self.to,
+            elapsed: self.over,
+            ..*self
         }
     }
 }
+
+//  __  __       _   _             ____        _
+// |  \/  | ___ | |_(_) ___  _ __ |  _ \  __ _| |_ __ _   _ _
+// | |\/| |/ _ \| __| |/ _ \| '_ \| | | |/ _` | __/ _` | (_|_)
+// | |  | | (_) | |_| | (_) | | | | |_| | (_| | || (_| |  _ _
+// |_|  |_|\___/ \__|_|\___/|_| |_|____/ \__,_|\__\__,_| (_|_)
+
+impl MotionData {
+
+    //        _                 _
+    //  _ _  | | ___   __ _  __| |
+    // (_|_) | |/ _ \ / _` |/ _` |
+    //  _ _  | | (_) | (_| | (_| |
+    // (_|_) |_|\___/ \__,_|\__,_|
+
+    fn load(path:  &Path,
+            sound: Option<PathBuf>) -> Result<Self, Box<dyn Error>>
+    {
+        let motion3 = {
+            let file = File::open(path)?;
+            Motion3::from_reader(file)?
+        };
+
+        let looped   = motion3.meta.looped;
+        let duration = motion3.meta.duration;
+        let motion   = Motion::new(motion3);
+
+        Ok(Self {
+            looped,
+            duration,
+            motion,
+            sound,
+            clip: None,
+        })
+    }
+
+    //            _                    _
+    //  _ _    __| | ___  ___ ___   __| | ___
+    // (_|_)  / _` |/ _ \/ __/ _ \ / _` |/ _ \
+    //  _ _  | (_| |  __/ (_| (_) | (_| |  __/
+    // (_|_)  \__,_|\___|\___\___/ \__,_|\___|
+
+    // Sound that fails is reported every time the motion starts. Raw ones are
+    // taken as the defaults have them, models hardly come with any
+    fn decode(&mut self)
+    {
+        if self.clip.is_some() {return}
+        let sound = match &self.sound {
+            Some(s) => s,
+            None    => return,
+        };
+
+        self.clip =
+            Clip::load(sound,
+                       &PcmConfig::default())
+            .map_err(|e| eprintln!("Failed to load sound {}: {e}",
+                                   sound.display()))
+            .ok();
+    }
+}
+
+//  _   _
+// | \ | | __ _ _ __ ___   ___  ___
+// |  \| |/ _` | '_ ` _ \ / _ \/ __|
+// | |\  | (_| | | | | | |  __/\__ \
+// |_| \_|\__,_|_| |_| |_|\___||___/
+
+fn names(path: &Path) -> Result<HashMap<String, String>, Box<dyn Error>>
+{
+    let cdi3 = Cdi3::from_reader(File::open(path)?)?;
+
+    Ok(cdi3.parts.into_iter()
+       .map(|p| (p.id, p.name))
+       .collect())
+}
+
+//     _                                     _
+//    / \   _ __  _ __  _ __ ___   __ _  ___| |__
+//   / _ \ | '_ \| '_ \| '__/ _ \ / _` |/ __| '_ \
+//  / ___ \| |_) | |_) | | | (_) | (_| | (__| | | |
+// /_/   \_\ .__/| .__/|_|  \___/ \__,_|\___|_| |_|
+//         |_|   |_|
+
+// Step towards a value, not past it
+pub fn approach(from: f32,
+                to:   f32,
+                step: f32) -> f32
+{
+    from + (to - from).clamp(-step, step)
+}
+
+//  _____
+// | ____|__ _ ___  ___
+// |  _| / _` / __|/ _ \
+// | |__| (_| \__ \  __/
+// |_____\__,_|___/\___|
+
+// Time in [0; 1] to how far along a tween is
+fn ease(ease: Ease,
+        t:    f32) -> f32
+{
+    match ease {
+        Ease::Linear => t,
+        Ease::In     => t * t,
+        Ease::Out    => 1. - (1. - t) * (1. - t),
+        Ease::InOut  => match t < 0.5 {
+            true  => 2. * t * t,
+            false => 1. - 2. * (1. - t) * (1. - t),
+        },
+    }
+}
+
+//  _____         _
+// |_   _|____  _| |_ _   _ _ __ ___
+//   | |/ _ \ \/ / __| | | | '__/ _ \
+//   | |  __/>  <| |_| |_| | | |  __/
+//   |_|\___/_/\_\\__|\__,_|_|  \___|
+
+pub fn texture<D>(path:   &Path,
+                  device: &D) -> Result<D::Texture, Box<dyn Error>>
+where D: Device
+{
+    device.texture(image(path)?)
+}
+
+//  ___
+// |_ _|_ __ ___   __ _  __ _  ___
+//  | || '_ ` _ \ / _` |/ _` |/ _ \
+//  | || | | | | | (_| | (_| |  __/
+// |___|_| |_| |_|\__,_|\__, |\___|
+//                      |___/
+
+pub fn image(path: &Path) -> Result<RgbaImage, Box<dyn Error>>
+{
+    let mut image =
+        image::open(path)?
+        .to_rgba8();
+
+    premultiply(&mut image);
+
+    Ok(image)
+}
+
+//  ____                           _ _   _       _
+// |  _ \ _ __ ___ _ __ ___  _   _| | |_(_)_ __ | |_   _
+// | |_) | '__/ _ \ '_ ` _ \| | | | | __| | '_ \| | | | |
+// |  __/| | |  __/ | | | | | |_| | | |_| | |_) | | |_| |
+// |_|   |_|  \___|_| |_| |_|\__,_|_|\__|_| .__/|_|\__, |
+//                                        |_|      |___/
+
+// Premultiplied alpha is what the original framework expects, and it keeps
+// transparent texels from bleeding black into the edges when the texture is
+// filtered
+pub fn premultiply(image: &mut RgbaImage)
+{
+    image
+    .pixels_mut()
+    .for_each(|p| {
+        let a = p[3] as u32;
+        p.0[..3].iter_mut()
+        .for_each(|c| *c = ((*c as u32 * a + 127) / 255) as u8);
+    });
+}
@@ -0,0 +1,633 @@
+use std::collections::HashMap;
+use chrono::{Local, Timelike};
+use crate::config::{GraphConfig, EventConfig};
+
+// Motions of a state, each with its weight
+type Pool = Vec<((String, String), f32)>;
+
+//   ____                 _
+//  / ___|_ __ __ _ _ __ | |__
+// | |  _| '__/ _` | '_ \| '_ \
+// | |_| | | | (_| | |_) | | | |
+//  \____|_|  \__,_| .__/|_| |_|
+//                 |_|
+
+// States the model goes through, each with motions picked by their weights
+pub struct Graph {
+    states:      HashMap<String, Pool>,
+    transitions: Vec<Transition>,
+    fade:        f32,
+    current:     String,
+    // Seconds in the current state, and since anyone did anything
+    elapsed:     f32,
+    inactive:    f32,
+}
+
+//  _____                    _ _   _
+// |_   _| __ __ _ _ __  ___(_) |_(_) ___  _ __
+//   | || '__/ _` | '_ \/ __| | __| |/ _ \| '_ \
+//   | || | | (_| | | | \__ \ | |_| | (_) | | | |
+//   |_||_|  \__,_|_| |_|___/_|\__|_|\___/|_| |_|
+
+struct Transition {
+    from: Vec<String>,
+    to:   String,
+    on:   On,
+    fade: f32,
+}
+
+// Times of day are in minutes since midnight
+enum On {
+    Hit(String),
+    Trigger(String),
+    Timer(f32),
+    Inactive(f32),
+    Active,
+    Time([u32; 2]),
+    End,
+}
+
+//  _____                 _
+// | ____|_   _____ _ __ | |_
+// |  _| \ \ / / _ \ '_ \| __|
+// | |___ \ V /  __/ | | | |_
+// |_____| \_/ \___|_| |_|\__|
+
+// What the graph is told about as it happens
+pub enum Event<'a> {
+    Hit(&'a str),
+    Trigger(&'a str),
+    Active,
+}
+
+//  ____  _
+// / ___|| |_ ___ _ __
+// \___ \| __/ _ \ '_ \
+//  ___) | ||  __/ |_) |
+// |____/ \__\___| .__/
+//               |_|
+
+// Motion to go to, faded into over seconds
+pub struct Step {
+    pub motion: (String, String),
+    pub fade:   f32,
+}
+
+impl Graph {
+
+    //  _ _   _ __   _____      __
+    // (_|_) | '_ \ / _ \ \ /\ / /
+    //  _ _  | | | |  __/\ V  V /
+    // (_|_) |_| |_|\___| \_/\_/
+
+    // No graph without states
+    pub fn new(config: &GraphConfig) -> Option<Self>
+    {
+        if config.states.is_empty() {return None}
+
+        let states: HashMap<_, Pool> =
+            config.states.iter()
+            .map(|(name, s)| (name.clone(),
+                              s.motions.iter()
+                              .filter(|p| p.weight > 0.)
+                              .map(|p| (p.motion.clone(), p.weight))
+                              .collect()))
+            .collect();
+
+        let transitions =
+            config.transitions.iter()
+            .filter_map(|t| {
+                if !states.contains_key(&t.to) {
+                    eprintln!("No state {} to go to", t.to);
+                    return None
+                }
+
+                let on = match &t.on {
+                    EventConfig::Hit(n)      => On::Hit(n.clone()),
+                    EventConfig::Trigger(n)  => On::Trigger(n.clone()),
+                    EventConfig::Timer(s)    => On::Timer(*s),
+                    EventConfig::Inactive(s) => On::Inactive(*s),
+                    EventConfig::Active      => On::Active,
+                    EventConfig::End         => On::End,
+                    EventConfig::Time([a, b]) => match (minutes(a),
+                                                        minutes(b)) {
+                        (Some(a), Some(b)) => On::Time([a, b]),
+                        _                  => {
+                            eprintln!("Unexpected time of day {a} to {b}");
+                            return None
+                        }
+                    },
+                };
+
+                Some(Transition {
+                    from: t.from.clone(),
+                    to:   t.to.clone(),
+                    on,
+                    fade: t.fade.unwrap_or(config.fade),
+                })
+            })
+            .collect();
+
+        let current = match states.contains_key(&config.start) {
+            true  => config.start.clone(),
+            false => {
+                let first =
+                    states.keys()
+                    .min()
+                    .cloned()
+                    .unwrap_or_default();
+                eprintln!("No state {} to start in, starting in {first}",
+                          config.start);
+                first
+            }
+        };
+
+        Some(Self {
+            states,
+            transitions,
+            fade:     config.fade,
+            current,
+            elapsed:  0.,
+            inactive: 0.,
+        })
+    }
+
+    //        _       _               _ _
+    //  _ _  (_)_ __ | |__   ___ _ __(_) |_
+    // (_|_) | | '_ \| '_ \ / _ \ '__| | __|
+    //  _ _  | | | | | | | |  __/ |  | | |_
+    // (_|_) |_|_| |_|_| |_|\___|_|  |_|\__|
+
+    // Stays where the old graph was, as long as there is such a state
+    pub fn inherit(&mut self,
+                   old: &Self)
+    {
+        if !self.states.contains_key(&old.current) {return}
+
+        self.current = old.current.clone();
+        self.elapsed = old.elapsed;
+        self.inactive = old.inactive;
+    }
+
+    //            _        _
+    //  _ _   ___| |_ __ _| |_ ___
+    // (_|_) / __| __/ _` | __/ _ \
+    //  _ _  \__ \ || (_| | ||  __/
+    // (_|_) |___/\__\__,_|\__\___|
+
+    pub fn state(&self) -> &str {&self.current}
+
+    //        _ _     _
+    //  _ _  | (_)___| |_
+    // (_|_) | | / __| __|
+    //  _ _  | | \__ \ |_
+    // (_|_) |_|_|___/\__|
+
+    pub fn list(&self) -> String
+    {
+        let mut names: Vec<_> = self.states.keys().collect();
+        names.sort();
+
+        names.into_iter()
+        .map(|n| {
+            let mark = match *n == self.current {
+                true  => "*",
+                false => " ",
+            };
+
+            format!("{mark} {n}\n")
+        })
+        .collect()
+    }
+
+    //                 _
+    //  _ _   ___  ___| |_
+    // (_|_) / __|/ _ \ __|
+    //  _ _  \__ \  __/ |_
+    // (_|_) |___/\___|\__|
+
+    pub fn set(&mut self,
+               state: &str) -> Result<Option<Step>, String>
+    {
+        if !self.states.contains_key(state) {
+            return Err(format!("No state {state}"))
+        }
+
+        Ok(self.enter(state,
+                      self.fade))
+    }
+
+    //        _   _      _
+    //  _ _  | |_(_) ___| | __
+    // (_|_) | __| |/ __| |/ /
+    //  _ _  | |_| | (__|   <
+    // (_|_)  \__|_|\___|_|\_\
+
+    // Timers, inactivity and times of day never go back into the state the
+    // model is in, or they would keep going off
+    pub fn tick(&mut self,
+                dt: f32) -> Option<Step>
+    {
+        self.elapsed += dt;
+        self.inactive += dt;
+
+        let now = || {
+            let time = Local::now();
+            time.hour() * 60 + time.minute()
+        };
+
+        let (to, fade) =
+            self.transitions.iter()
+            .filter(|t| self.leaves(t) && t.to != self.current)
+            .find(|t| match t.on {
+                On::Timer(s)    => self.elapsed >= s,
+                On::Inactive(s) => self.inactive >= s,
+                On::Time(w)     => within(now(), w),
+                _               => false,
+            })
+            .map(|t| (t.to.clone(), t.fade))?;
+
+        self.enter(&to,
+                   fade)
+    }
+
+    //  _ _    ___ _   _  ___
+    // (_|_)  / __| | | |/ _ \
+    //  _ _  | (__| |_| |  __/
+    // (_|_)  \___|\__,_|\___|
+
+    // Hits and triggers may start the state the model is in over
+    pub fn cue(&mut self,
+               event: Event) -> Option<Step>
+    {
+        if let Event::Active = event {self.inactive = 0.}
+
+        let (to, fade) =
+            self.transitions.iter()
+            .filter(|t| self.leaves(t))
+            .find(|t| match (&t.on, &event) {
+                (On::Hit(a), Event::Hit(b))         => a == b,
+                (On::Trigger(a), Event::Trigger(b)) => a == b,
+                (On::Active, Event::Active)         => t.to != self.current,
+                _                                   => false,
+            })
+            .map(|t| (t.to.clone(), t.fade))?;
+
+        self.enter(&to,
+                   fade)
+    }
+
+    //                        _
+    //  _ _   _ __   _____  _| |_
+    // (_|_) | '_ \ / _ \ \/ / __|
+    //  _ _  | | | |  __/>  <| |_
+    // (_|_) |_| |_|\___/_/\_\\__|
+
+    // Motion of the state ended, so either the state is left or another of
+    // its motions is picked
+    pub fn next(&mut self) -> Option<Step>
+    {
+        let end =
+            self.transitions.iter()
+            .filter(|t| self.leaves(t) && t.to != self.current)
+            .find(|t| matches!(t.on, On::End))
+            .map(|t| (t.to.clone(), t.fade));
+
+        match end {
+            Some((to, fade)) => self.enter(&to,
+                                           fade),
+            None             => self.pick(self.fade),
+        }
+    }
+
+    //                   _
+    //  _ _    ___ _ __ | |_ ___ _ __
+    // (_|_)  / _ \ '_ \| __/ _ \ '__|
+    //  _ _  |  __/ | | | ||  __/ |
+    // (_|_)  \___|_| |_|\__\___|_|
+
+    fn enter(&mut self,
+             state: &str,
+             fade:  f32) -> Option<Step>
+    {
+        self.current = state.to_string();
+        self.elapsed = 0.;
+        eprintln!("Entered state {state}");
+
+        self.pick(fade)
+    }
+
+    //              _      _
+    //  _ _   _ __ (_) ___| | __
+    // (_|_) | '_ \| |/ __| |/ /
+    //  _ _  | |_) | | (__|   <
+    // (_|_) | .__/|_|\___|_|\_\
+    //       |_|
+
+    fn pick(&self,
+            fade: f32) -> Option<Step>
+    {
+        let pool = self.states.get(&self.current)?;
+
+        let total: f32 = pool.iter().map(|(_, w)| w).sum();
+        let mut r = fastrand::f32() * total;
+
+        pool.iter()
+        .find(|(_, w)| {r -= w; r < 0.})
+        .or(pool.last())
+        .map(|(m, _)| Step {
+            motion: m.clone(),
+            fade,
+        })
+    }
+
+    //        _
+    //  _ _  | | ___  __ ___   _____  ___
+    // (_|_) | |/ _ \/ _` \ \ / / _ \/ __|
+    //  _ _  | |  __/ (_| |\ V /  __/\__ \
+    // (_|_) |_|\___|\__,_| \_/ \___||___/
+
+    fn leaves(&self,
+              transition: &Transition) -> bool
+    {
+        transition.from.is_empty()
+        || transition.from.contains(&self.current)
+    }
+}
+
+//  __  __ _             _
+// |  \/  (_)_ __  _   _| |_ ___  ___
+// | |\/| | | '_ \| | | | __/ _ \/ __|
+// | |  | | | | | | |_| | ||  __/\__ \
+// |_|  |_|_|_| |_|\__,_|\__\___||___/
+
+// Time of day as in 22:00
+fn minutes(time: &str) -> Option<u32>
+{
+    let (h, m) = time.split_once(':')?;
+    let h: u32 = h.trim().parse().ok().filter(|h| *h < 24)?;
+    let m: u32 = m.trim().parse().ok().filter(|m| *m < 60)?;
+
+    Some(h * 60 + m)
+}
+
+// __        ___ _   _     _
+// \ \      / (_) |_| |__ (_)_ __
+//  \ \ /\ / /| | __| '_ \| | '_ \
+//   \ V  V / | | |_| | | | | | | |
+//    \_/\_/  |_|\__|_| |_|_|_| |_|
+
+// Windows that go past midnight wrap around it
+fn within(now:    u32,
+          window: [u32; 2]) -> bool
+{
+    let [from, to] = window;
+
+    match from <= to {
+        true  => from <= now && now < to,
+        false => from <= now || now < to,
+    }
+}
+
+//  _____         _
+// |_   _|__  ___| |_ ___
+//   | |/ _ \/ __| __/ __|
+//   | |  __/\__ \ |_\__ \
+//   |_|\___||___/\__|___/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Whole day, which no time of day is outside of
+    const DAY: [u32; 2] = [0, 24 * 60];
+
+    // States of one motion each, named after the state
+    fn graph(start:       &str,
+             transitions: Vec<(&[&str], &str, On)>) -> Graph
+    {
+        let states =
+            ["idle", "sleep", "wave"].iter()
+            .map(|s| (s.to_string(),
+                      vec![((s.to_string(), "0".to_string()), 1.)]))
+            .collect();
+
+        let transitions =
+            transitions.into_iter()
+            .map(|(from, to, on)| Transition {
+                from: from.iter().map(|s| s.to_string()).collect(),
+                to:   to.to_string(),
+                on,
+                fade: 0.5,
+            })
+            .collect();
+
+        Graph {
+            states,
+            transitions,
+            fade:     1.,
+            current:  start.to_string(),
+            elapsed:  0.,
+            inactive: 0.,
+        }
+    }
+
+    fn entered(step: Option<Step>) -> Option<String>
+    {
+        step.map(|s| s.motion.0)
+    }
+
+    //                  _             _
+    //  _ _   _ __ ___ (_)_ __  _   _| |_ ___  ___
+    // (_|_) | '_ ` _ \| | '_ \| | | | __/ _ \/ __|
+    //  _ _  | | | | | | | | | | |_| | ||  __/\__ \
+    // (_|_) |_| |_| |_|_|_| |_|\__,_|\__\___||___/
+
+    #[test]
+    fn minutes_parse()
+    {
+        assert_eq!(minutes("22:00"), Some(1320));
+        assert_eq!(minutes(" 7:05"), Some(425));
+        assert_eq!(minutes("0:00"), Some(0));
+        assert_eq!(minutes("23:59"), Some(1439));
+
+        for time in ["24:00", "12:60", "12", "noon", "-1:00", ""] {
+            assert_eq!(minutes(time), None, "{time}");
+        }
+    }
+
+    //                 _ _   _     _
+    //  _ _  __      _(_) |_| |__ (_)_ __
+    // (_|_) \ \ /\ / / | __| '_ \| | '_ \
+    //  _ _   \ V  V /| | |_| | | | | | | |
+    // (_|_)   \_/\_/ |_|\__|_| |_|_|_| |_|
+
+    #[test]
+    fn within_windows()
+    {
+        let day = [540, 1020];
+        assert!(within(540, day));
+        assert!(within(1019, day));
+        assert!(!within(1020, day));
+        assert!(!within(0, day));
+
+        // Past midnight
+        let night = [1320, 360];
+        for now in [1320, 1439, 0, 359] {
+            assert!(within(now, night), "{now}");
+        }
+        for now in [360, 720, 1319] {
+            assert!(!within(now, night), "{now}");
+        }
+    }
+
+    //              _      _
+    //  _ _   _ __ (_) ___| | __
+    // (_|_) | '_ \| |/ __| |/ /
+    //  _ _  | |_) | | (__|   <
+    // (_|_) | .__/|_|\___|_|\_\
+    //       |_|
+
+    // Motions come up about as often as their weights say
+    #[test]
+    fn pick_weighted()
+    {
+        let mut graph = graph("idle", Vec::new());
+        graph.states.insert("idle".to_string(),
+                            vec![(("Idle".to_string(), "a".to_string()), 3.),
+                                 (("Idle".to_string(), "b".to_string()), 1.)]);
+        fastrand::seed(7);
+
+        let picks = 4000;
+        let a =
+            (0..picks)
+            .filter_map(|_| graph.pick(1.))
+            .filter(|s| s.motion.1 == "a")
+            .count();
+        let share = a as f32 / picks as f32;
+        assert!((share - 0.75).abs() < 0.05, "picked a {share} of the time");
+
+        let step = graph.pick(0.25).unwrap();
+        assert_eq!(step.fade, 0.25);
+
+        graph.states.insert("idle".to_string(), Vec::new());
+        assert!(graph.pick(1.).is_none());
+    }
+
+    //        _   _      _
+    //  _ _  | |_(_) ___| | __
+    // (_|_) | __| |/ __| |/ /
+    //  _ _  | |_| | (__|   <
+    // (_|_)  \__|_|\___|_|\_\
+
+    #[test]
+    fn tick_timer()
+    {
+        let mut graph = graph("idle", vec![(&[], "sleep", On::Timer(2.))]);
+
+        assert!(graph.tick(1.).is_none());
+        let step = graph.tick(1.5).unwrap();
+        assert_eq!(step.motion.0, "sleep");
+        assert_eq!(step.fade, 0.5);
+        assert_eq!(graph.state(), "sleep");
+        assert_eq!(graph.elapsed, 0.);
+
+        // Not into the state it is already in
+        assert!(graph.tick(10.).is_none());
+        assert_eq!(graph.state(), "sleep");
+    }
+
+    #[test]
+    fn tick_inactive()
+    {
+        let mut graph = graph("idle", vec![(&[], "sleep", On::Inactive(5.)),
+                                           (&["sleep"], "idle", On::Active)]);
+
+        assert!(graph.tick(4.).is_none());
+        assert!(graph.cue(Event::Active).is_none());
+        assert!(graph.tick(4.).is_none());
+        assert_eq!(entered(graph.tick(1.)).as_deref(), Some("sleep"));
+        assert!(graph.tick(60.).is_none());
+
+        // Activity wakes it up and starts inactivity over
+        assert_eq!(entered(graph.cue(Event::Active)).as_deref(), Some("idle"));
+        assert_eq!(graph.inactive, 0.);
+    }
+
+    #[test]
+    fn tick_time()
+    {
+        let mut graph = graph("sleep", vec![(&[], "idle", On::Time(DAY))]);
+
+        assert_eq!(entered(graph.tick(0.)).as_deref(), Some("idle"));
+        assert!(graph.tick(0.).is_none());
+        assert_eq!(graph.state(), "idle");
+    }
+
+    // Only transitions from the state the model is in, or from any
+    #[test]
+    fn tick_from()
+    {
+        let mut graph = graph("idle", vec![(&["wave"], "sleep", On::Timer(1.)),
+                                           (&["idle"], "wave", On::Timer(2.))]);
+
+        assert!(graph.tick(1.5).is_none());
+        assert_eq!(entered(graph.tick(1.)).as_deref(), Some("wave"));
+        assert_eq!(entered(graph.tick(1.)).as_deref(), Some("sleep"));
+    }
+
+    //  _ _    ___ _   _  ___
+    // (_|_)  / __| | | |/ _ \
+    //  _ _  | (__| |_| |  __/
+    // (_|_)  \___|\__,_|\___|
+
+    #[test]
+    fn cue_events()
+    {
+        let transitions: Vec<(&[&str], _, _)> =
+            vec![(&[], "wave",  On::Hit("Head".into())),
+                 (&[], "sleep", On::Trigger("nap".into())),
+                 (&[], "sleep", On::Active)];
+        let mut graph = graph("idle", transitions);
+
+        assert!(graph.cue(Event::Hit("Body")).is_none());
+        assert!(graph.cue(Event::Trigger("wave")).is_none());
+        assert_eq!(entered(graph.cue(Event::Hit("Head"))).as_deref(),
+                   Some("wave"));
+
+        // Hits and triggers start the state over, activity does not
+        graph.elapsed = 3.;
+        assert_eq!(entered(graph.cue(Event::Hit("Head"))).as_deref(),
+                   Some("wave"));
+        assert_eq!(graph.elapsed, 0.);
+
+        assert_eq!(entered(graph.cue(Event::Trigger("nap"))).as_deref(),
+                   Some("sleep"));
+        assert!(graph.cue(Event::Active).is_none());
+        assert_eq!(entered(graph.cue(Event::Trigger("nap"))).as_deref(),
+                   Some("sleep"));
+    }
+
+    //                        _
+    //  _ _   _ __   _____  _| |_
+    // (_|_) | '_ \ / _ \ \/ / __|
+    //  _ _  | | | |  __/>  <| |_
+    // (_|_) |_| |_|\___/_/\_\\__|
+
+    #[test]
+    fn next_end()
+    {
+        let mut graph = graph("wave", vec![(&["wave"], "idle", On::End),
+                                           (&["idle"], "idle", On::End)]);
+
+        assert_eq!(entered(graph.next()).as_deref(), Some("idle"));
+        assert_eq!(graph.state(), "idle");
+
+        // Another motion of the state instead, at the graph's fade
+        graph.elapsed = 3.;
+        let step = graph.next().unwrap();
+        assert_eq!(step.motion.0, "idle");
+        assert_eq!(step.fade, 1.);
+        assert_eq!(graph.elapsed, 3.);
+    }
+}
@@ -0,0 +1,371 @@
+use std::{
+    env,
+    thread,
+    error::Error,
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
+use cubism::json::model::Model3;
+use crate::{
+    message::Shelved,
+    render::Stage,
+    renderer::Raster,
+    config::{
+        Config,
+        FitConfig,
+        RotationConfig,
+        constant::APP_NAME,
+    },
+};
+
+pub const MODEL3: &str = ".model3.json";
+const THUMBNAIL: u32  = 256;
+
+//  _____       _
+// | ____|_ __ | |_ _ __ _   _
+// |  _| | '_ \| __| '__| | | |
+// | |___| | | | |_| |  | |_| |
+// |_____|_| |_|\__|_|   \__, |
+//                       |___/
+
+pub struct Entry {
+    pub name:      String,
+    pub file:      PathBuf,
+    pub textures:  Vec<PathBuf>,
+    pub classes:   Vec<String>,
+    pub thumbnail: PathBuf,
+}
+
+impl Entry {
+
+    //  _ _   _ __   _____      __
+    // (_|_) | '_ \ / _ \ \ /\ / /
+    //  _ _  | | | |  __/\ V  V /
+    // (_|_) |_| |_|\___| \_/\_/
+
+    fn new(file: PathBuf) -> Result<Self, Box<dyn Error>>
+    {
+        let model3 = Model3::from_reader(File::open(&file)?)?;
+        let dir = file.parent().ok_or("Model file has no directory")?;
+
+        let name =
+            file.file_name()
+            .and_then(|f| f.to_str())
+            .and_then(|f| f.strip_suffix(MODEL3))
+            .ok_or("Model file has no name")?
+            .to_string();
+
+        let refs = &model3.file_references;
+        let textures =
+            refs.textures.iter()
+            .map(|t| dir.join(t))
+            .collect();
+
+        let mut classes: Vec<_> = refs.motions.keys().cloned().collect();
+        classes.sort();
+
+        // Same file always gets the same thumbnail, whatever liver is built
+        // with
+        let hash = fnv(file.as_os_str().as_encoded_bytes());
+        let thumbnail = cache().join(format!("{hash:016x}.png"));
+
+        Ok(Self {
+            name,
+            file,
+            textures,
+            classes,
+            thumbnail,
+        })
+    }
+}
+
+//  _     _ _
+// | |   (_) |__  _ __ __ _ _ __ _   _
+// | |   | | '_ \| '__/ _` | '__| | | |
+// | |___| | |_) | | | (_| | |  | |_| |
+// |_____|_|_.__/|_|  \__,_|_|   \__, |
+//                               |___/
+
+pub struct Library {
+    entries: Vec<Entry>,
+    current: Option<usize>,
+}
+
+impl Library {
+
+    //  _ _   ___  ___ __ _ _ __
+    // (_|_) / __|/ __/ _` | '_ \
+    //  _ _  \__ \ (_| (_| | | | |
+    // (_|_) |___/\___\__,_|_| |_|
+
+    // Models that fail to parse are left out, not to lose the whole library
+    pub fn scan(config: &Config) -> Self
+    {
+        let mut files = Vec::new();
+        for p in &config.library.paths {
+            match expanduser::expanduser(p) {
+                Ok(p)  => find(&p, &mut files),
+                Err(e) => eprintln!("Failed to expand {p}: {e}"),
+            }
+        }
+        files.sort();
+        files.dedup();
+
+        let entries: Vec<_> =
+            files.into_iter()
+            .filter_map(|f| Entry::new(f.clone())
+                            .map_err(|e| eprintln!("Failed to add {}: {e}",
+                                                   f.display()))
+                            .ok())
+            .collect();
+
+        if !config.library.paths.is_empty() {
+            eprintln!("Found {} models in library", entries.len());
+        }
+
+        thumbnails(&entries);
+
+        Self {
+            entries,
+            current: None,
+        }
+    }
+
+    //        _                 _
+    //  _ _  | | ___   ___ __ _| |_ ___
+    // (_|_) | |/ _ \ / __/ _` | __/ _ \
+    //  _ _  | | (_) | (_| (_| | ||  __/
+    // (_|_) |_|\___/ \___\__,_|\__\___|
+
+    // Whatever is loaded, so that next knows where to go from
+    pub fn locate(&mut self,
+                  file: &Path)
+    {
+        self.current =
+            self.entries.iter()
+            .position(|e| e.file == file);
+    }
+
+    //        _ _     _
+    //  _ _  | (_)___| |_
+    // (_|_) | | / __| __|
+    //  _ _  | | \__ \ |_
+    // (_|_) |_|_|___/\__|
+
+    pub fn list(&self) -> String
+    {
+        self.entries.iter()
+        .enumerate()
+        .map(|(i, e)| {
+            let mark = if self.current == Some(i) {"*"} else {" "};
+            let thumbnail = match e.thumbnail.exists() {
+                true  => e.thumbnail.display().to_string(),
+                false => "-".to_string(),
+            };
+
+            format!("{mark} {}\t{}\t{} textures\t{}\t{}\n",
+                    e.name,
+                    e.classes.join(","),
+                    e.textures.len(),
+                    e.file.display(),
+                    thumbnail)
+        })
+        .collect()
+    }
+
+    //                            _      _
+    //  _ _   _ __ ___   ___   __| | ___| |___
+    // (_|_) | '_ ` _ \ / _ \ / _` |/ _ \ / __|
+    //  _ _  | | | | | | (_) | (_| |  __/ \__ \
+    // (_|_) |_| |_| |_|\___/ \__,_|\___|_|___/
+
+    pub fn models(&self) -> Vec<Shelved>
+    {
+        self.entries.iter()
+        .enumerate()
+        .map(|(i, e)| Shelved {
+            name:    e.name.clone(),
+            file:    e.file.clone(),
+            classes: e.classes.clone(),
+            loaded:  self.current == Some(i),
+        })
+        .collect()
+    }
+
+    //                        _
+    //  _ _   _ __   _____  _| |_
+    // (_|_) | '_ \ / _ \ \/ / __|
+    //  _ _  | | | |  __/>  <| |_
+    // (_|_) |_| |_|\___/_/\_\\__|
+
+    pub fn next(&self,
+                rotation: &RotationConfig) -> Option<&Entry>
+    {
+        let n = self.entries.len();
+        let start = self.current.map(|c| c + 1).unwrap_or(0);
+
+        (0..n)
+        .map(|k| &self.entries[(start + k) % n])
+        .find(|e| !rotation.exclude.contains(&e.name))
+    }
+
+    //                            _
+    //  _ _   _ __ __ _ _ __   __| | ___  _ __ ___
+    // (_|_) | '__/ _` | '_ \ / _` |/ _ \| '_ ` _ \
+    //  _ _  | | | (_| | | | | (_| | (_) | | | | | |
+    // (_|_) |_|  \__,_|_| |_|\__,_|\___/|_| |_| |_|
+
+    // Current model is not picked again, unless there is nothing else
+    pub fn random(&self,
+                  rotation: &RotationConfig) -> Option<&Entry>
+    {
+        let weighted: Vec<_> =
+            self.entries.iter()
+            .enumerate()
+            .filter(|(_, e)| !rotation.exclude.contains(&e.name))
+            .map(|(i, e)| (i, rotation.weights.get(&e.name)
+                              .copied()
+                              .unwrap_or(1.)))
+            .filter(|(_, w)| *w > 0.)
+            .collect();
+
+        let others: Vec<_> =
+            weighted.iter()
+            .filter(|(i, _)| Some(*i) != self.current)
+            .copied()
+            .collect();
+        let pool = if others.is_empty() {weighted} else {others};
+
+        let total: f64 = pool.iter().map(|(_, w)| w).sum();
+        let mut r = fastrand::f64() * total;
+
+        pool.iter()
+        .find(|(_, w)| {r -= w; r < 0.})
+        .or(pool.last())
+        .map(|(i, _)| &self.entries[*i])
+    }
+}
+
+//  _____ _           _
+// |  ___(_)_ __   __| |
+// | |_  | | '_ \ / _` |
+// |  _| | | | | | (_| |
+// |_|   |_|_| |_|\__,_|
+
+// Symlinked directories are not followed, not to go round in circles
+fn find(dir:   &Path,
+        files: &mut Vec<PathBuf>)
+{
+    let entries = match fs::read_dir(dir) {
+        Ok(e)  => e,
+        Err(e) => {eprintln!("Failed to read {}: {e}", dir.display()); return}
+    };
+
+    for e in entries.flatten() {
+        let path = e.path();
+        let is_dir =
+            e.file_type()
+            .map(|t| t.is_dir())
+            .unwrap_or(false);
+
+        if is_dir {
+            find(&path, files)
+        } else if path.to_str().is_some_and(|p| p.ends_with(MODEL3)) {
+            files.push(fs::canonicalize(&path).unwrap_or(path))
+        }
+    }
+}
+
+//   ____           _
+//  / ___|__ _  ___| |__   ___
+// | |   / _` |/ __| '_ \ / _ \
+// | |__| (_| | (__| | | |  __/
+//  \____\__,_|\___|_| |_|\___|
+
+fn cache() -> PathBuf
+{
+    env::var_os("XDG_CACHE_HOME")
+    .map(PathBuf::from)
+    .or_else(|| env::var_os("HOME").map(|h| PathBuf::from(h).join(".cache")))
+    .unwrap_or_else(env::temp_dir)
+    .join(APP_NAME)
+    .join("thumbnails")
+}
+
+//  _____
+// |  ___| ____   __
+// | |_ | '_ \ \ / /
+// |  _|| | | \ V /
+// |_|  |_| |_|\_/
+
+// FNV-1a, fixed unlike the hasher of the standard library
+fn fnv(bytes: &[u8]) -> u64
+{
+    bytes.iter()
+    .fold(0xcbf29ce484222325,
+          |h, b| (h ^ *b as u64).wrapping_mul(0x100000001b3))
+}
+
+//  _____ _                     _                 _ _
+// |_   _| |__  _   _ _ __ ___ | |__  _ __   __ _(_) |___
+//   | | | '_ \| | | | '_ ` _ \| '_ \| '_ \ / _` | | / __|
+//   | | | | | | |_| | | | | | | |_) | | | | (_| | | \__ \
+//   |_| |_| |_|\__,_|_| |_| |_|_.__/|_| |_|\__,_|_|_|___/
+
+// Drawn in the background with the cpu backend, only where the model changed
+// since the last time
+fn thumbnails(entries: &[Entry])
+{
+    let stale: Vec<_> =
+        entries.iter()
+        .filter(|e| {
+            let modified = |p: &Path| fs::metadata(p).and_then(|m| m.modified());
+            match (modified(&e.file), modified(&e.thumbnail)) {
+                (Ok(m), Ok(t)) => t < m,
+                _              => true,
+            }
+        })
+        .map(|e| (e.file.clone(),
+                  e.thumbnail.clone()))
+        .collect();
+
+    if stale.is_empty() {return}
+
+    thread::spawn(move || {
+        for (file, out) in stale {
+            thumbnail(&file,
+                      &out)
+            .unwrap_or_else(|e| eprintln!("Failed to draw thumbnail of {}: \
+                                           {e}",
+                                          file.display()));
+        }
+    });
+}
+
+//  _____ _                     _                 _ _
+// |_   _| |__  _   _ _ __ ___ | |__  _ __   __ _(_) |
+//   | | | '_ \| | | | '_ ` _ \| '_ \| '_ \ / _` | | |
+//   | | | | | | |_| | | | | | | |_) | | | | (_| | | |
+//   |_| |_| |_|\__,_|_| |_| |_|_.__/|_| |_|\__,_|_|_|
+
+fn thumbnail(file: &Path,
+             out:  &Path) -> Result<(), Box<dyn Error>>
+{
+    let mut config = Config::default();
+    config.window.size = [THUMBNAIL; 2];
+    config.window.fit = FitConfig::Contain;
+    config.model.file = Some(file.to_str().ok_or("Path is not UTF-8")?.into());
+
+    let mut stage = Stage::<Raster>::new(&config)?;
+    stage.model.update(0.)?;
+
+    if let Some(dir) = out.parent() {
+        fs::create_dir_all(dir)?
+    }
+
+    stage
+    .snapshot(&config)?
+    .save(out)?;
+
+    Ok(())
+}
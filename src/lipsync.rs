@@ -0,0 +1,768 @@
+use std::{
+    fs::{self, File},
+    path::Path,
+    error::Error,
+    time::Instant,
+};
+use cubism::{
+    core,
+    id::{groups, param},
+    controller::Controller,
+    json::model::{Model3, GroupTarget},
+};
+use crate::{
+    framework::approach,
+    config::{LipSyncConfig, PcmConfig, PcmFormat},
+};
+
+// Seconds of sound every level is taken over
+pub const WINDOW: f32 = 1. / 60.;
+
+// Seconds without anything from the pipe before the voice is gone
+const SILENCE: f32 = 0.25;
+
+// Seconds it takes to hand the mouth over between voice and motions
+const HANDOVER: f32 = 0.1;
+
+// Rough pitch of a round vowel and of a wide one, in crossings per second
+// over two. Anything in between is somewhere in between
+const ROUND: f32 = 300.;
+const WIDE:  f32 = 1500.;
+
+// How much of the level goes to the mouth, as in the Cubism samples
+const WEIGHT: f32 = 0.8;
+
+// Runs after motions and expressions, before tracking
+const PRIORITY: usize = 200;
+
+//   ____ _ _
+//  / ___| (_)_ __
+// | |   | | | '_ \
+// | |___| | | |_) |
+//  \____|_|_| .__/
+//           |_|
+
+// Decoded sound of a motion, with how loud it is over time. Samples are
+// left unplayed without audio
+#[cfg_attr(not(feature = "audio"), allow(dead_code))]
+#[derive(Clone)]
+pub struct Clip {
+    rate:     u32,
+    channels: u16,
+    // Interleaved, only kept to be played
+    samples:  Vec<f32>,
+    levels:   Vec<f32>,
+    // Mouth form of the vowel, from round to wide
+    forms:    Vec<f32>,
+}
+
+//  _     _         ____
+// | |   (_)_ __   / ___| _   _ _ __   ___
+// | |   | | '_ \  \___ \| | | | '_ \ / __|
+// | |___| | |_) |  ___) | |_| | | | | (__
+// |_____|_| .__/  |____/ \__, |_| |_|\___|
+//         |_|            |___/
+
+// Mouth of the model, opened as loud as the sound is
+pub struct LipSync {
+    // Parameters of the LipSync group
+    ids:       Vec<String>,
+    gain:      f32,
+    // Level of the motion sound
+    level:     f32,
+    // Voice from bleed speak and how far into it, or from the pipe
+    speech:    Option<(Clip, f32)>,
+    heard:     [f32; 2],
+    seen:      Option<Instant>,
+    // Level and form the mouth is at, catching up with the voice
+    smoothed:  [f32; 2],
+    smoothing: f32,
+    // How much of the voice is shown
+    weight:    f32,
+}
+
+//  ____                   _
+// / ___| _ __   ___  __ _| | _____ _ __
+// \___ \| '_ \ / _ \/ _` | |/ / _ \ '__|
+//  ___) | |_) |  __/ (_| |   <  __/ |
+// |____/| .__/ \___|\__,_|_|\_\___|_|
+//       |_|
+
+// Plays sounds of motions, kept in sync with them
+#[cfg(feature = "audio")]
+pub struct Speaker {
+    _stream: rodio::OutputStream,
+    handle:  rodio::OutputStreamHandle,
+    sink:    Option<rodio::Sink>,
+    volume:  f32,
+}
+
+#[cfg(not(feature = "audio"))]
+pub struct Speaker;
+
+//   ____ _ _
+//  / ___| (_)_ __    _ _
+// | |   | | | '_ \  (_|_)
+// | |___| | | |_) |  _ _
+//  \____|_|_| .__/  (_|_)
+//           |_|
+
+impl Clip {
+
+    //        _                 _
+    //  _ _  | | ___   __ _  __| |
+    // (_|_) | |/ _ \ / _` |/ _` |
+    //  _ _  | | (_) | (_| | (_| |
+    // (_|_) |_|\___/ \__,_|\__,_|
+
+    // WAV or Ogg Vorbis, told by the extension, and raw PCM as configured
+    pub fn load(path: &Path,
+                pcm:  &PcmConfig) -> Result<Self, Box<dyn Error>>
+    {
+        let extension =
+            path.extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase());
+
+        let (rate, channels, samples) = match extension.as_deref() {
+            Some("wav")         => wav(path)?,
+            Some("ogg" | "oga") => ogg(path)?,
+            Some("pcm" | "raw") => (pcm.rate,
+                                    pcm.channels,
+                                    decode(&fs::read(path)?, pcm.format)),
+            _                   => return Err(format!("Unsupported sound \
+                                                       {}",
+                                                      path.display()).into()),
+        };
+        if rate == 0 || channels == 0 {
+            return Err(format!("Broken sound {}", path.display()).into())
+        }
+
+        Ok(Self::new(rate, channels, samples))
+    }
+
+    //  _ _   _ __   _____      __
+    // (_|_) | '_ \ / _ \ \ /\ / /
+    //  _ _  | | | |  __/\ V  V /
+    // (_|_) |_| |_|\___| \_/\_/
+
+    pub fn new(rate:     u32,
+               channels: u16,
+               samples:  Vec<f32>) -> Self
+    {
+        let (levels, forms) =
+            samples.chunks(window(rate) * channels as usize)
+            .map(|w| {
+                let [level, form] = analyze(w,
+                                            channels,
+                                            rate);
+                (level, form)
+            })
+            .unzip();
+
+        Self {
+            rate,
+            channels,
+            samples,
+            levels,
+            forms,
+        }
+    }
+
+    //        _                _
+    //  _ _  | | _____   _____| |
+    // (_|_) | |/ _ \ \ / / _ \ |
+    //  _ _  | |  __/\ V /  __/ |
+    // (_|_) |_|\___| \_/ \___|_|
+
+    // Silent past the end
+    pub fn level(&self,
+                 time: f32) -> f32
+    {
+        if time < 0. {return 0.}
+
+        self.levels
+        .get((time / WINDOW) as usize)
+        .copied()
+        .unwrap_or(0.)
+    }
+
+    //         __
+    //  _ _   / _| ___  _ __ _ __ ___
+    // (_|_) | |_ / _ \| '__| '_ ` _ \
+    //  _ _  |  _| (_) | |  | | | | | |
+    // (_|_) |_|  \___/|_|  |_| |_| |_|
+
+    pub fn form(&self,
+                time: f32) -> f32
+    {
+        if time < 0. {return 0.}
+
+        self.forms
+        .get((time / WINDOW) as usize)
+        .copied()
+        .unwrap_or(0.)
+    }
+
+    //            _                 _   _
+    //  _ _    __| |_   _ _ __ __ _| |_(_) ___  _ __
+    // (_|_)  / _` | | | | '__/ _` | __| |/ _ \| '_ \
+    //  _ _  | (_| | |_| | | | (_| | |_| | (_) | | | |
+    // (_|_)  \__,_|\__,_|_|  \__,_|\__|_|\___/|_| |_|
+
+    pub fn duration(&self) -> f32 {self.levels.len() as f32 * WINDOW}
+}
+
+//  _     _         ____
+// | |   (_)_ __   / ___| _   _ _ __   ___   _ _
+// | |   | | '_ \  \___ \| | | | '_ \ / __| (_|_)
+// | |___| | |_) |  ___) | |_| | | | | (__   _ _
+// |_____|_| .__/  |____/ \__, |_| |_|\___| (_|_)
+//         |_|            |___/
+
+impl LipSync {
+
+    //  _ _   _ __   _____      __
+    // (_|_) | '_ \ / _ \ \ /\ / /
+    //  _ _  | | | |  __/\ V  V /
+    // (_|_) |_| |_|\___| \_/\_/
+
+    pub fn new(model3: &Model3,
+               config: &LipSyncConfig) -> Self
+    {
+        let ids =
+            model3.groups.iter()
+            .filter(|g| g.target == GroupTarget::Parameter
+                        && g.name == groups::LIP_SYNC)
+            .flat_map(|g| g.ids.iter().cloned())
+            .collect();
+
+        Self {
+            ids,
+            gain:      config.gain,
+            level:     0.,
+            speech:    None,
+            heard:     [0., 0.],
+            seen:      None,
+            smoothed:  [0., 0.],
+            smoothing: config.smoothing,
+            weight:    0.,
+        }
+    }
+
+    //                         __ _
+    //  _ _    ___ ___  _ __  / _(_) __ _ _   _ _ __ ___
+    // (_|_)  / __/ _ \| '_ \| |_| |/ _` | | | | '__/ _ \
+    //  _ _  | (_| (_) | | | |  _| | (_| | |_| | | |  __/
+    // (_|_)  \___\___/|_| |_|_| |_|\__, |\__,_|_|  \___|
+    //                              |___/
+
+    pub fn configure(&mut self,
+                     config: &LipSyncConfig)
+    {
+        self.gain = config.gain;
+        self.smoothing = config.smoothing;
+    }
+
+    //                                  _
+    //  _ _   ___  ___  _   _ _ __   __| |
+    // (_|_) / __|/ _ \| | | | '_ \ / _` |
+    //  _ _  \__ \ (_) | |_| | | | | (_| |
+    // (_|_) |___/\___/ \__,_|_| |_|\__,_|
+
+    // Level of the motion sound right now
+    pub fn sound(&mut self,
+                 level: f32)
+    {
+        self.level = level;
+    }
+
+    //  _ _   ___  __ _ _   _
+    // (_|_) / __|/ _` | | | |
+    //  _ _  \__ \ (_| | |_| |
+    // (_|_) |___/\__,_|\__, |
+    //                  |___/
+
+    // Clip is gone through along with the frames, same as motions are
+    pub fn say(&mut self,
+               clip: Clip)
+    {
+        self.speech = Some((clip, 0.));
+    }
+
+    //        _
+    //  _ _  | |__   ___  __ _ _ __
+    // (_|_) | '_ \ / _ \/ _` | '__|
+    //  _ _  | | | |  __/ (_| | |
+    // (_|_) |_| |_|\___|\__,_|_|
+
+    pub fn hear(&mut self,
+                level: f32,
+                form:  f32)
+    {
+        self.heard = [level, form];
+        self.seen = Some(Instant::now());
+    }
+
+    //                   _
+    //  _ _  __   _____ (_) ___ ___
+    // (_|_) \ \ / / _ \| |/ __/ _ \
+    //  _ _   \ V / (_) | | (_|  __/
+    // (_|_)   \_/ \___/|_|\___\___|
+
+    // Spoken clip goes before the pipe, the pipe goes quiet on its own
+    fn voice(&mut self,
+             delta: f32) -> Option<[f32; 2]>
+    {
+        if let Some((clip, time)) = &mut self.speech {
+            *time += delta;
+            if *time < clip.duration() {
+                return Some([clip.level(*time),
+                             clip.form(*time)])
+            }
+            self.speech = None;
+        }
+
+        self.seen
+        .filter(|t| t.elapsed().as_secs_f32() < SILENCE)
+        .map(|_| self.heard)
+    }
+}
+
+impl Controller for LipSync {
+    // Levels go on top of what motions do with the mouth. Motion sounds open
+    // the LipSync group, voice opens the mouth as well and shapes it
+    fn update_parameters(&mut self,
+                         model: &mut core::Model,
+                         delta: f32)
+    {
+        let voice = self.voice(delta);
+        let target = match voice {
+            Some(_) => 1.,
+            None    => 0.,
+        };
+        self.weight = approach(self.weight,
+                               target,
+                               delta / HANDOVER);
+
+        let catch_up = match self.smoothing > 0. {
+            true  => 1. - (-delta / self.smoothing).exp(),
+            false => 1.,
+        };
+        let [level, form] = voice.unwrap_or([0., self.smoothed[1]]);
+        self.smoothed[0] += (level - self.smoothed[0]) * catch_up;
+        self.smoothed[1] += (form - self.smoothed[1]) * catch_up;
+
+        if self.level == 0. && self.weight == 0. {return}
+
+        let position = |id: &str| {
+            model.parameter_ids().iter()
+            .position(|p| *p == id)
+        };
+        let mut mouth: Vec<_> =
+            self.ids.iter()
+            .filter_map(|id| position(id))
+            .map(|i| (i, self.level))
+            .collect();
+        if let Some(i) = position(param::MOUTH_OPEN_Y) {
+            if !mouth.iter().any(|(m, _)| *m == i) {mouth.push((i, 0.))}
+        }
+        let form = position(param::MOUTH_FORM);
+
+        let [min, max] = [model.parameter_min().to_vec(),
+                          model.parameter_max().to_vec()];
+        let values = model.parameter_values_mut();
+        let voice = self.smoothed[0] * self.weight;
+
+        for (i, sound) in mouth {
+            let open = (sound + voice) * self.gain * WEIGHT;
+            values[i] = (values[i] + open).clamp(min[i], max[i]);
+        }
+        if let Some(i) = form {
+            let shaped = values[i] + (self.smoothed[1] - values[i]) * self.weight;
+            values[i] = shaped.clamp(min[i], max[i]);
+        }
+    }
+
+    fn priority(&self) -> usize {PRIORITY}
+}
+
+//  ____                   _
+// / ___| _ __   ___  __ _| | _____ _ __   _ _
+// \___ \| '_ \ / _ \/ _` | |/ / _ \ '__| (_|_)
+//  ___) | |_) |  __/ (_| |   <  __/ |     _ _
+// |____/| .__/ \___|\__,_|_|\_\___|_|    (_|_)
+//       |_|
+
+#[cfg(feature = "audio")]
+impl Speaker {
+
+    //  _ _   _ __   _____      __
+    // (_|_) | '_ \ / _ \ \ /\ / /
+    //  _ _  | | | |  __/\ V  V /
+    // (_|_) |_| |_|\___| \_/\_/
+
+    pub fn new(config: &LipSyncConfig) -> Result<Self, Box<dyn Error>>
+    {
+        let (stream, handle) = rodio::OutputStream::try_default()?;
+
+        Ok(Self {
+            _stream: stream,
+            handle,
+            sink:    None,
+            volume:  config.volume,
+        })
+    }
+
+    //              _
+    //  _ _   _ __ | | __ _ _   _
+    // (_|_) | '_ \| |/ _` | | | |
+    //  _ _  | |_) | | (_| | |_| |
+    // (_|_) | .__/|_|\__,_|\__, |
+    //       |_|            |___/
+
+    // Starts the clip over, from where the motion is
+    pub fn play(&mut self,
+                clip: &Clip,
+                from: f32)
+    {
+        self.stop();
+
+        let start =
+            (from.max(0.) * clip.rate as f32) as usize * clip.channels as usize;
+        let samples = match clip.samples.get(start..) {
+            Some(s) if !s.is_empty() => s.to_vec(),
+            _                        => return,
+        };
+
+        let sink = match rodio::Sink::try_new(&self.handle) {
+            Ok(s)  => s,
+            Err(e) => {eprintln!("Failed to play sound: {e}"); return}
+        };
+        sink.set_volume(self.volume);
+        sink.append(rodio::buffer::SamplesBuffer::new(clip.channels,
+                                                      clip.rate,
+                                                      samples));
+        self.sink = Some(sink);
+    }
+
+    //  _ _   _ __   __ _ _   _ ___  ___
+    // (_|_) | '_ \ / _` | | | / __|/ _ \
+    //  _ _  | |_) | (_| | |_| \__ \  __/
+    // (_|_) | .__/ \__,_|\__,_|___/\___|
+    //       |_|
+
+    pub fn pause(&self)
+    {
+        if let Some(s) = &self.sink {s.pause()}
+    }
+
+    //  _ _   _ __ ___  ___ _   _ _ __ ___   ___
+    // (_|_) | '__/ _ \/ __| | | | '_ ` _ \ / _ \
+    //  _ _  | | |  __/\__ \ |_| | | | | | |  __/
+    // (_|_) |_|  \___||___/\__,_|_| |_| |_|\___|
+
+    pub fn resume(&self)
+    {
+        if let Some(s) = &self.sink {s.play()}
+    }
+
+    //            _
+    //  _ _   ___| |_ ___  _ __
+    // (_|_) / __| __/ _ \| '_ \
+    //  _ _  \__ \ || (_) | |_) |
+    // (_|_) |___/\__\___/| .__/
+    //                    |_|
+
+    pub fn stop(&mut self)
+    {
+        if let Some(s) = self.sink.take() {s.stop()}
+    }
+
+    //                         __ _
+    //  _ _    ___ ___  _ __  / _(_) __ _ _   _ _ __ ___
+    // (_|_)  / __/ _ \| '_ \| |_| |/ _` | | | | '__/ _ \
+    //  _ _  | (_| (_) | | | |  _| | (_| | |_| | | |  __/
+    // (_|_)  \___\___/|_| |_|_| |_|\__, |\__,_|_|  \___|
+    //                              |___/
+
+    pub fn configure(&mut self,
+                     config: &LipSyncConfig)
+    {
+        self.volume = config.volume;
+        if let Some(s) = &self.sink {s.set_volume(self.volume)}
+    }
+}
+
+// Built without audio, sounds only move the mouth
+#[cfg(not(feature = "audio"))]
+impl Speaker {
+    pub fn new(_: &LipSyncConfig) -> Result<Self, Box<dyn Error>>
+    {
+        Err("Built without audio, sounds are not played".into())
+    }
+
+    pub fn play(&mut self, _: &Clip, _: f32) {}
+
+    pub fn pause(&self) {}
+
+    pub fn resume(&self) {}
+
+    pub fn stop(&mut self) {}
+
+    pub fn configure(&mut self, _: &LipSyncConfig) {}
+}
+
+//  _ _  __      ____ ___   __
+// (_|_) \ \ /\ / / _` \ \ / /
+//  _ _   \ V  V / (_| |\ V /
+// (_|_)   \_/\_/ \__,_| \_/
+
+fn wav(path: &Path) -> Result<(u32, u16, Vec<f32>), Box<dyn Error>>
+{
+    let mut reader = hound::WavReader::open(path)?;
+    let spec = reader.spec();
+
+    let samples = match spec.sample_format {
+        hound::SampleFormat::Float => {
+            reader.samples::<f32>()
+            .collect::<Result<_, _>>()?
+        }
+        hound::SampleFormat::Int   => {
+            let scale = (1_i64 << (spec.bits_per_sample - 1)) as f32;
+            reader.samples::<i32>()
+            .map(|s| s.map(|s| s as f32 / scale))
+            .collect::<Result<_, _>>()?
+        }
+    };
+
+    Ok((spec.sample_rate, spec.channels, samples))
+}
+
+//  _ _    ___   __ _  __ _
+// (_|_)  / _ \ / _` |/ _` |
+//  _ _  | (_) | (_| | (_| |
+// (_|_)  \___/ \__, |\__, |
+//              |___/ |___/
+
+fn ogg(path: &Path) -> Result<(u32, u16, Vec<f32>), Box<dyn Error>>
+{
+    let file = File::open(path)?;
+    let mut reader = lewton::inside_ogg::OggStreamReader::new(file)?;
+
+    let mut samples = Vec::new();
+    while let Some(packet) = reader.read_dec_packet_itl()? {
+        samples.extend(packet.iter().map(|s| *s as f32 / 32768.));
+    }
+
+    let header = &reader.ident_hdr;
+    Ok((header.audio_sample_rate,
+        header.audio_channels as u16,
+        samples))
+}
+
+//                 _           _
+//  _ _  __      _(_)_ __   __| | _____      __
+// (_|_) \ \ /\ / / | '_ \ / _` |/ _ \ \ /\ / /
+//  _ _   \ V  V /| | | | | (_| | (_) \ V  V /
+// (_|_)   \_/\_/ |_|_| |_|\__,_|\___/ \_/\_/
+
+// Frames every level is taken over
+pub fn window(rate: u32) -> usize {((rate as f32 * WINDOW) as usize).max(1)}
+
+//                          _
+//  _ _    __ _ _ __   __ _| |_   _ _______
+// (_|_)  / _` | '_ \ / _` | | | | |_  / _ \
+//  _ _  | (_| | | | | (_| | | |_| |/ /  __/
+// (_|_)  \__,_|_| |_|\__,_|_|\__, /___\___|
+//                            |___/
+
+// Root mean square of a window, channels mixed together, and how wide the
+// vowel is from how often the sound crosses zero
+pub fn analyze(samples:  &[f32],
+               channels: u16,
+               rate:     u32) -> [f32; 2]
+{
+    let mono: Vec<f32> =
+        samples.chunks(channels.max(1) as usize)
+        .map(|f| f.iter().sum::<f32>() / f.len() as f32)
+        .collect();
+    if mono.is_empty() {return [0., 0.]}
+
+    let level =
+        (mono.iter().map(|s| s * s).sum::<f32>() / mono.len() as f32).sqrt();
+
+    let crossings =
+        mono.windows(2)
+        .filter(|w| (w[0] < 0.) != (w[1] < 0.))
+        .count();
+    let pitch = crossings as f32 / 2. / (mono.len() as f32 / rate as f32);
+    let form = ((pitch - ROUND) / (WIDE - ROUND) * 2. - 1.).clamp(-1., 1.);
+
+    [level, form]
+}
+
+//                 _     _ _   _
+//  _ _  __      _(_) __| | |_| |__
+// (_|_) \ \ /\ / / |/ _` | __| '_ \
+//  _ _   \ V  V /| | (_| | |_| | | |
+// (_|_)   \_/\_/ |_|\__,_|\__|_| |_|
+
+// Bytes of a sample
+pub fn width(format: PcmFormat) -> usize
+{
+    match format {
+        PcmFormat::S16le => 2,
+        PcmFormat::F32le => 4,
+        PcmFormat::U8    => 1,
+    }
+}
+
+//            _                    _
+//  _ _    __| | ___  ___ ___   __| | ___
+// (_|_)  / _` |/ _ \/ __/ _ \ / _` |/ _ \
+//  _ _  | (_| |  __/ (_| (_) | (_| |  __/
+// (_|_)  \__,_|\___|\___\___/ \__,_|\___|
+
+// Raw PCM to samples from -1 to 1, a sample cut short is dropped
+pub fn decode(bytes:  &[u8],
+              format: PcmFormat) -> Vec<f32>
+{
+    bytes.chunks_exact(width(format))
+    .map(|b| match format {
+        PcmFormat::S16le => i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.,
+        PcmFormat::F32le => f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+        PcmFormat::U8    => (b[0] as f32 - 128.) / 128.,
+    })
+    .collect()
+}
+
+//  _____         _
+// |_   _|__  ___| |_ ___
+//   | |/ _ \/ __| __/ __|
+//   | |  __/\__ \ |_\__ \
+//   |_|\___||___/\__|___/
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+    use super::*;
+
+    const RATE: u32 = 48000;
+
+    // Whole periods of it in a window at the rate above, halfway between a
+    // round vowel and a wide one
+    const PITCH: f32 = 900.;
+
+    fn sine(amplitude: f32,
+            frames:    usize) -> Vec<f32>
+    {
+        (0..frames)
+        .map(|i| amplitude * (TAU * PITCH * i as f32 / RATE as f32).sin())
+        .collect()
+    }
+
+    fn close(a: f32,
+             b: f32,
+             by: f32) -> bool
+    {
+        (a - b).abs() < by
+    }
+
+    //                          _
+    //  _ _    __ _ _ __   __ _| |_   _ _______
+    // (_|_)  / _` | '_ \ / _` | | | | |_  / _ \
+    //  _ _  | (_| | | | | (_| | | |_| |/ /  __/
+    // (_|_)  \__,_|_| |_|\__,_|_|\__, /___\___|
+    //                            |___/
+
+    #[test]
+    fn analyze_silence()
+    {
+        assert_eq!(analyze(&[], 1, RATE), [0., 0.]);
+
+        let [level, form] = analyze(&vec![0.; window(RATE)], 1, RATE);
+        assert_eq!(level, 0.);
+        assert_eq!(form, -1.);
+    }
+
+    #[test]
+    fn analyze_sine()
+    {
+        let [level, form] = analyze(&sine(0.5, window(RATE)), 1, RATE);
+        assert!(close(level, 0.5 / 2f32.sqrt(), 1e-3), "level {level}");
+        assert!(close(form, 0., 0.1), "form {form}");
+
+        // Channels are mixed before anything else, opposite ones cancel out
+        let stereo: Vec<_> =
+            sine(0.5, window(RATE)).into_iter()
+            .flat_map(|s| [s, -s])
+            .collect();
+        assert_eq!(analyze(&stereo, 2, RATE)[0], 0.);
+    }
+
+    //             _ _
+    //  _ _    ___| (_)_ __
+    // (_|_)  / __| | | '_ \
+    //  _ _  | (__| | | |_) |
+    // (_|_)  \___|_|_| .__/
+    //                |_|
+
+    #[test]
+    fn clip_windows()
+    {
+        assert_eq!(window(RATE), 800);
+        assert_eq!(window(44100), 735);
+        assert_eq!(window(0), 1);
+
+        // Silent, loud, silent, and a bit of a window at the end
+        let w = window(RATE);
+        let mut samples = vec![0.; w];
+        samples.extend(sine(0.5, w));
+        samples.extend(vec![0.; w]);
+        samples.extend(sine(0.5, w / 4));
+        let clip = Clip::new(RATE, 1, samples);
+
+        assert_eq!(clip.levels.len(), 4);
+        assert!(close(clip.duration(), 4. * WINDOW, 1e-6));
+
+        let at = |i: f32| (i + 0.5) * WINDOW;
+        assert_eq!(clip.level(at(0.)), 0.);
+        assert!(close(clip.level(at(1.)), 0.5 / 2f32.sqrt(), 1e-3));
+        assert!(close(clip.form(at(1.)), 0., 0.1));
+        assert_eq!(clip.level(at(2.)), 0.);
+        assert!(clip.level(at(3.)) > 0.);
+
+        // Before the start and past the end
+        assert_eq!(clip.level(-WINDOW), 0.);
+        assert_eq!(clip.form(-WINDOW), 0.);
+        assert_eq!(clip.level(at(4.)), 0.);
+        assert_eq!(clip.form(at(10.)), 0.);
+    }
+
+    //            _                    _
+    //  _ _    __| | ___  ___ ___   __| | ___
+    // (_|_)  / _` |/ _ \/ __/ _ \ / _` |/ _ \
+    //  _ _  | (_| |  __/ (_| (_) | (_| |  __/
+    // (_|_)  \__,_|\___|\___\___/ \__,_|\___|
+
+    #[test]
+    fn decode_formats()
+    {
+        let samples = decode(&[0x00, 0x80, 0x00, 0x00, 0xff, 0x7f, 0x01],
+                             PcmFormat::S16le);
+        assert_eq!(samples.len(), 3);
+        assert_eq!(samples[0], -1.);
+        assert_eq!(samples[1], 0.);
+        assert!(close(samples[2], 1., 1e-4));
+
+        let bytes: Vec<_> =
+            [0.5f32, -0.25].iter()
+            .flat_map(|s| s.to_le_bytes())
+            .chain([0, 0])
+            .collect();
+        assert_eq!(decode(&bytes, PcmFormat::F32le), vec![0.5, -0.25]);
+
+        assert_eq!(decode(&[0, 128, 192], PcmFormat::U8), vec![-1., 0., 0.5]);
+    }
+}
@@ -3,43 +3,113 @@ use std::{
     env,
     thread,
     rc::Rc,
-    io::Read,
+    sync::mpsc,
+    io::{Read, Write},
     path::Path,
     error::Error,
-    time::Instant,
+    time::{Duration, Instant},
     os::unix::net::UnixListener,
 };
+use signal_hook::{
+    consts::SIGHUP,
+    iterator::Signals,
+};
 use glium::{
     Frame,
     Display,
-    Surface,
-    uniform,
-    program::Program,
-    vertex::VertexBuffer,
     backend::{Facade, Context},
-    index::{IndexBuffer, PrimitiveType},
-    texture::{RawImage2d, SrgbTexture2d},
     glutin::{
         ContextBuilder,
-        dpi::LogicalSize,
+        dpi::{LogicalSize, PhysicalPosition},
         window::WindowBuilder,
-        event::{Event, WindowEvent},
+        event::{
+            Event,
+            MouseButton,
+            WindowEvent,
+            ElementState,
+            MouseScrollDelta,
+        },
         event_loop::EventLoopBuilder,
     },
 };
 
 mod config;
-use config::{Config, FitConfig, BgType};
+use config::{
+    Mode,
+    Config,
+    Anchor,
+    Backend,
+    FitConfig,
+    RotationType,
+    color,
+};
 
 mod message;
-use message::{Message, SOCKET_ADDR};
+use message::{
+    Message,
+    Load,
+    Shelf,
+    Reply,
+    Recolor,
+    Outfit,
+    Puppet,
+    Mood,
+    Cue,
+    SOCKET_ADDR,
+};
 
 mod framework;
-use framework::{Model, Vert};
+use framework::Model;
+
+mod renderer;
+use renderer::{Gl, Device, Renderer};
 
 mod xsecurelock;
 use xsecurelock::XSecureLock;
 
+mod render;
+
+mod egl;
+
+mod export;
+
+mod pipe;
+
+mod watch;
+use watch::watch;
+
+mod library;
+use library::Library;
+
+mod scene;
+use scene::Scene;
+
+mod background;
+use background::Background;
+
+mod effects;
+use effects::Effects;
+
+mod tracking;
+
+mod vmc;
+
+mod osf;
+
+mod lipsync;
+use lipsync::Clip;
+
+mod voice;
+
+mod vts;
+
+mod graph;
+
+// Zoom for a line of the scroll wheel
+const ZOOM: f32 = 1.1;
+// Time for zoom and pan to stay put before they are saved
+const SETTLE: Duration = Duration::from_secs(1);
+
 //                  _
 //  _ __ ___   __ _(_)_ __
 // | '_ ` _ \ / _` | | '_ \
@@ -48,8 +118,6 @@ use xsecurelock::XSecureLock;
 
 fn main() -> Result<(), Box<dyn Error>>
 {
-    use ProgramVariant as PV;
-
     //                    __ _
     //    ___ ___  _ __  / _(_) __ _
     //   / __/ _ \| '_ \| |_| |/ _` |
@@ -57,7 +125,19 @@ fn main() -> Result<(), Box<dyn Error>>
     // (_)___\___/|_| |_|_| |_|\__, |
     //                         |___/
 
-    let config = Config::new()?;
+    let mut config = Config::new()?;
+
+    match &config.mode {
+        Mode::Window    => if let Backend::Cpu = config.backend {
+            return Err("Window is only drawn with gl backend".into())
+        },
+        Mode::Render(r) => return render::render(&config,
+                                                 r),
+        Mode::Export(e) => return export::export(&config,
+                                                 e),
+        Mode::Pipe(p)   => return pipe::pipe(&config,
+                                             p),
+    }
 
     //                        _     _
     //    _____   _____ _ __ | |_  | | ___   ___  _ __
@@ -70,34 +150,10 @@ fn main() -> Result<(), Box<dyn Error>>
         EventLoopBuilder::<Message>::with_user_event()
         .build();
 
-    let path = Path::new(SOCKET_ADDR);
-
-    if path.exists() {
-        eprintln!("Removing existing socket before connecting");
-        fs::remove_file(path)?
-    }
-
-    let listener = UnixListener::bind(path)?;
-
     let proxy = event_loop.create_proxy();
 
-    thread::spawn(move || {
-        listener.incoming()
-        .for_each(|stream| {
-            stream
-            .map_err(|e| format!("Socket connection error: {e}"))
-            .map(|mut s| {
-                let mut input = String::new();
-                s.read_to_string(&mut input).unwrap_or(0);
-                input
-            })
-            .and_then(|i| Message::parse(i)
-                          .ok_or("Failed to parse message".to_string()))
-            .and_then(|m| proxy.send_event(m)
-                          .map_err(|e| format!("Failed to send message: {e}")))
-            .unwrap_or_else(|e| eprintln!("{e}"))
-        });
-    });
+    listen(move |m| proxy.send_event(m)
+                    .map_err(|e| format!("Failed to send message: {e}")))?;
 
     //       _ _           _
     //    __| (_)___ _ __ | | __ _ _   _
@@ -128,39 +184,14 @@ fn main() -> Result<(), Box<dyn Error>>
         Hack::Display(display)
     };
 
-    //    _ __  _ __ ___   __ _ _ __ __ _ _ __ ___  ___
-    //   | '_ \| '__/ _ \ / _` | '__/ _` | '_ ` _ \/ __|
-    //  _| |_) | | | (_) | (_| | | | (_| | | | | | \__ \
-    // (_) .__/|_|  \___/ \__, |_|  \__,_|_| |_| |_|___/
-    //   |_|              |___/
-
-    let prg_background =
-        Program::from_source(&display,
-                             include_str!("shaders/bg_vert.glsl"),
-                             include_str!("shaders/bg_frag.glsl"),
-                             None)?;
-
-    let prg_mask =
-        Program::from_source(&display,
-                             include_str!("shaders/mask_vert.glsl"),
-                             include_str!("shaders/mask_frag.glsl"),
-                             None)?;
-
-    let prg_normal =
-        Program::from_source(&display,
-                             include_str!("shaders/vert.glsl"),
-                             include_str!("shaders/frag.glsl"),
-                             None)?;
-
-    // Ideally I'd like to initialize array with Rc::new_zeroed() and then fill
-    // with correct indices using PV::NormalBlend etc., but this method for Rc
-    // is currently in nightly, so I just have to make sure that correct
-    // programs are at correct indices
-    let programs: [Rc<Program>; PV::Counter as usize] = [
-        Rc::new(prg_background),
-        Rc::new(prg_mask),
-        Rc::new(prg_normal),
-    ];
+    //         _
+    //    __ _| |
+    //   / _` | |
+    //  | (_| | |
+    // (_)__, |_|
+    //   |___/
+
+    let gl = Gl::new(&display)?;
 
     //    _                _                                   _
     //   | |__   __ _  ___| | ____ _ _ __ ___  _   _ _ __   __| |
@@ -169,11 +200,17 @@ fn main() -> Result<(), Box<dyn Error>>
     // (_)_.__/ \__,_|\___|_|\_\__, |_|  \___/ \__,_|_| |_|\__,_|
     //                         |___/
 
-    let background_image = match &config.window.bg.variant {
-        BgType::Image => Some(Background::new(config.window.bg.image.as_str(),
-                                              &display)?),
-        BgType::Color => None,
-    };
+    let mut background = Background::new(&config.window.bg,
+                                         &gl)?;
+
+    //         __  __           _
+    //    ___ / _|/ _| ___  ___| |_ ___
+    //   / _ \ |_| |_ / _ \/ __| __/ __|
+    //  |  __/  _|  _|  __/ (__| |_\__ \
+    // (_)___|_| |_|  \___|\___|\__|___/
+
+    let mut effects = Effects::new(&config.window.effects,
+                                   &gl)?;
 
     //                        _      _
     //    _ __ ___   ___   __| | ___| |
@@ -181,8 +218,104 @@ fn main() -> Result<(), Box<dyn Error>>
     //  _| | | | | | (_) | (_| |  __/ |
     // (_)_| |_| |_|\___/ \__,_|\___|_|
 
-    let mut model = Model::new(&config,
-                               &display)?;
+    let mut library = Library::scan(&config);
+
+    // Every lock starts liver anew, so that is when it picks a model
+    let rotation = &config.library.rotation;
+    let first = match &rotation.variant {
+        RotationType::Lock => library.random(rotation).map(|e| e.file.clone()),
+        _                  => None,
+    };
+
+    let mut scene = Scene::new(&config,
+                               first.as_deref(),
+                               &gl)?;
+    library.locate(scene.model(None)?.path());
+
+    //                _       _
+    // __      ____ _| |_ ___| |__
+    // \ \ /\ / / _` | __/ __| '_ \
+    //  \ V  V / (_| | || (__| | | |
+    // (_)_/\_/ \__,_|\__\___|_| |_|
+
+    let proxy = event_loop.create_proxy();
+    let changed = move |p| proxy.send_event(Message::Changed(p))
+                           .unwrap_or_else(|e| eprintln!("Failed to send \
+                                                          message: {e}"));
+
+    // Only kept around, files are watched for as long as it lives
+    let mut _watcher = watch(scene.files(),
+                             changed.clone())?;
+
+    let proxy = event_loop.create_proxy();
+    let _config_watcher = watch([Config::path()?.as_path()],
+                                move |_| proxy.send_event(Message::Reload)
+                                         .unwrap_or_else(|e| eprintln!("Failed \
+                                                                        to send \
+                                                                        message: \
+                                                                        {e}")))?;
+
+    //        _                   _
+    //    ___(_) __ _ _ __   __ _| |___
+    //   / __| |/ _` | '_ \ / _` | / __|
+    //  _\__ \ | (_| | | | | (_| | \__ \
+    // (_)___/_|\__, |_| |_|\__,_|_|___/
+    //          |___/
+
+    let proxy = event_loop.create_proxy();
+    let mut signals = Signals::new([SIGHUP])?;
+
+    thread::spawn(move || {
+        signals.forever()
+        .for_each(|_| proxy.send_event(Message::Reload)
+                      .unwrap_or_else(|e| eprintln!("Failed to send \
+                                                     message: {e}")))
+    });
+
+    //   _                  _    _
+    //  | |_ _ __ __ _  ___| | _(_)_ __   __ _
+    //  | __| '__/ _` |/ __| |/ / | '_ \ / _` |
+    //  | |_| | | (_| | (__|   <| | | | | (_| |
+    // (_)__|_|  \__,_|\___|_|\_\_|_| |_|\__, |
+    //                                   |___/
+
+    // Bound once, a new address takes a restart
+    let proxy = event_loop.create_proxy();
+    vmc::receive(&config.tracking.vmc,
+                 move |v| proxy.send_event(Message::Track(v))
+                          .unwrap_or_else(|e| eprintln!("Failed to send \
+                                                         message: {e}")))?;
+
+    let proxy = event_loop.create_proxy();
+    osf::receive(&config.tracking.osf,
+                 move |v| proxy.send_event(Message::Track(v))
+                          .unwrap_or_else(|e| eprintln!("Failed to send \
+                                                         message: {e}")))?;
+
+    //          _             _
+    //    _ __ | |_   _  __ _(_)_ __  ___
+    //   | '_ \| | | | |/ _` | | '_ \/ __|
+    //  _| |_) | | |_| | (_| | | | | \__ \
+    // (_) .__/|_|\__,_|\__, |_|_| |_|___/
+    //   |_|            |___/
+
+    let proxy = event_loop.create_proxy();
+    let gate = vts::serve(&config.vts,
+                          move |m| proxy.send_event(m)
+                                   .map_err(|e| format!("Failed to send \
+                                                         message: {e}")))?;
+
+    //             _
+    // __   _____ (_) ___ ___
+    // \ \ / / _ \| |/ __/ _ \
+    //  \ V / (_) | | (_|  __/
+    // (_)_/ \___/|_|\___\___|
+
+    let proxy = event_loop.create_proxy();
+    voice::listen(&config.lipsync,
+                  move |l, f| proxy.send_event(Message::Voice(l, f))
+                              .unwrap_or_else(|e| eprintln!("Failed to send \
+                                                             message: {e}")))?;
 
     //  _ _   _ __ _   _ _ __
     // (_|_) | '__| | | | '_ \
@@ -191,42 +324,11 @@ fn main() -> Result<(), Box<dyn Error>>
 
     let mut last_frame = Instant::now();
 
-    let mut aspect = {
-        let frame =
-            display
-            .get_context()
-            .get_framebuffer_dimensions();
-        let view = [
-            frame.0 as f32,
-            frame.1 as f32
-        ];
-        let object = model.size();
-        let fit = &config.window.fit;
-
-        calc_aspect(object,
-                    view,
-                    fit)
-    };
-
-    let mut bg_aspect = {
-        let frame =
-            display
-            .get_context()
-            .get_framebuffer_dimensions();
-        let view = [
-            frame.0 as f32,
-            frame.1 as f32
-        ];
-        let object =
-            background_image.as_ref()
-            .map(|bg| bg.size)
-            .unwrap_or([1., 1.]);
-        let fit = &FitConfig::Cover;
-
-        calc_aspect(object,
-                    view,
-                    fit)
-    };
+    let mut cursor: Option<PhysicalPosition<f64>> = None;
+    let mut panning = false;
+    let mut moved: Option<Instant> = None;
+    let mut rotated = Instant::now();
+    let rotate = event_loop.create_proxy();
 
     event_loop.run(move |event,
                          _,
@@ -239,51 +341,284 @@ fn main() -> Result<(), Box<dyn Error>>
                     .as_secs_f64();
                 last_frame = Instant::now();
 
-                model
+                scene
                 .update(elapsed)
                 .unwrap_or_else(|e| eprintln!("Failed to update model: {e}"));
+                background.update(elapsed,
+                                  &gl);
+
+                // Saved once zoom and pan settle, not on every move
+                if moved.is_some_and(|t| t.elapsed() >= SETTLE) {
+                    moved = None;
+                    config
+                    .store_view()
+                    .unwrap_or_else(|e| eprintln!("Failed to save view: {e}"));
+                }
+
+                let rotation = &config.library.rotation;
+                let due = rotated.elapsed().as_secs_f64() >= rotation.minutes * 60.;
+                if rotation.variant == RotationType::Timer && due {
+                    rotated = Instant::now();
+
+                    if let Some(e) = library.random(rotation) {
+                        rotate
+                        .send_event(Message::Load(Load {
+                            file:  e.file.clone(),
+                            open:  Vec::new(),
+                            reply: Reply::default(),
+                        }))
+                        .unwrap_or_else(|e| eprintln!("Failed to send \
+                                                       message: {e}"));
+                    }
+                }
+            }
+            Event::UserEvent(msg) => {
+                let (target, msg) = match msg {
+                    Message::To(id, m) => (Some(id), *m),
+                    m                  => (None, m),
+                };
+                let target = target.as_deref();
+
+                match msg {
+                    Message::Exit       => control_flow.set_exit(),
+                    Message::Changed(p) => match scene.reload(&config,
+                                                              &gl,
+                                                              &p) {
+                        Ok(true)  => match watch(scene.files(),
+                                                 changed.clone()) {
+                            Ok(w)  => _watcher = w,
+                            Err(e) => eprintln!("Failed to watch model: {e}"),
+                        }
+                        Ok(false) => {}
+                        Err(e)    => eprintln!("Failed to reload {}: {e}",
+                                               p.display()),
+                    }
+                    Message::Load(l)    => {
+                        let result = scene.load(target,
+                                                &config,
+                                                &gl,
+                                                &l.file,
+                                                &l.open)
+                                     .map_err(|e| format!("Failed to load \
+                                                           {}: {e}",
+                                                          l.file.display()));
+
+                        match &result {
+                            Ok(_)  => {
+                                library.locate(&l.file);
+
+                                match watch(scene.files(),
+                                            changed.clone()) {
+                                    Ok(w)  => _watcher = w,
+                                    Err(e) => eprintln!("Failed to watch model: {e}"),
+                                }
+                            }
+                            Err(e) => eprintln!("{e}"),
+                        }
+
+                        l.reply.answer(result.map(|_| String::new()));
+                    }
+                    Message::Library(s, r) => {
+                        let rotation = &config.library.rotation;
+                        let entry = match s {
+                            Shelf::List   => {r.answer(Ok(library.list())); return}
+                            Shelf::Models => {
+                                let models =
+                                    serde_json::to_string(&library.models())
+                                    .map_err(|e| e.to_string());
+
+                                r.answer(models);
+                                return
+                            }
+                            Shelf::Random => library.random(rotation),
+                            Shelf::Next   => library.next(rotation),
+                        };
+
+                        // Loaded same as bleed load would do it
+                        let load = |e: &library::Entry, r| Message::Load(Load {
+                            file:  e.file.clone(),
+                            open:  Vec::new(),
+                            reply: r,
+                        }).to(target);
+
+                        match entry {
+                            Some(e) => rotate
+                                       .send_event(load(e, r))
+                                       .unwrap_or_else(|e| eprintln!("Failed \
+                                                                      to send \
+                                                                      message: \
+                                                                      {e}")),
+                            None    => r.answer(Err("No models in library"
+                                                    .to_string())),
+                        }
+                    }
+                    Message::Background(b, r) => {
+                        // Stays until the config is reloaded
+                        let result =
+                            config.window.bg
+                            .change(&b)
+                            .and_then(|bg| {
+                                background = Background::new(&bg,
+                                                             &gl)?;
+                                config.window.bg = bg;
+                                Ok(())
+                            })
+                            .map_err(|e| format!("Failed to change \
+                                                  background: {e}"));
+
+                        if let Err(e) = &result {eprintln!("{e}")}
+                        r.answer(result.map(|_| String::new()));
+                    }
+                    Message::Effects(e, r)    => {
+                        // Stays until the config is reloaded
+                        let result =
+                            config.window.effects
+                            .change(&e)
+                            .map(|new| {
+                                effects.configure(&new);
+                                config.window.effects = new;
+                            })
+                            .map_err(|e| format!("Failed to change \
+                                                  effects: {e}"));
+
+                        if let Err(e) = &result {eprintln!("{e}")}
+                        r.answer(result.map(|_| String::new()));
+                    }
+                    Message::Color(c)         => {
+                        let result =
+                            scene.select(target)
+                            .and_then(|models| recolor(models,
+                                                       &c));
+
+                        if let Err(e) = &result {eprintln!("{e}")}
+                        c.reply.answer(result);
+                    }
+                    Message::Part(o, r)       => {
+                        let result =
+                            scene.select(target)
+                            .and_then(|models| dress(models,
+                                                     &o));
+
+                        if let Err(e) = &result {eprintln!("{e}")}
+                        r.answer(result);
+                    }
+                    Message::Param(p)         => {
+                        let result =
+                            scene.select(target)
+                            .and_then(|models| drive(models,
+                                                     &p));
+
+                        if let Err(e) = &result {eprintln!("{e}")}
+                        p.reply.answer(result);
+                    }
+                    Message::Expression(m, r) => {
+                        let result =
+                            scene.select(target)
+                            .and_then(|models| express(models,
+                                                       &m));
+
+                        if let Err(e) = &result {eprintln!("{e}")}
+                        r.answer(result);
+                    }
+                    Message::Info(r)          => {
+                        let result =
+                            scene.model(target)
+                            .and_then(|m| serde_json::to_string(&m.info())
+                                          .map_err(|e| e.to_string()));
+
+                        r.answer(result);
+                    }
+                    Message::Calibrate(r)     => {
+                        let result =
+                            scene.select(target)
+                            .and_then(calibrate);
+
+                        if let Err(e) = &result {eprintln!("{e}")}
+                        r.answer(result);
+                    }
+                    Message::Speak(f, r)      => {
+                        let result =
+                            scene.select(target)
+                            .and_then(|models| speak(models,
+                                                     &f,
+                                                     &config));
+
+                        if let Err(e) = &result {eprintln!("{e}")}
+                        r.answer(result);
+                    }
+                    Message::Vts(a, r)        => r.answer(gate.answer(&a)),
+                    Message::State(c, r)      => {
+                        let result =
+                            scene.select(target)
+                            .and_then(|models| cue(models,
+                                                   &c));
+
+                        if let Err(e) = &result {eprintln!("{e}")}
+                        r.answer(result);
+                    }
+                    Message::Reload     => {
+                        reload(&mut config,
+                               &display,
+                               &gl,
+                               &mut scene,
+                               &mut background,
+                               &mut effects,
+                               &mut library)
+                        .unwrap_or_else(|e| eprintln!("Failed to reload \
+                                                       config: {e}"));
+                    }
+                    m => match scene.select(target) {
+                        Ok(models) => models.into_iter()
+                                      .for_each(|model| control(model, &m)
+                                                        .unwrap_or(())),
+                        Err(e)     => eprintln!("{e}"),
+                    }
+                }
             }
-            Event::UserEvent(msg) => match msg {
-                    Message::SetMotion(m)   => model.set((m.0.as_str(),
-                                                          m.1.as_str())),
-                    Message::QueueMotion(m) => model.queue((m.0.as_str(),
-                                                            m.1.as_str())),
-                    Message::Toggle         => model.toggle(),
-                    Message::Pause          => model.pause(),
-                    Message::Play           => model.play(),
-                    #[allow(clippy::unit_arg)]
-                    Message::Exit           => Some(control_flow.set_exit()),
-                }.unwrap_or(()),
             Event::WindowEvent {event, ..} => match event {
                 WindowEvent::CloseRequested => control_flow.set_exit(),
-                WindowEvent::Resized(s) => {
-                    aspect = {
-                        let view = [
-                            s.width as f32,
-                            s.height as f32
-                        ];
-                        let object = model.size();
-                        let fit = &config.window.fit;
-
-                        calc_aspect(object,
-                                    view,
-                                    fit)
+                WindowEvent::KeyboardInput {..} => scene.poke(),
+                WindowEvent::MouseWheel {delta, ..} => {
+                    let lines = match delta {
+                        MouseScrollDelta::LineDelta(_, y) => y,
+                        MouseScrollDelta::PixelDelta(p)   => p.y as f32 / 100.,
                     };
-                    bg_aspect = {
-                        let view = [
-                            s.width as f32,
-                            s.height as f32
-                        ];
-                        let object =
-                            background_image.as_ref()
-                            .map(|bg| bg.size)
-                            .unwrap_or([1., 1.]);
-                        let fit = &FitConfig::Cover;
-
-                        calc_aspect(object,
+
+                    config.window.scale *= ZOOM.powf(lines);
+                    moved = Some(Instant::now());
+                    scene.poke();
+                }
+                WindowEvent::MouseInput {
+                    button: MouseButton::Middle,
+                    state,
+                    ..
+                } => panning = state == ElementState::Pressed,
+                WindowEvent::MouseInput {
+                    button: MouseButton::Left,
+                    state:  ElementState::Pressed,
+                    ..
+                } => {
+                    // Clip space, same as the models are drawn in
+                    if let Some(p) = cursor {
+                        let view = view(&display);
+                        let point = [p.x as f32 / view[0] * 2. - 1.,
+                                     1. - p.y as f32 / view[1] * 2.];
+                        scene.touch(point,
                                     view,
-                                    fit)
-                    };
+                                    &config);
+                    }
+                    scene.poke();
+                }
+                WindowEvent::CursorMoved {position, ..} => {
+                    if let (true, Some(last)) = (panning, cursor) {
+                        let [w, h] = view(&display);
+                        let offset = &mut config.window.offset;
+                        offset[0] += (position.x - last.x) as f32 * 2. / w;
+                        offset[1] -= (position.y - last.y) as f32 * 2. / h;
+                        moved = Some(Instant::now());
+                    }
+                    cursor = Some(position);
+                    scene.poke();
                 }
                 _ => {}
             }
@@ -296,35 +631,19 @@ fn main() -> Result<(), Box<dyn Error>>
                 //  \__,_|_|  \__,_| \_/\_/   |___/\__\__,_|_|   \__|
 
                 let mut frame = display.draw();
+                let mut renderer = gl.renderer(&mut frame);
+                let view = view(&display);
 
-                match &config.window.bg.variant {
-                    BgType::Image => {
-                        let bg =
-                            background_image.as_ref()
-                            .expect("image to be here");
-                        let uniforms = uniform!{
-                            tex: &bg.texture,
-                            aspect: bg_aspect,
-                        };
-
-                        frame.draw(&bg.vertex_buffer,
-                                   &bg.index_buffer,
-                                   &programs[PV::Background as usize],
-                                   &uniforms,
-                                   &Default::default())
-                        .unwrap_or_else(|e| eprintln!("{e}"))
-                    }
-                    BgType::Color => {
-                        let c = config.window.bg.color;
-                        frame.clear_color(c[0], c[1], c[2], c[3])
-                    }
-                }
-
-                model
-                .draw(&mut frame,
-                      &programs,
-                      aspect)
-                .unwrap_or_else(|e| eprintln!("Failed to draw model: {e}"));
+                background
+                .draw(&mut renderer,
+                      view,
+                      &config.window.fit)
+                .and_then(|_| effects.draw(&mut renderer,
+                                           view,
+                                           |r| scene.draw(r,
+                                                          view,
+                                                          &config)))
+                .unwrap_or_else(|e| eprintln!("Failed to draw: {e}"));
 
                 frame
                 .finish()
@@ -344,20 +663,329 @@ fn main() -> Result<(), Box<dyn Error>>
     });
 }
 
+//  ____             _        _
+// / ___|  ___   ___| | _____| |_
+// \___ \ / _ \ / __| |/ / _ \ __|
+//  ___) | (_) | (__|   <  __/ |_
+// |____/ \___/ \___|_|\_\___|\__|
+
+fn listen<F>(send: F) -> Result<(), Box<dyn Error>>
+where F: Fn(Message) -> Result<(), String> + Send + 'static
+{
+    let path = Path::new(SOCKET_ADDR);
+
+    if path.exists() {
+        eprintln!("Removing existing socket before connecting");
+        fs::remove_file(path)?
+    }
+
+    let listener = UnixListener::bind(path)?;
+
+    thread::spawn(move || {
+        listener.incoming()
+        .for_each(|stream| {
+            stream
+            .map_err(|e| format!("Socket connection error: {e}"))
+            .and_then(|mut s| {
+                let mut input = String::new();
+                s.read_to_string(&mut input).unwrap_or(0);
+
+                let mut message =
+                    Message::parse(input)
+                    .ok_or("Failed to parse message".to_string())?;
+
+                // Some messages are answered, bleed waits for that
+                let (tx, rx) = mpsc::channel();
+                match message.reply() {
+                    Some(r) => *r = Reply::new(tx),
+                    None    => return send(message),
+                }
+                send(message)?;
+
+                let reply = match rx.recv() {
+                    Ok(Ok(r))  => format!("ok:{r}"),
+                    Ok(Err(e)) => format!("error:{e}"),
+                    Err(_)     => "error:No answer".to_string(),
+                };
+
+                write!(s, "{reply}")
+                .map_err(|e| format!("Failed to reply: {e}"))
+            })
+            .unwrap_or_else(|e| eprintln!("{e}"))
+        });
+    });
+
+    Ok(())
+}
+
+// Exit is up to the caller, as it is different for a window and for a pipe,
+// and so are reloads and loads
+fn control<D>(model: &mut Model<D>,
+              msg:   &Message) -> Option<()>
+where D: Device
+{
+    match msg {
+        Message::SetMotion(m)   => model.set((m.0.as_str(),
+                                              m.1.as_str())),
+        Message::QueueMotion(m) => model.queue((m.0.as_str(),
+                                                m.1.as_str())),
+        Message::Toggle         => model.toggle(),
+        Message::Pause          => model.pause(),
+        Message::Play           => model.play(),
+        Message::Exit           => None,
+        Message::Reload         => None,
+        Message::Load(_)        => None,
+        Message::Library(..)    => None,
+        Message::Background(..) => None,
+        Message::Effects(..)    => None,
+        Message::Color(_)       => None,
+        Message::Part(..)       => None,
+        Message::Param(_)       => None,
+        Message::Track(t)       => {model.track(t); Some(())}
+        Message::Calibrate(_)   => None,
+        Message::Speak(..)      => None,
+        Message::State(..)      => None,
+        Message::Vts(..)        => None,
+        Message::Voice(l, f)    => {model.hear(*l, *f); Some(())}
+        Message::Expression(..) => None,
+        Message::Info(_)        => None,
+        Message::Changed(_)     => None,
+        Message::To(..)         => None,
+    }
+}
+
+//  ____                _
+// |  _ \ ___  ___ ___ | | ___  _ __
+// | |_) / _ \/ __/ _ \| |/ _ \| '__|
+// |  _ <  __/ (_| (_) | | (_) | |
+// |_| \_\___|\___\___/|_|\___/|_|
+
+// Stays until the config is reloaded, same as the other changes from bleed
+fn recolor<'a, D>(models:  Vec<&'a mut Model<D>>,
+                  recolor: &Recolor) -> Result<String, String>
+where D: Device + 'a
+{
+    let rgba =
+        recolor.color.as_deref()
+        .map(color)
+        .transpose()
+        .map_err(|e| format!("Failed to recolor: {e}"))?;
+
+    for model in models {
+        model.set_color(&recolor.target,
+                        recolor.tint,
+                        rgba)?;
+    }
+
+    Ok(String::new())
+}
+
 //  ____
-// |  _ \ _ __ ___   __ _ _ __ __ _ _ __ ___  ___
-// | |_) | '__/ _ \ / _` | '__/ _` | '_ ` _ \/ __|
-// |  __/| | | (_) | (_| | | | (_| | | | | | \__ \
-// |_|   |_|  \___/ \__, |_|  \__,_|_| |_| |_|___/
-//                  |___/
-
-#[derive(Copy, Clone)]
-enum ProgramVariant {
-    Background = 0,
-    Mask,
-    BlendNormal,
-
-    Counter
+// |  _ \ _ __ ___  ___ ___
+// | | | | '__/ _ \/ __/ __|
+// | |_| | | |  __/\__ \__ \
+// |____/|_|  \___||___/___/
+
+// Parts are shown and hidden until the config is reloaded, and listed for
+// every model asked
+fn dress<'a, D>(models: Vec<&'a mut Model<D>>,
+                outfit: &Outfit) -> Result<String, String>
+where D: Device + 'a
+{
+    let mut list = String::new();
+
+    for model in models {
+        match outfit {
+            Outfit::List           => list += &model.parts(),
+            Outfit::Show(id)       => model.set_part(id, Some(1.))?,
+            Outfit::Hide(id)       => model.set_part(id, Some(0.))?,
+            Outfit::Opacity(o, id) => model.set_part(id, Some(*o))?,
+            Outfit::Reset(id)      => model.set_part(id, None)?,
+        }
+    }
+
+    Ok(list)
+}
+
+//  ____       _
+// |  _ \ _ __(_)_   _____
+// | | | | '__| \ \ / / _ \
+// | |_| | |  | |\ V /  __/
+// |____/|_|  |_| \_/ \___|
+
+fn drive<'a, D>(models: Vec<&'a mut Model<D>>,
+                puppet: &Puppet) -> Result<String, String>
+where D: Device + 'a
+{
+    for model in models {
+        model.drive(puppet)?;
+    }
+
+    Ok(String::new())
+}
+
+//  _____
+// | ____|_  ___ __  _ __ ___  ___ ___
+// |  _| \ \/ / '_ \| '__/ _ \/ __/ __|
+// | |___ >  <| |_) | | |  __/\__ \__ \
+// |_____/_/\_\ .__/|_|  \___||___/___/
+//            |_|
+
+// Expressions are listed for every model asked
+fn express<'a, D>(models: Vec<&'a mut Model<D>>,
+                  mood:   &Mood) -> Result<String, String>
+where D: Device + 'a
+{
+    let mut list = String::new();
+
+    for model in models {
+        list += &model.express(mood)?;
+    }
+
+    Ok(list)
+}
+
+//   ____      _ _ _               _
+//  / ___|__ _| (_) |__  _ __ __ _| |_ ___
+// | |   / _` | | | '_ \| '__/ _` | __/ _ \
+// | |__| (_| | | | |_) | | | (_| | ||  __/
+//  \____\__,_|_|_|_.__/|_|  \__,_|\__\___|
+
+// Every model is told the same pose, and lists it
+fn calibrate<D>(models: Vec<&mut Model<D>>) -> Result<String, String>
+where D: Device
+{
+    let mut list = String::new();
+
+    for model in models {
+        list += &model.calibrate()?;
+    }
+
+    Ok(list)
+}
+
+//  ____                   _
+// / ___| _ __   ___  __ _| | __
+// \___ \| '_ \ / _ \/ _` | |/ /
+//  ___) | |_) |  __/ (_| |   <
+// |____/| .__/ \___|\__,_|_|\_\
+//       |_|
+
+// Decoded once, every model says it from the start
+fn speak<D>(models: Vec<&mut Model<D>>,
+            file:   &Path,
+            config: &Config) -> Result<String, String>
+where D: Device
+{
+    let clip =
+        Clip::load(file,
+                   &config.lipsync.pcm)
+        .map_err(|e| format!("Failed to load {}: {e}", file.display()))?;
+
+    models.into_iter()
+    .for_each(|m| m.say(clip.clone()));
+
+    Ok(String::new())
+}
+
+//   ____
+//  / ___|   _  ___
+// | |  | | | |/ _ \
+// | |__| |_| |  __/
+//  \____\__,_|\___|
+
+// States are told for every model asked
+fn cue<'a, D>(models: Vec<&'a mut Model<D>>,
+              cue:    &Cue) -> Result<String, String>
+where D: Device + 'a
+{
+    let mut list = String::new();
+
+    for model in models {
+        list += &model.cue(cue)?;
+    }
+
+    Ok(list)
+}
+
+//  ____      _                 _
+// |  _ \ ___| | ___   __ _  __| |
+// | |_) / _ \ |/ _ \ / _` |/ _` |
+// |  _ <  __/ | (_) | (_| | (_| |
+// |_| \_\___|_|\___/ \__,_|\__,_|
+
+// Whatever can be changed on the fly is, the rest needs a restart
+fn reload(config:     &mut Config,
+          display:    &Hack,
+          gl:         &Gl,
+          scene:      &mut Scene,
+          background: &mut Background,
+          effects:    &mut Effects,
+          library:    &mut Library) -> Result<(), Box<dyn Error>>
+{
+    let mut new = Config::load()?;
+
+    // Zoom and pan from the window stay, unless the config has new ones
+    if new.file == config.file {
+        new.window.scale = config.window.scale;
+        new.window.offset = config.window.offset;
+    } else {
+        new.store_view()?;
+    }
+
+    // Slideshow and animation go on, unless there is something new to show
+    let new_background = match new.window.bg != config.window.bg {
+        true  => Some(Background::new(&new.window.bg,
+                                      gl)?),
+        false => None,
+    };
+
+    if new.model.file != config.model.file {
+        eprintln!("Model file changed, restart to load it");
+    }
+    if new.scene.models.len() != config.scene.models.len() {
+        eprintln!("Scene models changed, restart to load them");
+    }
+    if new.model.motions.open != config.model.motions.open {
+        eprintln!("Open motions changed, they only play on start");
+    }
+    if new.tracking.vmc != config.tracking.vmc {
+        eprintln!("VMC tracking changed, restart to apply it");
+    }
+    if new.tracking.osf != config.tracking.osf {
+        eprintln!("OpenSeeFace tracking changed, restart to apply it");
+    }
+    if new.vts != config.vts {
+        eprintln!("Plugin API changed, restart to apply it");
+    }
+    if new.lipsync.audio != config.lipsync.audio {
+        eprintln!("Audio changed, restart to apply it");
+    }
+    if new.lipsync.pipe != config.lipsync.pipe
+    || new.lipsync.pcm != config.lipsync.pcm {
+        eprintln!("Lip sync pipe changed, restart to apply it");
+    }
+    if new.window.title != config.window.title {
+        display.set_title(&new.window.title);
+    }
+    if new.window.size != config.window.size {
+        display.set_size(new.window.size);
+    }
+
+    if new.library.paths != config.library.paths {
+        *library = Library::scan(&new);
+        library.locate(scene.model(None)?.path());
+    }
+
+    if let Some(b) = new_background {
+        *background = b;
+    }
+    effects.configure(&new.window.effects);
+    scene.configure(&new);
+    *config = new;
+
+    eprintln!("Reloaded config");
+    Ok(())
 }
 
 //  _   _            _
@@ -379,6 +1007,25 @@ impl Hack {
             Hack::Display(d)     => d.draw(),
         }
     }
+
+    // Xsecurelock window is not ours to change
+    fn set_title(&self,
+                 title: &str)
+    {
+        if let Hack::Display(d) = self {
+            d.gl_window().window().set_title(title)
+        }
+    }
+
+    fn set_size(&self,
+                size: [u32; 2])
+    {
+        if let Hack::Display(d) = self {
+            let [width, height] = size;
+            d.gl_window().window().set_inner_size(LogicalSize::new(width,
+                                                                   height))
+        }
+    }
 }
 
 impl Facade for Hack {
@@ -391,63 +1038,54 @@ impl Facade for Hack {
     }
 }
 
-//  ____             _                                   _
-// | __ )  __ _  ___| | ____ _ _ __ ___  _   _ _ __   __| |
-// |  _ \ / _` |/ __| |/ / _` | '__/ _ \| | | | '_ \ / _` |
-// | |_) | (_| | (__|   < (_| | | | (_) | |_| | | | | (_| |
-// |____/ \__,_|\___|_|\_\__, |_|  \___/ \__,_|_| |_|\__,_|
-//                       |___/
-
-struct Background {
-    texture:       SrgbTexture2d,
-    vertex_buffer: VertexBuffer<Vert>,
-    index_buffer:  IndexBuffer<u16>,
-    size:          [f32; 2],
+//            _
+//  _ _    __| |_ __ __ ___      __
+// (_|_)  / _` | '__/ _` \ \ /\ / /
+//  _ _  | (_| | | | (_| |\ V  V /
+// (_|_)  \__,_|_|  \__,_| \_/\_/
+
+fn draw<D>(renderer:   &mut dyn Renderer<Device = D>,
+           config:     &Config,
+           background: &Background<D>,
+           effects:    &mut Effects<D>,
+           model:      &Model<D>,
+           view:       [f32; 2]) -> Result<(), Box<dyn Error>>
+where D: Device + Clone
+{
+    background.draw(renderer,
+                    view,
+                    &config.window.fit)?;
+
+    let [aspect, offset] = calc_framing(model,
+                                        view,
+                                        config);
+
+    effects.draw(renderer,
+                 view,
+                 |r| model.draw(r,
+                                aspect,
+                                offset))
+    .map_err(|e| format!("Failed to draw model: {e}"))?;
+
+    Ok(())
 }
 
-impl Background {
-    fn new<T>(bg: &str,
-              display: &T) -> Result<Self, Box<dyn Error>>
-    where T: Facade
-    {
-        let bg_path = expanduser::expanduser(bg)?;
-
-        let image =
-            image::open(bg_path)?
-            .to_rgba8();
-        let image_dimensions = image.dimensions();
-        let (x, y) = (image_dimensions.0 as f32,
-                      image_dimensions.1 as f32);
-        let size = [x, y];
-        let (rx, ry) = (x/y, 1.);
-        let image_raw =
-            RawImage2d::from_raw_rgba_reversed(&image.into_raw(),
-                                               image_dimensions);
-
-        let texture = SrgbTexture2d::new(display,
-                                         image_raw)?;
-
-        let vertices = vec![
-            Vert::new([-rx, -ry], [0., 0.]),
-            Vert::new([-rx,  ry], [0., 1.]),
-            Vert::new([ rx, -ry], [1., 0.]),
-            Vert::new([ rx,  ry], [1., 1.]),
-        ];
-        let indices: Vec<u16> = vec![0, 1, 2, 3];
-
-        let vertex_buffer = VertexBuffer::new(display,
-                                              &vertices)?;
-        let index_buffer = IndexBuffer::new(display,
-                                            PrimitiveType::TriangleStrip,
-                                            &indices)?;
-
-        Ok(Self {
-            texture,
-            vertex_buffer,
-            index_buffer,
-            size,
-        })
-    }
+//              _
+//  _ _  __   _(_) _____      __
+// (_|_) \ \ / / |/ _ \ \ /\ / /
+//  _ _   \ V /| |  __/\ V  V /
+// (_|_)   \_/ |_|\___| \_/\_/
+
+fn view<T>(display: &T) -> [f32; 2]
+where T: Facade
+{
+    let frame =
+        display
+        .get_context()
+        .get_framebuffer_dimensions();
+
+    [frame.0 as f32,
+     frame.1 as f32]
 }
 
 //                                  _
@@ -470,5 +1108,43 @@ fn calc_aspect(object: [f32; 2],
     match fit {
         FitConfig::Contain => if mode {plus_w} else {plus_h}
         FitConfig::Cover   => if mode {plus_h} else {plus_w}
+        FitConfig::Fill    => [1., 1.],
+        FitConfig::None    => [object[0]/view[0], object[1]/view[1]],
     }
 }
+
+// Aspect and offset, with zoom and anchor on top of the fit and then the pan.
+// Fit, zoom and anchor go by the frame of the model, which is all the canvas
+// unless the model has a layout
+fn calc_framing<D>(model:  &Model<D>,
+                   view:   [f32; 2],
+                   config: &Config) -> [[f32; 2]; 2]
+where D: Device
+{
+    let window = &config.window;
+    let canvas = model.size();
+    let [frame, middle] = model.frame(config.model.layout);
+
+    let fitted =
+        calc_aspect(frame,
+                    view,
+                    &window.fit)
+        .map(|a| a * window.scale);
+
+    let [x, y] = fitted;
+    let anchor = match window.anchor {
+        Anchor::Center => [0.,      0.],
+        Anchor::Top    => [0.,      1. - y],
+        Anchor::Bottom => [0.,      y - 1.],
+        Anchor::Left   => [x - 1.,  0.],
+        Anchor::Right  => [1. - x,  0.],
+    };
+
+    // Canvas is bigger than the frame, and its middle is elsewhere
+    let aspect = [0, 1].map(|i| fitted[i] * canvas[i] / frame[i]);
+    let offset = [0, 1].map(|i| {
+        anchor[i] + window.offset[i] - middle[i] * 2. / frame[i] * fitted[i]
+    });
+
+    [aspect, offset]
+}
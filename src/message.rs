@@ -1,19 +1,73 @@
 #![allow(dead_code)]
-use std::fmt::{
-    Error,
-    Display,
-    Formatter,
+use std::{
+    str::Split,
+    path::PathBuf,
+    sync::mpsc::Sender,
+    fmt::{
+        Error,
+        Display,
+        Formatter,
+    },
 };
+use serde::{Serialize, Deserialize};
 
 pub const SOCKET_ADDR: &str = "/run/user/1000/liver.sock";
 pub const USAGE:       &str =
 "
-Usage:
+Usage: bleed [-m <id>] <command>
+
+    -m <id>                 send to model <id> of the scene, rather than all
     queue [class] <motion>  queue <motion> from <class>
     set [class] <motion>    set <motion> from <class>
     play                    resume animation
     pause                   pause animation
     toggle                  toggle animation
+    reload                  reload the configuration
+    load <file> [-open ..]  load model from <file>, playing open motions
+    library <action>        list library, or load a random or next model
+    background <variant> .. change background to one of:
+        color RRGGBB[AA]
+        image <file>
+        slideshow <dir>
+        gradient {vertical|radial} RRGGBB[AA] RRGGBB[AA]
+    effects <effect> ..     turn on or change one of the effects:
+        outline <width> RRGGBB[AA]
+        shadow <blur> <x> <y> RRGGBB[AA]
+        glow <radius> <strength>
+        grade <hue> <saturation> <brightness>
+        off [outline|shadow|glow|grade|all]
+    color <id> <color>      recolor part or drawable <id>, <color> being
+                            RRGGBB[AA] or clear, --screen after it sets
+                            the screen color rather than the multiply one
+    part list               list parts with their names and opacities
+    part <id> <action>      change part <id> with one of:
+        show
+        hide
+        opacity <value>
+        reset               back to what the model does with it
+    param <id> <value> ..   drive parameter <id>, adding <value> to what
+                            motions do, with any of:
+        --over <time>       tween over <time>, as in 0.4s or 400ms
+        --ease <ease>       linear, in, out or in-out
+        --hold              hold <value> rather than adding to it
+    param <id> --release .. give parameter <id> back to motions, tweened
+                            same as above
+    expression <name>       set expression <name>, --toggle after it
+                            clears it if it is set already
+    expression list         list expressions, the one set marked
+    expression clear        go back to no expression
+    info                    print what the model has, as JSON
+    calibrate               take the tracked pose as the one the model
+                            rests in, printing it for the config
+    speak <file>            move the mouth to <file>, WAV, Ogg or raw
+                            PCM, without playing it
+    state                   print the state of the motion graph
+    state list              list states, the current one marked
+    state set <name>        go to state <name>
+    state trigger <name>    set off transitions on trigger <name>
+    vts                     list plugins waiting for a token
+    vts allow <plugin>      let <plugin> in
+    vts deny <plugin>       turn <plugin> away
     exit                    exit the application
     help                    print this info and quit
 ";
@@ -32,6 +86,30 @@ pub enum Message {
     Pause,
     Play,
     Exit,
+    Reload,
+    Load(Load),
+    Library(Shelf, Reply),
+    Background(Backdrop, Reply),
+    Effects(Effect, Reply),
+    Color(Recolor),
+    Part(Outfit, Reply),
+    Param(Puppet),
+    Expression(Mood, Reply),
+    Info(Reply),
+    // Parameter values from trackers, held for as long as they keep coming
+    Track(Vec<(String, f32)>),
+    Calibrate(Reply),
+    Speak(PathBuf, Reply),
+    State(Cue, Reply),
+    Vts(Access, Reply),
+    // Level and form of the voice from the pipe, held for as long as it
+    // keeps coming
+    Voice(f32, f32),
+    // Sent by liver itself when one of the model files changes, not parsed
+    // from nor written to the socket
+    Changed(PathBuf),
+    // For one model of the scene, rather than all of them
+    To(String, Box<Message>),
 }
 
 //  __  __
@@ -47,11 +125,213 @@ impl Message {
         let mut message = input.split(':');
 
         match message.next()? {
-            "toggle" => Some(Message::Toggle),
-            "pause"  => Some(Message::Pause),
-            "play"   => Some(Message::Play),
-            "exit"   => Some(Message::Exit),
-            "queue"  => {
+            "toggle"     => Some(Message::Toggle),
+            "pause"      => Some(Message::Pause),
+            "play"       => Some(Message::Play),
+            "exit"       => Some(Message::Exit),
+            "reload"     => Some(Message::Reload),
+            "load"       => {
+                let open =
+                    message.next()?
+                    .split(',')
+                    .filter(|m| !m.is_empty())
+                    .map(|m| {
+                        let mut s = m.rsplit('/');
+                        let m = s.next().unwrap_or("").to_string();
+                        let c = s.next().unwrap_or("").to_string();
+                        (c, m)
+                    })
+                    .collect();
+
+                // Path goes last, it may have colons of its own
+                let file: Vec<_> = message.collect();
+
+                Some(Message::Load(Load {
+                    file:  file.join(":").into(),
+                    open,
+                    reply: Reply::default(),
+                }))
+            }
+            "to"         => {
+                let id = message.next()?.to_string();
+                let rest: Vec<_> = message.collect();
+                let m = Message::parse(rest.join(":"))?;
+
+                Some(Message::To(id, Box::new(m)))
+            }
+            "library"    => {
+                let shelf = match message.next()? {
+                    "list"   => Shelf::List,
+                    "models" => Shelf::Models,
+                    "random" => Shelf::Random,
+                    "next"   => Shelf::Next,
+                    _        => return None,
+                };
+
+                Some(Message::Library(shelf, Reply::default()))
+            }
+            "background" => {
+                let backdrop = match message.next()? {
+                    "color"     => Backdrop::Color(message.next()?.to_string()),
+                    "gradient"  => Backdrop::Gradient(
+                        message.next()?.to_string(),
+                        [message.next()?.to_string(),
+                         message.next()?.to_string()]),
+                    // Paths may have colons of their own
+                    "image"     => Backdrop::Image(
+                        message.collect::<Vec<_>>().join(":").into()),
+                    "slideshow" => Backdrop::Slideshow(
+                        message.collect::<Vec<_>>().join(":").into()),
+                    _           => return None,
+                };
+
+                Some(Message::Background(backdrop, Reply::default()))
+            }
+            "effects"    => {
+                let number = |m: &mut Split<'_, char>| m.next()?.parse().ok();
+                let m = &mut message;
+
+                let effect = match m.next()? {
+                    "outline" => Effect::Outline(number(m)?,
+                                                 m.next()?.to_string()),
+                    "shadow"  => Effect::Shadow(number(m)?,
+                                                [number(m)?, number(m)?],
+                                                m.next()?.to_string()),
+                    "glow"    => Effect::Glow(number(m)?,
+                                              number(m)?),
+                    "grade"   => Effect::Grade(number(m)?,
+                                               number(m)?,
+                                               number(m)?),
+                    "off"     => Effect::Off(m.next()?.to_string()),
+                    _         => return None,
+                };
+
+                Some(Message::Effects(effect, Reply::default()))
+            }
+            "color"      => {
+                let tint = match message.next()? {
+                    "multiply" => Tint::Multiply,
+                    "screen"   => Tint::Screen,
+                    _          => return None,
+                };
+                let color = match message.next()? {
+                    "clear" => None,
+                    c       => Some(c.to_string()),
+                };
+
+                Some(Message::Color(Recolor {
+                    target: message.collect::<Vec<_>>().join(":"),
+                    tint,
+                    color,
+                    reply:  Reply::default(),
+                }))
+            }
+            "part"       => {
+                let outfit = match message.next()? {
+                    "list"    => Outfit::List,
+                    "show"    => Outfit::Show(message.next()?.to_string()),
+                    "hide"    => Outfit::Hide(message.next()?.to_string()),
+                    "opacity" => Outfit::Opacity(
+                        message.next()?.parse().ok()?,
+                        message.next()?.to_string()),
+                    "reset"   => Outfit::Reset(message.next()?.to_string()),
+                    _         => return None,
+                };
+
+                Some(Message::Part(outfit, Reply::default()))
+            }
+            "param"      => {
+                let value = match message.next()? {
+                    "release" => None,
+                    v         => Some(v.parse().ok()?),
+                };
+                let over = message.next()?.parse().ok()?;
+                let ease = Ease::parse(message.next()?)?;
+                let hold = match message.next()? {
+                    "hold" => true,
+                    "add"  => false,
+                    _      => return None,
+                };
+
+                Some(Message::Param(Puppet {
+                    id: message.collect::<Vec<_>>().join(":"),
+                    value,
+                    over,
+                    ease,
+                    hold,
+                    reply: Reply::default(),
+                }))
+            }
+            "expression" => {
+                let action = message.next()?;
+                // Names may have colons of their own
+                let name = message.collect::<Vec<_>>().join(":");
+
+                let mood = match action {
+                    "list"   => Mood::List,
+                    "clear"  => Mood::Clear,
+                    "set"    => Mood::Set(name),
+                    "toggle" => Mood::Toggle(name),
+                    _        => return None,
+                };
+
+                Some(Message::Expression(mood, Reply::default()))
+            }
+            "info"       => Some(Message::Info(Reply::default())),
+            "calibrate"  => Some(Message::Calibrate(Reply::default())),
+            "speak"      => {
+                // Paths may have colons of their own
+                let file: Vec<_> = message.collect();
+                Some(Message::Speak(file.join(":").into(),
+                                    Reply::default()))
+            }
+            "state"      => {
+                let action = message.next()?;
+                // Names may have colons of their own
+                let name = message.collect::<Vec<_>>().join(":");
+
+                let cue = match action {
+                    "get"     => Cue::Get,
+                    "list"    => Cue::List,
+                    "set"     => Cue::Set(name),
+                    "trigger" => Cue::Trigger(name),
+                    _         => return None,
+                };
+
+                Some(Message::State(cue, Reply::default()))
+            }
+            "vts"        => {
+                let action = message.next()?;
+                // Names may have colons of their own
+                let name = message.collect::<Vec<_>>().join(":");
+
+                let access = match action {
+                    "list"  => Access::List,
+                    "allow" => Access::Allow(name),
+                    "deny"  => Access::Deny(name),
+                    _       => return None,
+                };
+
+                Some(Message::Vts(access, Reply::default()))
+            }
+            "voice"      => {
+                let level = message.next()?.parse().ok()?;
+                let form = message.next()?.parse().ok()?;
+                Some(Message::Voice(level, form))
+            }
+            "track"      => {
+                let values =
+                    message.collect::<Vec<_>>().join(":")
+                    .split(',')
+                    .map(|v| {
+                        let (id, v) = v.rsplit_once('=')?;
+                        Some((id.to_string(), v.parse().ok()?))
+                    })
+                    .collect::<Option<_>>()?;
+
+                Some(Message::Track(values))
+            }
+            "queue"      => {
                 let first = message.next()?;
                 let result = match message.next() {
                     Some(second) => (first.to_string(),
@@ -62,7 +342,7 @@ impl Message {
 
                 Some(Message::QueueMotion(result))
             }
-            "set"    => {
+            "set"        => {
                 let first = message.next()?;
                 let result = match message.next() {
                     Some(second) => (first.to_string(),
@@ -76,6 +356,50 @@ impl Message {
             _ => None
         }
     }
+
+    //        _
+    //  _ _  | |_ ___
+    // (_|_) | __/ _ \
+    //  _ _  | || (_) |
+    // (_|_)  \__\___/
+
+    pub fn to(self,
+              target: Option<&str>) -> Self
+    {
+        match target {
+            Some(id) => Message::To(id.to_string(), Box::new(self)),
+            None     => self,
+        }
+    }
+
+    //                       _
+    //  _ _   _ __ ___ _ __ | |_   _
+    // (_|_) | '__/ _ \ '_ \| | | | |
+    //  _ _  | | |  __/ |_) | | |_| |
+    // (_|_) |_|  \___| .__/|_|\__, |
+    //                |_|      |___/
+
+    // Messages that bleed waits an answer to
+    pub fn reply(&mut self) -> Option<&mut Reply>
+    {
+        match self {
+            Message::Load(l)          => Some(&mut l.reply),
+            Message::Library(_, r)    => Some(r),
+            Message::Background(_, r) => Some(r),
+            Message::Effects(_, r)    => Some(r),
+            Message::Color(c)         => Some(&mut c.reply),
+            Message::Part(_, r)       => Some(r),
+            Message::Param(p)         => Some(&mut p.reply),
+            Message::Expression(_, r) => Some(r),
+            Message::Info(r)          => Some(r),
+            Message::Calibrate(r)     => Some(r),
+            Message::Speak(_, r)      => Some(r),
+            Message::State(_, r)      => Some(r),
+            Message::Vts(_, r)        => Some(r),
+            Message::To(_, m)         => m.reply(),
+            _                         => None,
+        }
+    }
 }
 
 impl Display for Message {
@@ -83,13 +407,366 @@ impl Display for Message {
            f: &mut Formatter<'_>) -> Result<(), Error>
     {
         match self {
-            Message::SetMotion(s)   => write!(f, "set:{}:{}", s.0, s.1),
-            Message::QueueMotion(s) => write!(f, "queue:{}:{}", s.0, s.1),
-            Message::Toggle         => write!(f, "toggle:"),
-            Message::Pause          => write!(f, "pause:"),
-            Message::Play           => write!(f, "play:"),
-            Message::Exit           => write!(f, "exit:"),
+            Message::SetMotion(s)     => write!(f, "set:{}:{}", s.0, s.1),
+            Message::QueueMotion(s)   => write!(f, "queue:{}:{}", s.0, s.1),
+            Message::Toggle           => write!(f, "toggle:"),
+            Message::Pause            => write!(f, "pause:"),
+            Message::Play             => write!(f, "play:"),
+            Message::Exit             => write!(f, "exit:"),
+            Message::Reload           => write!(f, "reload:"),
+            Message::Load(l)          => {
+                let open: Vec<_> =
+                    l.open.iter()
+                    .map(|(c, m)| format!("{c}/{m}"))
+                    .collect();
+
+                write!(f, "load:{}:{}", open.join(","), l.file.display())
+            }
+            Message::Library(s, _)    => write!(f, "library:{}", match s {
+                Shelf::List   => "list",
+                Shelf::Models => "models",
+                Shelf::Random => "random",
+                Shelf::Next   => "next",
+            }),
+            Message::Background(b, _) => write!(f, "background:{}", match b {
+                Backdrop::Color(c)       => format!("color:{c}"),
+                Backdrop::Image(p)       => format!("image:{}", p.display()),
+                Backdrop::Slideshow(p)   => format!("slideshow:{}", p.display()),
+                Backdrop::Gradient(v, c) => format!("gradient:{v}:{}:{}",
+                                                    c[0],
+                                                    c[1]),
+            }),
+            Message::Effects(e, _)    => write!(f, "effects:{}", match e {
+                Effect::Outline(w, c)   => format!("outline:{w}:{c}"),
+                Effect::Shadow(b, o, c) => format!("shadow:{b}:{}:{}:{c}",
+                                                   o[0],
+                                                   o[1]),
+                Effect::Glow(r, s)      => format!("glow:{r}:{s}"),
+                Effect::Grade(h, s, b)  => format!("grade:{h}:{s}:{b}"),
+                Effect::Off(e)          => format!("off:{e}"),
+            }),
+            Message::Color(c)         => write!(f, "color:{}:{}:{}",
+                                                match c.tint {
+                                                    Tint::Multiply => "multiply",
+                                                    Tint::Screen   => "screen",
+                                                },
+                                                c.color.as_deref()
+                                                .unwrap_or("clear"),
+                                                c.target),
+            Message::Part(o, _)       => write!(f, "part:{}", match o {
+                Outfit::List           => "list".to_string(),
+                Outfit::Show(id)       => format!("show:{id}"),
+                Outfit::Hide(id)       => format!("hide:{id}"),
+                Outfit::Opacity(v, id) => format!("opacity:{v}:{id}"),
+                Outfit::Reset(id)      => format!("reset:{id}"),
+            }),
+            Message::Param(p)         => write!(f, "param:{}:{}:{}:{}:{}",
+                                                match p.value {
+                                                    Some(v) => v.to_string(),
+                                                    None    => "release"
+                                                               .to_string(),
+                                                },
+                                                p.over,
+                                                p.ease.name(),
+                                                match p.hold {
+                                                    true  => "hold",
+                                                    false => "add",
+                                                },
+                                                p.id),
+            Message::Track(t)         => write!(f, "track:{}",
+                                                t.iter()
+                                                .map(|(id, v)| format!("{id}={v}"))
+                                                .collect::<Vec<_>>()
+                                                .join(",")),
+            Message::Expression(m, _) => write!(f, "expression:{}", match m {
+                Mood::List      => "list".to_string(),
+                Mood::Clear     => "clear".to_string(),
+                Mood::Set(n)    => format!("set:{n}"),
+                Mood::Toggle(n) => format!("toggle:{n}"),
+            }),
+            Message::Info(_)          => write!(f, "info"),
+            Message::Calibrate(_)     => write!(f, "calibrate"),
+            Message::Speak(p, _)      => write!(f, "speak:{}", p.display()),
+            Message::State(c, _)      => write!(f, "state:{}", match c {
+                Cue::Get        => "get".to_string(),
+                Cue::List       => "list".to_string(),
+                Cue::Set(n)     => format!("set:{n}"),
+                Cue::Trigger(n) => format!("trigger:{n}"),
+            }),
+            Message::Vts(a, _)        => write!(f, "vts:{}", match a {
+                Access::List     => "list".to_string(),
+                Access::Allow(p) => format!("allow:{p}"),
+                Access::Deny(p)  => format!("deny:{p}"),
+            }),
+            Message::Voice(l, v)      => write!(f, "voice:{l}:{v}"),
+            // Only ever sent by liver to itself, never over the socket
+            Message::Changed(_)       => Err(Error),
+            Message::To(id, m)        => write!(f, "to:{id}:{m}"),
+        }
+    }
+}
+
+//  _                    _
+// | |    ___   __ _  __| |
+// | |   / _ \ / _` |/ _` |
+// | |__| (_) | (_| | (_| |
+// |_____\___/ \__,_|\__,_|
+
+pub struct Load {
+    pub file:  PathBuf,
+    pub open:  Vec<(String, String)>,
+    pub reply: Reply,
+}
+
+//  ____  _          _  __
+// / ___|| |__   ___| |/ _|
+// \___ \| '_ \ / _ \ | |_
+//  ___) | | | |  __/ |  _|
+// |____/|_| |_|\___|_|_|
+
+// What to do with the model library
+pub enum Shelf {
+    List,
+    Models,
+    Random,
+    Next,
+}
+
+//  ____             _       _
+// | __ )  __ _  ___| | ____| |_ __ ___  _ __
+// |  _ \ / _` |/ __| |/ / _` | '__/ _ \| '_ \
+// | |_) | (_| | (__|   < (_| | | | (_) | |_) |
+// |____/ \__,_|\___|_|\_\__,_|_|  \___/| .__/
+//                                      |_|
+
+// What to put behind the model, colors are RRGGBB[AA]
+pub enum Backdrop {
+    Color(String),
+    Image(PathBuf),
+    Slideshow(PathBuf),
+    Gradient(String, [String; 2]),
+}
+
+//  _____  __  __           _
+// | ____|/ _|/ _| ___  ___| |_
+// |  _| | |_| |_ / _ \/ __| __|
+// | |___|  _|  _|  __/ (__| |_
+// |_____|_| |_|  \___|\___|\__|
+
+// Effect to turn on with its settings, or one to turn off. Sizes are in
+// pixels, hue in degrees, colors are RRGGBB[AA]
+pub enum Effect {
+    Outline(f32, String),
+    Shadow(f32, [f32; 2], String),
+    Glow(f32, f32),
+    Grade(f32, f32, f32),
+    Off(String),
+}
+
+//  ____                _
+// |  _ \ ___  ___ ___ | | ___  _ __
+// | |_) / _ \/ __/ _ \| |/ _ \| '__|
+// |  _ <  __/ (_| (_) | | (_) | |
+// |_| \_\___|\___\___/|_|\___/|_|
+
+// Color is RRGGBB[AA], none goes back to the model's own
+pub struct Recolor {
+    pub target: String,
+    pub tint:   Tint,
+    pub color:  Option<String>,
+    pub reply:  Reply,
+}
+
+//  _____ _       _
+// |_   _(_)_ __ | |_
+//   | | | | '_ \| __|
+//   | | | | | | | |_
+//   |_| |_|_| |_|\__|
+
+#[derive(Clone, Copy)]
+pub enum Tint {
+    Multiply,
+    Screen,
+}
+
+//   ___        _    __ _ _
+//  / _ \ _   _| |_ / _(_) |_
+// | | | | | | | __| |_| | __|
+// | |_| | |_| | |_|  _| | |_
+//  \___/ \__,_|\__|_| |_|\__|
+
+// What to do with the parts of the model, by their ids
+pub enum Outfit {
+    List,
+    Show(String),
+    Hide(String),
+    Opacity(f32, String),
+    Reset(String),
+}
+
+//  __  __                 _
+// |  \/  | ___   ___   __| |
+// | |\/| |/ _ \ / _ \ / _` |
+// | |  | | (_) | (_) | (_| |
+// |_|  |_|\___/ \___/ \__,_|
+
+// What to do with the expressions of the model, by their names
+pub enum Mood {
+    List,
+    Set(String),
+    Toggle(String),
+    Clear,
+}
+
+//   ____
+//  / ___|   _  ___
+// | |  | | | |/ _ \
+// | |__| |_| |  __/
+//  \____\__,_|\___|
+
+// What to do with the motion graph of the model, by state and trigger names
+pub enum Cue {
+    Get,
+    List,
+    Set(String),
+    Trigger(String),
+}
+
+//     _
+//    / \   ___ ___ ___  ___ ___
+//   / _ \ / __/ __/ _ \/ __/ __|
+//  / ___ \ (_| (_|  __/\__ \__ \
+// /_/   \_\___\___\___||___/___/
+
+// Plugins waiting for a token, by their names
+pub enum Access {
+    List,
+    Allow(String),
+    Deny(String),
+}
+
+//  ___        __
+// |_ _|_ __  / _| ___
+//  | || '_ \| |_ / _ \
+//  | || | | |  _| (_) |
+// |___|_| |_|_|  \___/
+
+// What bleed info tells about a model
+#[derive(Serialize, Deserialize)]
+pub struct Info {
+    pub file:        PathBuf,
+    pub parameters:  usize,
+    pub drawables:   usize,
+    pub textures:    usize,
+    pub motions:     Vec<(String, String)>,
+    pub expressions: Vec<String>,
+    pub expression:  Option<String>,
+    pub state:       Option<String>,
+}
+
+//  ____  _          _               _
+// / ___|| |__   ___| |_   _____  __| |
+// \___ \| '_ \ / _ \ \ \ / / _ \/ _` |
+//  ___) | | | |  __/ |\ V /  __/ (_| |
+// |____/|_| |_|\___|_| \_/ \___|\__,_|
+
+// What bleed library models tells about a model
+#[derive(Serialize, Deserialize)]
+pub struct Shelved {
+    pub name:    String,
+    pub file:    PathBuf,
+    pub classes: Vec<String>,
+    pub loaded:  bool,
+}
+
+//  ____                         _
+// |  _ \ _   _ _ __  _ __   ___| |_
+// | |_) | | | | '_ \| '_ \ / _ \ __|
+// |  __/| |_| | |_) | |_) |  __/ |_
+// |_|    \__,_| .__/| .__/ \___|\__|
+//             |_|   |_|
+
+// Value of a parameter held or added to motions, none releases it. Tween is
+// over seconds
+pub struct Puppet {
+    pub id:    String,
+    pub value: Option<f32>,
+    pub over:  f32,
+    pub ease:  Ease,
+    pub hold:  bool,
+    pub reply: Reply,
+}
+
+//  _____
+// | ____|__ _ ___  ___
+// |  _| / _` / __|/ _ \
+// | |__| (_| \__ \  __/
+// |_____\__,_|___/\___|
+
+#[derive(Clone, Copy)]
+pub enum Ease {
+    Linear,
+    In,
+    Out,
+    InOut,
+}
+
+impl Ease {
+    pub fn parse(name: &str) -> Option<Self>
+    {
+        match name {
+            "linear" => Some(Ease::Linear),
+            "in"     => Some(Ease::In),
+            "out"    => Some(Ease::Out),
+            "in-out" => Some(Ease::InOut),
+            _        => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str
+    {
+        match self {
+            Ease::Linear => "linear",
+            Ease::In     => "in",
+            Ease::Out    => "out",
+            Ease::InOut  => "in-out",
         }
     }
 }
 
+//  ____            _
+// |  _ \ ___ _ __ | |_   _
+// | |_) / _ \ '_ \| | | | |
+// |  _ <  __/ |_) | | |_| |
+// |_| \_\___| .__/|_|\__, |
+//           |_|      |___/
+
+// Set by the socket listener, bleed waits for the answer
+#[derive(Default)]
+pub struct Reply(Option<Sender<Result<String, String>>>);
+
+impl Reply {
+
+    //  _ _   _ __   _____      __
+    // (_|_) | '_ \ / _ \ \ /\ / /
+    //  _ _  | | | |  __/\ V  V /
+    // (_|_) |_| |_|\___| \_/\_/
+
+    pub fn new(sender: Sender<Result<String, String>>) -> Self
+    {
+        Self(Some(sender))
+    }
+
+    //  _ _    __ _ _ __  _____      _____ _ __
+    // (_|_)  / _` | '_ \/ __\ \ /\ / / _ \ '__|
+    //  _ _  | (_| | | | \__ \\ V  V /  __/ |
+    // (_|_)  \__,_|_| |_|___/ \_/\_/ \___|_|
+
+    pub fn answer(self,
+                  result: Result<String, String>)
+    {
+        if let Some(reply) = self.0 {
+            reply.send(result).unwrap_or(())
+        }
+    }
+}
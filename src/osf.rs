@@ -0,0 +1,103 @@
+use std::error::Error;
+use cubism::id::param;
+use crate::{
+    tracking,
+    config::OsfConfig,
+};
+
+// Every face is this long, a packet has one after another
+const FACE: usize = 1785;
+
+// Where things are in a face, little endian all of them
+const EYE_RIGHT: usize = 20;
+const EYE_LEFT:  usize = 24;
+const SUCCESS:   usize = 28;
+const EULER:     usize = 49;
+const FEATURES:  usize = 1729;
+
+// Features, as floats from where they start
+const BROW_LEFT:    usize = 3;
+const BROW_RIGHT:   usize = 6;
+const CORNER_LEFT:  usize = 8;
+const CORNER_RIGHT: usize = 10;
+const MOUTH_OPEN:   usize = 12;
+
+//                          _
+//  _ _   _ __ ___  ___ ___(_)_   _____
+// (_|_) | '__/ _ \/ __/ _ \ \ \ / / _ \
+//  _ _  | | |  __/ (_|  __/ |\ V /  __/
+// (_|_) |_|  \___|\___\___|_| \_/ \___|
+
+pub fn receive<F>(config: &OsfConfig,
+                  send:   F) -> Result<(), Box<dyn Error>>
+where F: Fn(Vec<(String, f32)>) + Send + 'static
+{
+    if !config.enabled {return Ok(())}
+
+    tracking::receive(&config.address,
+                      FACE * 8,
+                      parse,
+                      send)
+}
+
+//  _ _   _ __   __ _ _ __ ___  ___
+// (_|_) | '_ \ / _` | '__/ __|/ _ \
+//  _ _  | |_) | (_| | |  \__ \  __/
+// (_|_) | .__/ \__,_|_|  |___/\___|
+//       |_|
+
+// First face that is found, the others are someone else
+fn parse(data: &[u8]) -> Result<Vec<(String, f32)>, &'static str>
+{
+    if data.len() < FACE {return Err("Malformed OpenSeeFace packet")}
+
+    let values =
+        data.chunks_exact(FACE)
+        .find(|f| f[SUCCESS] != 0)
+        .map(translate)
+        .unwrap_or_default();
+
+    Ok(values)
+}
+
+//        _                       _       _
+//  _ _  | |_ _ __ __ _ _ __  ___| | __ _| |_ ___
+// (_|_) | __| '__/ _` | '_ \/ __| |/ _` | __/ _ \
+//  _ _  | |_| | | (_| | | | \__ \ | (_| | ||  __/
+// (_|_)  \__|_|  \__,_|_| |_|___/_|\__,_|\__\___|
+
+// Head angles in degrees, the rest roughly from 0 to 1
+fn translate(face: &[u8]) -> Vec<(String, f32)>
+{
+    let float = |at: usize| {
+        face[at..at + 4].try_into()
+        .map(f32::from_le_bytes)
+        .unwrap_or(0.)
+    };
+    let feature = |i: usize| float(FEATURES + i * 4);
+
+    let [pitch, yaw, roll] = [0, 1, 2].map(|i| float(EULER + i * 4));
+
+    // Facing the camera is half a turn around for some versions of the
+    // tracker, a head can not go past straight up or down anyway
+    let pitch = match pitch.abs() > 90. {
+        true  => (pitch + 360.) % 360. - 180.,
+        false => pitch,
+    };
+
+    [
+        (param::ANGLE_X,      yaw),
+        (param::ANGLE_Y,      pitch),
+        (param::ANGLE_Z,      roll),
+        (param::EYE_L_OPEN,   float(EYE_LEFT)),
+        (param::EYE_R_OPEN,   float(EYE_RIGHT)),
+        (param::BROW_LY,      feature(BROW_LEFT)),
+        (param::BROW_RY,      feature(BROW_RIGHT)),
+        (param::MOUTH_OPEN_Y, feature(MOUTH_OPEN)),
+        (param::MOUTH_FORM,   (feature(CORNER_LEFT)
+                               + feature(CORNER_RIGHT)) / 2.),
+    ]
+    .into_iter()
+    .map(|(id, v)| (id.to_string(), v))
+    .collect()
+}
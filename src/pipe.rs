@@ -0,0 +1,258 @@
+use std::{
+    thread,
+    error::Error,
+    sync::mpsc,
+    time::{Duration, Instant},
+    io::{self, Write, BufWriter, ErrorKind},
+};
+use image::RgbaImage;
+use crate::{
+    listen,
+    control,
+    recolor,
+    dress,
+    drive,
+    calibrate,
+    speak,
+    express,
+    cue,
+    vmc,
+    osf,
+    vts,
+    voice,
+    message::Message,
+    renderer::Raster,
+    render::{Stage, Canvas, Offscreen},
+    config::{Config, Backend, PipeConfig, PipeFormat},
+};
+
+const WINDOW: &str = "Models are only loaded in a window";
+
+//              _
+//  _ _   _ __ (_)_ __   ___
+// (_|_) | '_ \| | '_ \ / _ \
+//  _ _  | |_) | | |_) |  __/
+// (_|_) | .__/|_| .__/ \___|
+//       |_|     |_|
+
+pub fn pipe(config: &Config,
+            pipe:   &PipeConfig) -> Result<(), Box<dyn Error>>
+{
+    if pipe.fps <= 0. {
+        return Err("Frame rate has to be positive".into())
+    }
+
+    match config.backend {
+        Backend::Gl  => run::<Offscreen>(config,
+                                         pipe),
+        Backend::Cpu => run::<Raster>(config,
+                                      pipe),
+    }
+}
+
+//  _ _   _ __ _   _ _ __
+// (_|_) | '__| | | | '_ \
+//  _ _  | |  | |_| | | | |
+// (_|_) |_|   \__,_|_| |_|
+
+fn run<C>(config: &Config,
+          pipe:   &PipeConfig) -> Result<(), Box<dyn Error>>
+where C: Canvas
+{
+    let mut stage = Stage::<C>::new(config)?;
+    let mut bg = config.window.bg.clone();
+    let mut effects = config.window.effects.clone();
+
+    let (tx, rx) = mpsc::channel();
+
+    let track = tx.clone();
+    vmc::receive(&config.tracking.vmc,
+                 move |v| track.send(Message::Track(v))
+                          .unwrap_or_else(|e| eprintln!("Failed to send \
+                                                         message: {e}")))?;
+
+    let track = tx.clone();
+    osf::receive(&config.tracking.osf,
+                 move |v| track.send(Message::Track(v))
+                          .unwrap_or_else(|e| eprintln!("Failed to send \
+                                                         message: {e}")))?;
+
+    let plugin = tx.clone();
+    let gate = vts::serve(&config.vts,
+                          move |m| plugin.send(m)
+                                   .map_err(|e| format!("Failed to send \
+                                                         message: {e}")))?;
+
+    let hear = tx.clone();
+    voice::listen(&config.lipsync,
+                  move |l, f| hear.send(Message::Voice(l, f))
+                              .unwrap_or_else(|e| eprintln!("Failed to send \
+                                                             message: {e}")))?;
+
+    listen(move |m| tx.send(m)
+                    .map_err(|e| format!("Failed to send message: {e}")))?;
+
+    let mut out = BufWriter::new(io::stdout().lock());
+
+    if let PipeFormat::Y4m = pipe.format {
+        let [width, height] = config.window.size;
+        let (num, den) = rate(pipe.fps);
+        writeln!(out, "YUV4MPEG2 W{width} H{height} F{num}:{den} Ip A1:1 \
+                       C444alpha")?;
+    }
+
+    let frame_time = Duration::from_secs_f64(1. / pipe.fps);
+    let mut next = Instant::now();
+
+    loop {
+        for msg in rx.try_iter() {
+            match msg {
+                Message::Exit             => return Ok(()),
+                Message::Load(l)          => l.reply.answer(Err(WINDOW.to_string())),
+                Message::Library(_, r)    => r.answer(Err(WINDOW.to_string())),
+                Message::Background(b, r) => {
+                    let result =
+                        bg
+                        .change(&b)
+                        .and_then(|new| {
+                            stage.background(&new)?;
+                            bg = new;
+                            Ok(())
+                        })
+                        .map(|_| String::new())
+                        .map_err(|e| e.to_string());
+
+                    r.answer(result)
+                }
+                Message::Effects(e, r)    => {
+                    let result =
+                        effects
+                        .change(&e)
+                        .map(|new| {
+                            stage.effects(&new);
+                            effects = new;
+                            String::new()
+                        })
+                        .map_err(|e| e.to_string());
+
+                    r.answer(result)
+                }
+                Message::Color(c)         => {
+                    let result = recolor(vec![&mut stage.model],
+                                         &c);
+                    c.reply.answer(result)
+                }
+                Message::Part(o, r)       => {
+                    let result = dress(vec![&mut stage.model],
+                                       &o);
+                    r.answer(result)
+                }
+                Message::Param(p)         => {
+                    let result = drive(vec![&mut stage.model],
+                                       &p);
+                    p.reply.answer(result)
+                }
+                Message::Expression(m, r) => {
+                    let result = express(vec![&mut stage.model],
+                                         &m);
+                    r.answer(result)
+                }
+                Message::Info(r)          => {
+                    let result =
+                        serde_json::to_string(&stage.model.info())
+                        .map_err(|e| e.to_string());
+                    r.answer(result)
+                }
+                Message::Calibrate(r)     => {
+                    let result = calibrate(vec![&mut stage.model]);
+                    r.answer(result)
+                }
+                Message::Speak(f, r)      => {
+                    let result = speak(vec![&mut stage.model],
+                                       &f,
+                                       config);
+                    r.answer(result)
+                }
+                Message::State(c, r)      => {
+                    let result = cue(vec![&mut stage.model],
+                                     &c);
+                    r.answer(result)
+                }
+                Message::Vts(a, r)        => r.answer(gate.answer(&a)),
+                m                         => control(&mut stage.model, &m)
+                                             .unwrap_or(()),
+            }
+        }
+
+        stage.update(1. / pipe.fps);
+
+        let image = stage.snapshot(config)?;
+
+        let written = match pipe.format {
+            PipeFormat::Rgba => out.write_all(image.as_raw()),
+            PipeFormat::Y4m  => y4m(&mut out, &image),
+        }
+        .and_then(|_| out.flush());
+
+        // Reader going away is the usual way for a pipe to end
+        match written {
+            Err(e) if e.kind() == ErrorKind::BrokenPipe => return Ok(()),
+            r => r?,
+        }
+
+        // Stream is paced in real time, so that commands land where expected
+        next += frame_time;
+        let now = Instant::now();
+        match next.checked_duration_since(now) {
+            Some(d) => thread::sleep(d),
+            None    => next = now,
+        }
+    }
+}
+
+//                  _
+//  _ _   _ __ __ _| |_ ___
+// (_|_) | '__/ _` | __/ _ \
+//  _ _  | | | (_| | ||  __/
+// (_|_) |_|  \__,_|\__\___|
+
+// Frame rate as a fraction, y4m does not take decimals
+fn rate(fps: f64) -> (u64, u64)
+{
+    match fps.fract() == 0. {
+        true  => (fps as u64, 1),
+        false => ((fps * 1000.).round() as u64, 1000),
+    }
+}
+
+//              _  _
+//  _ _   _   _| || |  _ __ ___
+// (_|_) | | | | || |_| '_ ` _ \
+//  _ _  | |_| |__   _| | | | | |
+// (_|_)  \__, |  |_| |_| |_| |_|
+//        |___/
+
+// Planar 4:4:4 with alpha, BT.601 in limited range like ffmpeg expects
+fn y4m<W>(out:   &mut W,
+          image: &RgbaImage) -> io::Result<()>
+where W: Write
+{
+    let size = (image.width() * image.height()) as usize;
+    let mut planes = vec![0u8; size * 4];
+    let (y, rest) = planes.split_at_mut(size);
+    let (u, rest) = rest.split_at_mut(size);
+    let (v, a) = rest.split_at_mut(size);
+
+    image.pixels()
+    .enumerate()
+    .for_each(|(i, p)| {
+        let [r, g, b] = [p[0], p[1], p[2]].map(|c| c as f32);
+        y[i] = ( 16. + 0.257 * r + 0.504 * g + 0.098 * b).round() as u8;
+        u[i] = (128. - 0.148 * r - 0.291 * g + 0.439 * b).round() as u8;
+        v[i] = (128. + 0.439 * r - 0.368 * g - 0.071 * b).round() as u8;
+        a[i] = p[3];
+    });
+
+    out.write_all(b"FRAME\n")?;
+    out.write_all(&planes)
+}
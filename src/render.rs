@@ -0,0 +1,420 @@
+use std::{
+    rc::Rc,
+    path::Path,
+    error::Error,
+};
+use glium::{
+    HeadlessRenderer,
+    backend::{Facade, Context},
+    framebuffer::{SimpleFrameBuffer, StencilRenderBuffer},
+    texture::{
+        Texture2d,
+        RawImage2d,
+        StencilFormat,
+        MipmapsOption,
+        UncompressedFloatFormat,
+    },
+    glutin::{
+        ContextBuilder,
+        dpi::PhysicalSize,
+        platform::unix::HeadlessContextExt,
+    },
+};
+use image::{RgbaImage, imageops};
+use crate::{
+    draw,
+    egl::surfaceless,
+    framework::Model,
+    lipsync::Clip,
+    effects::Effects,
+    background::Background,
+    renderer::{Gl, Cpu, Raster, Device, Renderer},
+    config::{Config, BgConfig, Backend, RenderConfig, EffectsConfig},
+};
+
+// Motion is advanced in small steps rather than in one go, same as it would
+// be in a window
+const STEP: f64 = 1. / 60.;
+
+//                           _
+//  _ _   _ __ ___ _ __   __| | ___ _ __
+// (_|_) | '__/ _ \ '_ \ / _` |/ _ \ '__|
+//  _ _  | | |  __/ | | | (_| |  __/ |
+// (_|_) |_|  \___|_| |_|\__,_|\___|_|
+
+pub fn render(config: &Config,
+              render: &RenderConfig) -> Result<(), Box<dyn Error>>
+{
+    match config.backend {
+        Backend::Gl  => shoot::<Offscreen>(config,
+                                           render),
+        Backend::Cpu => shoot::<Raster>(config,
+                                        render),
+    }
+}
+
+//            _                 _
+//  _ _   ___| |__   ___   ___ | |_
+// (_|_) / __| '_ \ / _ \ / _ \| __|
+//  _ _  \__ \ | | | (_) | (_) | |_
+// (_|_) |___/_| |_|\___/ \___/ \__|
+
+fn shoot<C>(config: &Config,
+            render: &RenderConfig) -> Result<(), Box<dyn Error>>
+where C: Canvas
+{
+    let output =
+        render.output.as_ref()
+        .ok_or("No output file provided")?;
+
+    let mut stage = Stage::<C>::new(config)?;
+
+    if let Some((c, m)) = &render.motion {
+        stage.model
+        .set((c, m))
+        .ok_or(format!("No motion {m} in {c}"))?;
+    }
+
+    if let Some(file) = &render.speak {
+        let clip = Clip::load(Path::new(file),
+                              &config.lipsync.pcm)?;
+        stage.model.say(clip);
+    }
+
+    let steps = (render.time / STEP).ceil().max(1.);
+    let dt = render.time / steps;
+    for _ in 0..steps as usize {
+        stage.update(dt);
+    }
+
+    stage
+    .snapshot(config)?
+    .save(output)?;
+
+    eprintln!("Rendered {output}");
+    Ok(())
+}
+
+//  ____  _
+// / ___|| |_ __ _  __ _  ___
+// \___ \| __/ _` |/ _` |/ _ \
+//  ___) | || (_| | (_| |  __/
+// |____/ \__\__,_|\__, |\___|
+//                 |___/
+
+// Everything needed to draw a frame without a window
+pub struct Stage<C = Offscreen>
+where C: Canvas
+{
+    canvas:     C,
+    background: Background<C::Device>,
+    effects:    Effects<C::Device>,
+    view:       [f32; 2],
+    pub model:  Model<C::Device>,
+}
+
+impl<C> Stage<C>
+where C: Canvas
+{
+
+    //  _ _   _ __   _____      __
+    // (_|_) | '_ \ / _ \ \ /\ / /
+    //  _ _  | | | |  __/\ V  V /
+    // (_|_) |_| |_|\___| \_/\_/
+
+    pub fn new(config: &Config) -> Result<Self, Box<dyn Error>>
+    {
+        let canvas = C::new(config.window.size)?;
+
+        let background = Background::new(&config.window.bg,
+                                         canvas.device())?;
+
+        let effects = Effects::new(&config.window.effects,
+                                   canvas.device())?;
+
+        let model = Model::new(config,
+                               canvas.device())?;
+
+        let [width, height] = config.window.size;
+
+        Ok(Self {
+            canvas,
+            background,
+            effects,
+            view: [width as f32, height as f32],
+            model,
+        })
+    }
+
+    //                        _       _
+    //  _ _   _   _ _ __   __| | __ _| |_ ___
+    // (_|_) | | | | '_ \ / _` |/ _` | __/ _ \
+    //  _ _  | |_| | |_) | (_| | (_| | ||  __/
+    // (_|_)  \__,_| .__/ \__,_|\__,_|\__\___|
+    //             |_|
+
+    // Background goes on with the model, animated or not
+    pub fn update(&mut self,
+                  dt: f64)
+    {
+        self.model
+        .update(dt)
+        .unwrap_or_else(|e| eprintln!("Failed to update model: {e}"));
+
+        self.background.update(dt,
+                               self.canvas.device());
+    }
+
+    //        _                _                                   _
+    //  _ _  | |__   __ _  ___| | ____ _ _ __ ___  _   _ _ __   __| |
+    // (_|_) | '_ \ / _` |/ __| |/ / _` | '__/ _ \| | | | '_ \ / _` |
+    //  _ _  | |_) | (_| | (__|   < (_| | | | (_) | |_| | | | | (_| |
+    // (_|_) |_.__/ \__,_|\___|_|\_\__, |_|  \___/ \__,_|_| |_|\__,_|
+    //                             |___/
+
+    pub fn background(&mut self,
+                      config: &BgConfig) -> Result<(), Box<dyn Error>>
+    {
+        self.background = Background::new(config,
+                                          self.canvas.device())?;
+        Ok(())
+    }
+
+    //              __  __           _
+    //  _ _    ___ / _|/ _| ___  ___| |_ ___
+    // (_|_)  / _ \ |_| |_ / _ \/ __| __/ __|
+    //  _ _  |  __/  _|  _|  __/ (__| |_\__ \
+    // (_|_)  \___|_| |_|  \___|\___|\__|___/
+
+    pub fn effects(&mut self,
+                   config: &EffectsConfig)
+    {
+        self.effects.configure(config);
+    }
+
+    //                                  _           _
+    //  _ _   ___ _ __   __ _ _ __  ___| |__   ___ | |_
+    // (_|_) / __| '_ \ / _` | '_ \/ __| '_ \ / _ \| __|
+    //  _ _  \__ \ | | | (_| | |_) \__ \ | | | (_) | |_
+    // (_|_) |___/_| |_|\__,_| .__/|___/_| |_|\___/ \__|
+    //                       |_|
+
+    pub fn snapshot(&mut self,
+                    config: &Config) -> Result<RgbaImage, Box<dyn Error>>
+    {
+        self.canvas.paint(|r| draw(r,
+                                   config,
+                                   &self.background,
+                                   &mut self.effects,
+                                   &self.model,
+                                   self.view))
+    }
+}
+
+//   ____
+//  / ___|__ _ _ ____   ____ _ ___
+// | |   / _` | '_ \ \ / / _` / __|
+// | |__| (_| | | | \ V / (_| \__ \
+//  \____\__,_|_| |_|\_/ \__,_|___/
+
+// Somewhere to draw on without a window
+pub trait Canvas: Sized {
+    type Device: Device + Clone;
+
+    fn new(size: [u32; 2]) -> Result<Self, Box<dyn Error>>;
+
+    fn device(&self) -> &Self::Device;
+
+    fn paint<F>(&mut self,
+                f: F) -> Result<RgbaImage, Box<dyn Error>>
+    where F: FnOnce(&mut dyn Renderer<Device = Self::Device>)
+                    -> Result<(), Box<dyn Error>>;
+}
+
+//   ___   __  __
+//  / _ \ / _|/ _|___  ___ _ __ ___  ___ _ __
+// | | | | |_| |_/ __|/ __| '__/ _ \/ _ \ '_ \
+// | |_| |  _|  _\__ \ (__| | |  __/  __/ | | |
+//  \___/|_| |_| |___/\___|_|  \___|\___|_| |_|
+
+pub struct Offscreen {
+    context: Rc<Context>,
+    gl:      Gl,
+    texture: Texture2d,
+    stencil: StencilRenderBuffer,
+}
+
+impl Offscreen {
+
+    //  _ _   _ __   _____      __
+    // (_|_) | '_ \ / _ \ \ /\ / /
+    //  _ _  | | | |  __/\ V  V /
+    // (_|_) |_| |_|\___| \_/\_/
+
+    pub fn new(size: [u32; 2]) -> Result<Self, Box<dyn Error>>
+    {
+        let [width, height] = size;
+
+        // OSMesa does not need any display at all, surfaceless EGL at least
+        // does not need a GPU
+        let context =
+            match ContextBuilder::new()
+                  .build_osmesa(PhysicalSize::new(width,
+                                                  height)) {
+                Ok(c)  => HeadlessRenderer::new(c)?.get_context().clone(),
+                Err(e) => {
+                    eprintln!("Failed to create OSMesa context: {e}");
+                    eprintln!("Trying surfaceless EGL");
+                    surfaceless(size)?
+                }
+            };
+        let gl = Gl::new(&context)?;
+
+        let texture =
+            Texture2d::empty_with_format(&context,
+                                         UncompressedFloatFormat::U8U8U8U8,
+                                         MipmapsOption::NoMipmap,
+                                         width,
+                                         height)?;
+
+        let stencil = StencilRenderBuffer::new(&context,
+                                               StencilFormat::I8,
+                                               width,
+                                               height)?;
+
+        Ok(Self {
+            context,
+            gl,
+            texture,
+            stencil,
+        })
+    }
+
+    //         __
+    //  _ _   / _|_ __ __ _ _ __ ___   ___
+    // (_|_) | |_| '__/ _` | '_ ` _ \ / _ \
+    //  _ _  |  _| | | (_| | | | | | |  __/
+    // (_|_) |_| |_|  \__,_|_| |_| |_|\___|
+
+    pub fn frame(&self) -> Result<SimpleFrameBuffer<'_>, Box<dyn Error>>
+    {
+        let frame =
+            SimpleFrameBuffer::with_stencil_buffer(&self.context,
+                                                   &self.texture,
+                                                   &self.stencil)?;
+        Ok(frame)
+    }
+
+    //                           _
+    //  _ _   _ __ ___  __ _  __| |
+    // (_|_) | '__/ _ \/ _` |/ _` |
+    //  _ _  | | |  __/ (_| | (_| |
+    // (_|_) |_|  \___|\__,_|\__,_|
+
+    pub fn read(&self) -> RgbaImage
+    {
+        let raw: RawImage2d<u8> = self.texture.read();
+        let mut image =
+            RgbaImage::from_raw(raw.width,
+                                raw.height,
+                                raw.data.into_owned())
+            .expect("texture to be RGBA");
+
+        // Frame is premultiplied, images are not. Light added where nothing
+        // covers has no alpha to go with it, and is lost as on the CPU
+        image
+        .pixels_mut()
+        .for_each(|p| {
+            let a = p[3] as u32;
+            p.0[..3].iter_mut()
+            .for_each(|c| *c = match a {
+                0 => 0,
+                a => ((*c as u32 * 255 + a / 2) / a).min(255) as u8,
+            });
+        });
+
+        imageops::flip_vertical_in_place(&mut image);
+        image
+    }
+}
+
+impl Facade for Offscreen {
+    fn get_context(&self) -> &Rc<Context> {&self.context}
+}
+
+impl Canvas for Offscreen {
+    type Device = Gl;
+
+    fn new(size: [u32; 2]) -> Result<Self, Box<dyn Error>> {Self::new(size)}
+
+    fn device(&self) -> &Gl {&self.gl}
+
+    fn paint<F>(&mut self,
+                f: F) -> Result<RgbaImage, Box<dyn Error>>
+    where F: FnOnce(&mut dyn Renderer<Device = Gl>)
+                    -> Result<(), Box<dyn Error>>
+    {
+        f(&mut self.gl.renderer(&mut self.frame()?))?;
+        Ok(self.read())
+    }
+}
+
+impl Canvas for Raster {
+    type Device = Cpu;
+
+    fn new(size: [u32; 2]) -> Result<Self, Box<dyn Error>> {Ok(Self::new(size))}
+
+    fn device(&self) -> &Cpu {&Cpu}
+
+    fn paint<F>(&mut self,
+                f: F) -> Result<RgbaImage, Box<dyn Error>>
+    where F: FnOnce(&mut dyn Renderer<Device = Cpu>)
+                    -> Result<(), Box<dyn Error>>
+    {
+        f(self)?;
+        Ok(self.image())
+    }
+}
+
+//  _____         _
+// |_   _|__  ___| |_ ___
+//   | |/ _ \/ __| __/ __|
+//   | |  __/\__ \ |_\__ \
+//   |_|\___||___/\__|___/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::tests::{MODES, CLEARS, blending, golden};
+
+    //        _     _                _ _
+    //  _ _  | |__ | | ___ _ __   __| (_)_ __   __ _
+    // (_|_) | '_ \| |/ _ \ '_ \ / _` | | '_ \ / _` |
+    //  _ _  | |_) | |  __/ | | | (_| | | | | | (_| |
+    // (_|_) |_.__/|_|\___|_| |_|\__,_|_|_| |_|\__, |
+    //                                         |___/
+
+    // Same drawing as the CPU backend is checked with, skipped where there is
+    // no GL to draw it with
+    #[test]
+    fn blending_modes()
+    {
+        let mut canvas = match Offscreen::new([32, 32]) {
+            Ok(c)  => c,
+            Err(e) => return eprintln!("Skipping GL blending: {e}"),
+        };
+        let gl = canvas.device().clone();
+
+        for (mode, m) in MODES {
+            for (clear, c) in CLEARS {
+                let image =
+                    canvas
+                    .paint(|r| blending(&gl, r, clear, mode))
+                    .expect("blending");
+                golden("gl",
+                       &format!("{m}_{c}"),
+                       &image);
+            }
+        }
+    }
+}
@@ -0,0 +1,386 @@
+use std::error::Error;
+use image::RgbaImage;
+use crate::config::EffectsConfig;
+
+mod gl;
+pub use gl::Gl;
+
+mod cpu;
+pub use cpu::{Cpu, Raster};
+
+//  ____             _
+// |  _ \  _____   _(_) ___ ___
+// | | | |/ _ \ \ / / |/ __/ _ \
+// | |_| |  __/\ V /| | (_|  __/
+// |____/ \___| \_/ |_|\___\___|
+
+// Whatever holds textures and meshes of a backend
+pub trait Device {
+    type Texture;
+    type Mesh;
+    type Layer;
+
+    // Image is premultiplied, first row is the top one
+    fn texture(&self,
+               image: RgbaImage) -> Result<Self::Texture, Box<dyn Error>>;
+
+    fn mesh(&self,
+            positions: &[[f32; 2]],
+            uvs:       &[[f32; 2]],
+            indices:   &[u16]) -> Result<Self::Mesh, Box<dyn Error>>;
+
+    // Triangles stay the same for the whole life of a drawable
+    fn write(mesh:      &mut Self::Mesh,
+             positions: &[[f32; 2]],
+             uvs:       &[[f32; 2]]);
+
+    // Somewhere to draw the model on its own, for effects to go over it
+    fn layer(&self,
+             size: [u32; 2]) -> Result<Self::Layer, Box<dyn Error>>;
+
+    // Layer is cleared and drawn on, and comes out with the effects on as a
+    // texture like any other
+    fn develop<'a, F>(&self,
+                      layer: &'a mut Self::Layer,
+                      post:  &Post,
+                      f:     F) -> Result<&'a Self::Texture, Box<dyn Error>>
+    where F:    FnOnce(&mut dyn Renderer<Device = Self>)
+                       -> Result<(), Box<dyn Error>>,
+          Self: Sized;
+}
+
+//  ____                _
+// |  _ \ ___ _ __   __| | ___ _ __ ___ _ __
+// | |_) / _ \ '_ \ / _` |/ _ \ '__/ _ \ '__|
+// |  _ <  __/ | | | (_| |  __/ | |  __/ |
+// |_| \_\___|_| |_|\__,_|\___|_|  \___|_|
+
+// Whatever is being drawn on, colors are premultiplied all the way
+pub trait Renderer {
+    type Device: Device;
+
+    fn clear(&mut self,
+             color: [f32; 4]);
+
+    fn clear_mask(&mut self);
+
+    fn mask(&mut self,
+            mesh: &<Self::Device as Device>::Mesh,
+            pass: &Pass) -> Result<(), Box<dyn Error>>;
+
+    fn draw(&mut self,
+            mesh:    &<Self::Device as Device>::Mesh,
+            texture: &<Self::Device as Device>::Texture,
+            pass:    &Pass) -> Result<(), Box<dyn Error>>;
+}
+
+//  ____
+// |  _ \ __ _ ___ ___
+// | |_) / _` / __/ __|
+// |  __/ (_| \__ \__ \
+// |_|   \__,_|___/___/
+
+// Everything about drawing a mesh beside the mesh itself
+#[derive(Clone, Copy)]
+pub struct Pass {
+    pub transform: Transform,
+    pub opacity:   f32,
+    pub blend:     BlendMode,
+    pub mult:      [f32; 4],
+    pub screen:    [f32; 4],
+    pub mask:      Mask,
+    pub two_sided: bool,
+}
+
+impl Pass {
+
+    //  _ _   _ __   _____      __
+    // (_|_) | '_ \ / _ \ \ /\ / /
+    //  _ _  | | | |  __/\ V  V /
+    // (_|_) |_| |_|\___| \_/\_/
+
+    pub fn new(transform: Transform) -> Self
+    {
+        Self {
+            transform,
+            opacity:   1.,
+            blend:     BlendMode::Normal,
+            mult:      [1., 1., 1., 1.],
+            screen:    [0., 0., 0., 1.],
+            mask:      Mask::None,
+            two_sided: true,
+        }
+    }
+}
+
+//  _____                     __
+// |_   _| __ __ _ _ __  ___ / _| ___  _ __ _ __ ___
+//   | || '__/ _` | '_ \/ __| |_ / _ \| '__| '_ ` _ \
+//   | || | | (_| | | | \__ \  _| (_) | |  | | | | | |
+//   |_||_|  \__,_|_| |_|___/_|  \___/|_|  |_| |_| |_|
+
+// Model units to [-1; 1], same as the vertex shader does it
+#[derive(Clone, Copy)]
+pub struct Transform {
+    pub size:   [f32; 2],
+    pub origin: [f32; 2],
+    pub scale:  f32,
+    pub aspect: [f32; 2],
+    pub offset: [f32; 2],
+}
+
+impl Transform {
+
+    //         __ _       _
+    //  _ _   / _| | __ _| |_
+    // (_|_) | |_| |/ _` | __|
+    //  _ _  |  _| | (_| | |_
+    // (_|_) |_| |_|\__,_|\__|
+
+    // For things that are already in [-1; 1]
+    pub fn flat(aspect: [f32; 2]) -> Self
+    {
+        Self {
+            size:   [2., 2.],
+            origin: [1., 1.],
+            scale:  1.,
+            aspect,
+            offset: [0., 0.],
+        }
+    }
+
+    //                          _
+    //  _ _    __ _ _ __  _ __ | |_   _
+    // (_|_)  / _` | '_ \| '_ \| | | | |
+    //  _ _  | (_| | |_) | |_) | | |_| |
+    // (_|_)  \__,_| .__/| .__/|_|\__, |
+    //             |_|   |_|      |___/
+
+    pub fn apply(&self,
+                 p: [f32; 2]) -> [f32; 2]
+    {
+        [0, 1].map(|i| {
+            ((p[i] * self.scale + self.origin[i]) / self.size[i] * 2. - 1.)
+            * self.aspect[i]
+            + self.offset[i]
+        })
+    }
+}
+
+//  ____  _                _ __  __           _
+// | __ )| | ___ _ __   __| |  \/  | ___   __| | ___
+// |  _ \| |/ _ \ '_ \ / _` | |\/| |/ _ \ / _` |/ _ \
+// | |_) | |  __/ | | | (_| | |  | | (_) | (_| |  __/
+// |____/|_|\___|_| |_|\__,_|_|  |_|\___/ \__,_|\___|
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum BlendMode {
+    Normal,
+    Add,
+    Mult,
+}
+
+//  __  __           _
+// |  \/  | __ _ ___| | __
+// | |\/| |/ _` / __| |/ /
+// | |  | | (_| \__ \   <
+// |_|  |_|\__,_|___/_|\_\
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Mask {
+    None,
+    Inside,
+    Outside,
+}
+
+//  ____           _
+// |  _ \ ___  ___| |_
+// | |_) / _ \/ __| __|
+// |  __/ (_) \__ \ |_
+// |_|   \___/|___/\__|
+
+// Effects flattened for drawing, an effect that is off does nothing. Colors
+// are premultiplied, hue is in radians
+#[derive(Clone, Copy)]
+pub struct Post {
+    pub outline:       f32,
+    pub outline_color: [f32; 4],
+    pub shadow:        f32,
+    pub shadow_offset: [f32; 2],
+    pub shadow_color:  [f32; 4],
+    pub glow:          f32,
+    pub glow_radius:   f32,
+    pub hue:           f32,
+    pub saturation:    f32,
+    pub brightness:    f32,
+}
+
+impl Post {
+
+    //  _ _   _ __   _____      __
+    // (_|_) | '_ \ / _ \ \ /\ / /
+    //  _ _  | | | |  __/\ V  V /
+    // (_|_) |_| |_|\___| \_/\_/
+
+    pub fn new(config: &EffectsConfig) -> Self
+    {
+        let premultiply = |c: [f32; 4]| [c[0] * c[3],
+                                         c[1] * c[3],
+                                         c[2] * c[3],
+                                         c[3]];
+        let mut post = Self {
+            outline:       0.,
+            outline_color: [0.; 4],
+            shadow:        0.,
+            shadow_offset: [0.; 2],
+            shadow_color:  [0.; 4],
+            glow:          0.,
+            glow_radius:   0.,
+            hue:           0.,
+            saturation:    1.,
+            brightness:    1.,
+        };
+
+        if let Some(o) = &config.outline {
+            post.outline = o.width;
+            post.outline_color = premultiply(o.color);
+        }
+        if let Some(s) = &config.shadow {
+            post.shadow = s.blur;
+            post.shadow_offset = s.offset;
+            post.shadow_color = premultiply(s.color);
+        }
+        if let Some(g) = &config.glow {
+            post.glow = g.strength;
+            post.glow_radius = g.radius;
+        }
+        if let Some(g) = &config.grade {
+            post.hue = g.hue.to_radians();
+            post.saturation = g.saturation;
+            post.brightness = g.brightness;
+        }
+
+        post
+    }
+}
+
+//  _____         _
+// |_   _|__  ___| |_ ___
+//   | |/ _ \/ __| __/ __|
+//   | |  __/\__ \ |_\__ \
+//   |_|\___||___/\__|___/
+
+// Shared by the backends, so that both draw the same thing
+#[cfg(test)]
+pub mod tests {
+    use std::{env, fs, io, iter::zip, path::Path};
+    use super::*;
+
+    // Per channel difference that still counts as the same
+    const TOLERANCE: u8 = 2;
+
+    pub const MODES: [(BlendMode, &str); 3] = [(BlendMode::Normal, "normal"),
+                                               (BlendMode::Add,    "add"),
+                                               (BlendMode::Mult,   "mult")];
+    pub const CLEARS: [([f32; 4], &str); 2] = [([0.,  0.,   0.,  0.], "transparent"),
+                                               ([0.2, 0.25, 0.3, 1.], "opaque")];
+
+    // Over the whole of [-1; 1], V going up
+    pub fn quad<D>(device: &D,
+                   from:   [f32; 2],
+                   to:     [f32; 2]) -> D::Mesh
+    where D: Device
+    {
+        device.mesh(&[[from[0], from[1]],
+                      [to[0],   from[1]],
+                      [to[0],   to[1]],
+                      [from[0], to[1]]],
+                    &[[0., 0.], [1., 0.], [1., 1.], [0., 1.]],
+                    &[0, 1, 2, 0, 2, 3])
+        .expect("mesh")
+    }
+
+    // Premultiplied, rows from the top
+    pub fn texture<D>(device: &D,
+                      width:  u32,
+                      pixels: &[[u8; 4]]) -> D::Texture
+    where D: Device
+    {
+        let image =
+            RgbaImage::from_raw(width,
+                                pixels.len() as u32 / width,
+                                pixels.concat())
+            .expect("texture");
+        device.texture(image).expect("texture")
+    }
+
+    //        _     _                _ _
+    //  _ _  | |__ | | ___ _ __   __| (_)_ __   __ _
+    // (_|_) | '_ \| |/ _ \ '_ \ / _` | | '_ \ / _` |
+    //  _ _  | |_) | |  __/ | | | (_| | | | | | (_| |
+    // (_|_) |_.__/|_|\___|_| |_|\__,_|_|_| |_|\__, |
+    //                                         |___/
+
+    // Gradient with see through corners, over a half covered background
+    pub fn blending<D>(device:   &D,
+                       renderer: &mut dyn Renderer<Device = D>,
+                       clear:    [f32; 4],
+                       mode:     BlendMode) -> Result<(), Box<dyn Error>>
+    where D: Device
+    {
+        let base = texture(device, 1, &[[128, 64, 0, 128]]);
+        let gradient = texture(device, 2, &[[255, 0,   0,   255], [0,  96, 0,  96],
+                                            [0,   0,   255, 255], [48, 48, 48, 128]]);
+        let flat = Pass::new(Transform::flat([1., 1.]));
+
+        renderer.clear(clear);
+        renderer.draw(&quad(device, [-1., -0.6], [0.2, 0.6]),
+                      &base,
+                      &flat)?;
+        renderer.draw(&quad(device, [-0.4, -0.8], [0.8, 0.8]),
+                      &gradient,
+                      &Pass {
+                          opacity: 0.8,
+                          blend:   mode,
+                          .. flat
+                      })
+    }
+
+    //                    _     _
+    //  _ _    __ _  ___ | | __| | ___ _ __
+    // (_|_)  / _` |/ _ \| |/ _` |/ _ \ '_ \
+    //  _ _  | (_| | (_) | | (_| |  __/ | | |
+    // (_|_)  \__, |\___/|_|\__,_|\___|_| |_|
+    //        |___/
+
+    // Compared with tests/golden/<backend>, LIVER_BLESS=1 writes it there
+    pub fn golden(backend: &str,
+                  name:    &str,
+                  image:   &RgbaImage)
+    {
+        let reference =
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/golden")
+            .join(backend)
+            .join(format!("{name}.png"));
+
+        if env::var_os("LIVER_BLESS").is_some() {
+            fs::create_dir_all(reference.parent().expect("golden"))
+            .and_then(|_| image.save(&reference).map_err(io::Error::other))
+            .expect("reference to be written");
+            return
+        }
+
+        let expected =
+            image::open(&reference)
+            .unwrap_or_else(|e| panic!("No reference {}: {e}",
+                                       reference.display()))
+            .into_rgba8();
+
+        assert_eq!(image.dimensions(), expected.dimensions(), "{name}");
+        let same =
+            zip(image.pixels(), expected.pixels())
+            .all(|(a, e)| zip(a.0, e.0).all(|(a, e)| a.abs_diff(e) <= TOLERANCE));
+        assert!(same, "{name} differs from {}", reference.display());
+    }
+}
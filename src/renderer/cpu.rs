@@ -0,0 +1,794 @@
+use std::{
+    iter::zip,
+    error::Error,
+    f32::consts::TAU,
+};
+use image::RgbaImage;
+use super::{
+    Pass,
+    Post,
+    Mask,
+    Device,
+    Renderer,
+    BlendMode,
+};
+
+//   ____
+//  / ___|_ __  _   _
+// | |   | '_ \| | | |
+// | |___| |_) | |_| |
+//  \____| .__/ \__,_|
+//       |_|
+
+// Nothing to hold, textures and meshes are plain memory
+#[derive(Clone)]
+pub struct Cpu;
+
+impl Device for Cpu {
+    type Texture = RgbaImage;
+    type Mesh = CpuMesh;
+    type Layer = CpuLayer;
+
+    //        _            _
+    //  _ _  | |_ _____  _| |_ _   _ _ __ ___
+    // (_|_) | __/ _ \ \/ / __| | | | '__/ _ \
+    //  _ _  | ||  __/>  <| |_| |_| | | |  __/
+    // (_|_)  \__\___/_/\_\\__|\__,_|_|  \___|
+
+    fn texture(&self,
+               image: RgbaImage) -> Result<RgbaImage, Box<dyn Error>>
+    {
+        Ok(image)
+    }
+
+    //                            _
+    //  _ _   _ __ ___   ___  ___| |__
+    // (_|_) | '_ ` _ \ / _ \/ __| '_ \
+    //  _ _  | | | | | |  __/\__ \ | | |
+    // (_|_) |_| |_| |_|\___||___/_| |_|
+
+    fn mesh(&self,
+            positions: &[[f32; 2]],
+            uvs:       &[[f32; 2]],
+            indices:   &[u16]) -> Result<CpuMesh, Box<dyn Error>>
+    {
+        Ok(CpuMesh {
+            positions: positions.to_vec(),
+            uvs:       uvs.to_vec(),
+            indices:   indices.to_vec(),
+        })
+    }
+
+    //                      _ _
+    //  _ _  __      ___ __(_) |_ ___
+    // (_|_) \ \ /\ / / '__| | __/ _ \
+    //  _ _   \ V  V /| |  | | ||  __/
+    // (_|_)   \_/\_/ |_|  |_|\__\___|
+
+    fn write(mesh:      &mut CpuMesh,
+             positions: &[[f32; 2]],
+             uvs:       &[[f32; 2]])
+    {
+        mesh.positions.copy_from_slice(positions);
+        mesh.uvs.copy_from_slice(uvs);
+    }
+
+    //        _
+    //  _ _  | | __ _ _   _  ___ _ __
+    // (_|_) | |/ _` | | | |/ _ \ '__|
+    //  _ _  | | (_| | |_| |  __/ |
+    // (_|_) |_|\__,_|\__, |\___|_|
+    //                |___/
+
+    fn layer(&self,
+             size: [u32; 2]) -> Result<CpuLayer, Box<dyn Error>>
+    {
+        Ok(CpuLayer {
+            raster: Raster::new(size),
+            output: RgbaImage::new(size[0], size[1]),
+        })
+    }
+
+    //            _                _
+    //  _ _    __| | _____   _____| | ___  _ __
+    // (_|_)  / _` |/ _ \ \ / / _ \ |/ _ \| '_ \
+    //  _ _  | (_| |  __/\ V /  __/ | (_) | |_) |
+    // (_|_)  \__,_|\___| \_/ \___|_|\___/| .__/
+    //                                    |_|
+
+    fn develop<'a, F>(&self,
+                      layer: &'a mut CpuLayer,
+                      post:  &Post,
+                      f:     F) -> Result<&'a RgbaImage, Box<dyn Error>>
+    where F: FnOnce(&mut dyn Renderer<Device = Cpu>)
+                    -> Result<(), Box<dyn Error>>
+    {
+        let raster = &mut layer.raster;
+        raster.clear([0.; 4]);
+        raster.clear_mask();
+        f(raster)?;
+
+        let raster = &layer.raster;
+        for (x, y, p) in layer.output.enumerate_pixels_mut() {
+            let c = effects(raster,
+                            [x as f32 + 0.5, y as f32 + 0.5],
+                            post);
+            p.0 = c.map(|c| (c.clamp(0., 1.) * 255.).round() as u8);
+        }
+
+        Ok(&layer.output)
+    }
+}
+
+//   ____             __  __           _
+//  / ___|_ __  _   _|  \/  | ___  ___| |__
+// | |   | '_ \| | | | |\/| |/ _ \/ __| '_ \
+// | |___| |_) | |_| | |  | |  __/\__ \ | | |
+//  \____| .__/ \__,_|_|  |_|\___||___/_| |_|
+//       |_|
+
+pub struct CpuMesh {
+    positions: Vec<[f32; 2]>,
+    uvs:       Vec<[f32; 2]>,
+    indices:   Vec<u16>,
+}
+
+//   ____             _
+//  / ___|_ __  _   _| |    __ _ _   _  ___ _ __
+// | |   | '_ \| | | | |   / _` | | | |/ _ \ '__|
+// | |___| |_) | |_| | |__| (_| | |_| |  __/ |
+//  \____| .__/ \__,_|_____\__,_|\__, |\___|_|
+//       |_|                     |___/
+
+// Model alone goes to the raster, and with the effects on to the output
+pub struct CpuLayer {
+    raster: Raster,
+    output: RgbaImage,
+}
+
+//  ____           _
+// |  _ \ __ _ ___| |_ ___ _ __
+// | |_) / _` / __| __/ _ \ '__|
+// |  _ < (_| \__ \ ||  __/ |
+// |_| \_\__,_|___/\__\___|_|
+
+// Premultiplied float color and stencil, first row is the top one
+pub struct Raster {
+    width:   usize,
+    height:  usize,
+    color:   Vec<[f32; 4]>,
+    stencil: Vec<u8>,
+}
+
+impl Raster {
+
+    //  _ _   _ __   _____      __
+    // (_|_) | '_ \ / _ \ \ /\ / /
+    //  _ _  | | | |  __/\ V  V /
+    // (_|_) |_| |_|\___| \_/\_/
+
+    pub fn new(size: [u32; 2]) -> Self
+    {
+        let [width, height] = size.map(|s| s as usize);
+
+        Self {
+            width,
+            height,
+            color:   vec![[0.; 4]; width * height],
+            stencil: vec![0; width * height],
+        }
+    }
+
+    //        _
+    //  _ _  (_)_ __ ___   __ _  __ _  ___
+    // (_|_) | | '_ ` _ \ / _` |/ _` |/ _ \
+    //  _ _  | | | | | | | (_| | (_| |  __/
+    // (_|_) |_|_| |_| |_|\__,_|\__, |\___|
+    //                          |___/
+
+    pub fn image(&self) -> RgbaImage
+    {
+        let mut image = RgbaImage::new(self.width as u32,
+                                       self.height as u32);
+
+        // Frame is premultiplied, images are not
+        zip(image.pixels_mut(),
+            &self.color)
+        .for_each(|(p, c)| {
+            let a = c[3];
+            p.0 = [0, 1, 2, 3].map(|i| {
+                let v = match (i, a > 0.) {
+                    (3, _)     => a,
+                    (_, true)  => c[i] / a,
+                    (_, false) => 0.,
+                };
+                (v.clamp(0., 1.) * 255.).round() as u8
+            });
+        });
+
+        image
+    }
+
+    //                      _            _
+    //  _ _   _ __ __ _ ___| |_ ___ _ __(_)_______
+    // (_|_) | '__/ _` / __| __/ _ \ '__| |_  / _ \
+    //  _ _  | | | (_| \__ \ ||  __/ |  | |/ /  __/
+    // (_|_) |_|  \__,_|___/\__\___|_|  |_/___\___|
+
+    // Calls back with pixel index and barycentric weights for every pixel
+    // whose center is inside of a triangle, shared edges are only drawn once
+    fn rasterize<F>(&self,
+                    mesh:      &CpuMesh,
+                    pass:      &Pass,
+                    mut pixel: F)
+    where F: FnMut(usize, [f32; 3], [usize; 3])
+    {
+        let [w, h] = [self.width as f32, self.height as f32];
+
+        let screen: Vec<[f32; 2]> =
+            mesh.positions.iter()
+            .map(|p| pass.transform.apply(*p))
+            .map(|[x, y]| [(x + 1.) / 2. * w,
+                           (1. - y) / 2. * h])
+            .collect();
+
+        for tri in mesh.indices.chunks_exact(3) {
+            let mut i = [tri[0], tri[1], tri[2]].map(|i| i as usize);
+            let area = edge(screen[i[0]], screen[i[1]], screen[i[2]]);
+            if area == 0. {continue}
+
+            // Rows go down, so positive area here is clockwise in GL terms,
+            // which is what gets culled
+            if area > 0. && !pass.two_sided {continue}
+            if area < 0. {i.swap(1, 2)}
+            let area = area.abs();
+
+            let [a, b, c] = i.map(|i| screen[i]);
+            let edges = [(b, c), (c, a), (a, b)];
+            let top_left = edges.map(|(p, q)| {
+                let [dx, dy] = [q[0] - p[0], q[1] - p[1]];
+                (dy == 0. && dx > 0.) || dy < 0.
+            });
+
+            let min_x = a[0].min(b[0]).min(c[0]).floor().max(0.) as usize;
+            let max_x = a[0].max(b[0]).max(c[0]).ceil().min(w) as usize;
+            let min_y = a[1].min(b[1]).min(c[1]).floor().max(0.) as usize;
+            let max_y = a[1].max(b[1]).max(c[1]).ceil().min(h) as usize;
+
+            for y in min_y..max_y {
+                for x in min_x..max_x {
+                    let p = [x as f32 + 0.5, y as f32 + 0.5];
+                    let e = [0, 1, 2].map(|k| edge(edges[k].0,
+                                                   edges[k].1,
+                                                   p));
+
+                    let inside =
+                        zip(e, top_left)
+                        .all(|(e, tl)| e > 0. || (e == 0. && tl));
+                    if !inside {continue}
+
+                    pixel(y * self.width + x,
+                          e.map(|e| e / area),
+                          i);
+                }
+            }
+        }
+    }
+}
+
+impl Renderer for Raster {
+    type Device = Cpu;
+
+    //             _
+    //  _ _    ___| | ___  __ _ _ __
+    // (_|_)  / __| |/ _ \/ _` | '__|
+    //  _ _  | (__| |  __/ (_| | |
+    // (_|_)  \___|_|\___|\__,_|_|
+
+    fn clear(&mut self,
+             color: [f32; 4])
+    {
+        self.color.fill(color);
+    }
+
+    //             _                                       _
+    //  _ _    ___| | ___  __ _ _ __   _ __ ___   __ _ ___| | __
+    // (_|_)  / __| |/ _ \/ _` | '__| | '_ ` _ \ / _` / __| |/ /
+    //  _ _  | (__| |  __/ (_| | |    | | | | | | (_| \__ \   <
+    // (_|_)  \___|_|\___|\__,_|_|    |_| |_| |_|\__,_|___/_|\_\
+
+    fn clear_mask(&mut self) {self.stencil.fill(0)}
+
+    //                            _
+    //  _ _   _ __ ___   __ _ ___| | __
+    // (_|_) | '_ ` _ \ / _` / __| |/ /
+    //  _ _  | | | | | | (_| \__ \   <
+    // (_|_) |_| |_| |_|\__,_|___/_|\_\
+
+    fn mask(&mut self,
+            mesh: &CpuMesh,
+            pass: &Pass) -> Result<(), Box<dyn Error>>
+    {
+        let mut covered = Vec::new();
+        self.rasterize(mesh,
+                       pass,
+                       |i, _, _| covered.push(i));
+
+        covered.into_iter()
+        .for_each(|i| self.stencil[i] = 1);
+
+        Ok(())
+    }
+
+    //            _
+    //  _ _    __| |_ __ __ ___      __
+    // (_|_)  / _` | '__/ _` \ \ /\ / /
+    //  _ _  | (_| | | | (_| |\ V  V /
+    // (_|_)  \__,_|_|  \__,_| \_/\_/
+
+    fn draw(&mut self,
+            mesh:    &CpuMesh,
+            texture: &RgbaImage,
+            pass:    &Pass) -> Result<(), Box<dyn Error>>
+    {
+        let mut fragments = Vec::new();
+        self.rasterize(mesh,
+                       pass,
+                       |i, w, v| {
+                           let uv = [0, 1].map(|k| {
+                               w[0] * mesh.uvs[v[0]][k]
+                               + w[1] * mesh.uvs[v[1]][k]
+                               + w[2] * mesh.uvs[v[2]][k]
+                           });
+                           fragments.push((i, uv));
+                       });
+
+        for (i, uv) in fragments {
+            let pass_mask = match pass.mask {
+                Mask::None    => true,
+                Mask::Inside  => self.stencil[i] == 1,
+                Mask::Outside => self.stencil[i] == 0,
+            };
+            if !pass_mask {continue}
+
+            let src = shade(sample(texture, uv), pass);
+            let dst = &mut self.color[i];
+            *dst = blend(src, *dst, pass.blend);
+        }
+
+        Ok(())
+    }
+}
+
+//  _____    _
+// | ____|__| | __ _  ___
+// |  _| / _` |/ _` |/ _ \
+// | |__| (_| | (_| |  __/
+// |_____\__,_|\__, |\___|
+//             |___/
+
+// Twice the signed area of a triangle
+fn edge(a: [f32; 2],
+        b: [f32; 2],
+        p: [f32; 2]) -> f32
+{
+    (b[0] - a[0]) * (p[1] - a[1]) - (b[1] - a[1]) * (p[0] - a[0])
+}
+
+//  ____                        _
+// / ___|  __ _ _ __ ___  _ __ | | ___
+// \___ \ / _` | '_ ` _ \| '_ \| |/ _ \
+//  ___) | (_| | | | | | | |_) | |  __/
+// |____/ \__,_|_| |_| |_| .__/|_|\___|
+//                       |_|
+
+// Bilinear, clamped to edge, V goes up same as in GL
+fn sample(texture: &RgbaImage,
+          uv:      [f32; 2]) -> [f32; 4]
+{
+    let (w, h) = texture.dimensions();
+    let x = uv[0] * w as f32 - 0.5;
+    let y = (1. - uv[1]) * h as f32 - 0.5;
+
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+
+    let texel = |x: f32, y: f32| -> [f32; 4] {
+        let x = (x.max(0.) as u32).min(w - 1);
+        let y = (y.max(0.) as u32).min(h - 1);
+        texture.get_pixel(x, y).0.map(|c| c as f32 / 255.)
+    };
+
+    let [a, b, c, d] = [
+        texel(x0,      y0),
+        texel(x0 + 1., y0),
+        texel(x0,      y0 + 1.),
+        texel(x0 + 1., y0 + 1.),
+    ];
+
+    [0, 1, 2, 3].map(|i| {
+        let top = a[i] + (b[i] - a[i]) * fx;
+        let bottom = c[i] + (d[i] - c[i]) * fx;
+        top + (bottom - top) * fy
+    })
+}
+
+//  ____  _               _
+// / ___|| |__   __ _  __| | ___
+// \___ \| '_ \ / _` |/ _` |/ _ \
+//  ___) | | | | (_| | (_| |  __/
+// |____/|_| |_|\__,_|\__,_|\___|
+
+// Same as the fragment shader
+fn shade(color: [f32; 4],
+         pass:  &Pass) -> [f32; 4]
+{
+    let [mut r, mut g, mut b, a] = color;
+    [r, g, b] = [r * pass.mult[0], g * pass.mult[1], b * pass.mult[2]];
+    [r, g, b] = [(r, pass.screen[0]), (g, pass.screen[1]), (b, pass.screen[2])]
+                .map(|(c, s)| c + s * a - c * s);
+
+    [r, g, b, a].map(|c| c * pass.opacity)
+}
+
+//  ____  _                _
+// | __ )| | ___ _ __   __| |
+// |  _ \| |/ _ \ '_ \ / _` |
+// | |_) | |  __/ | | | (_| |
+// |____/|_|\___|_| |_|\__,_|
+
+// Same factors as the GL blend functions
+fn blend(src:  [f32; 4],
+         dst:  [f32; 4],
+         mode: BlendMode) -> [f32; 4]
+{
+    let sa = src[3];
+    let color = |i: usize| match mode {
+        BlendMode::Normal => src[i] + dst[i] * (1. - sa),
+        BlendMode::Add    => src[i] + dst[i],
+        BlendMode::Mult   => src[i] * dst[i] + dst[i] * (1. - sa),
+    };
+    let alpha = match mode {
+        BlendMode::Normal => sa + dst[3] * (1. - sa),
+        _                 => dst[3],
+    };
+
+    [color(0), color(1), color(2), alpha].map(|c| c.clamp(0., 1.))
+}
+
+//  _____  __  __           _
+// | ____|/ _|/ _| ___  ___| |_ ___
+// |  _| | |_| |_ / _ \/ __| __/ __|
+// | |___|  _|  _|  __/ (__| |_\__ \
+// |_____|_| |_|  \___|\___|\__|___/
+
+// Taps around a point, same as the effects shader takes them
+const DIRECTIONS: usize = 16;
+const RINGS: usize = 3;
+
+// Same as the effects shader, with rows going down
+fn effects(raster: &Raster,
+           p:      [f32; 2],
+           post:   &Post) -> [f32; 4]
+{
+    let taps = |radius: f32| {
+        (1..=RINGS)
+        .flat_map(move |r| (0..DIRECTIONS).map(move |d| (d, r)))
+        .map(move |(d, r)| {
+            let angle = TAU * d as f32 / DIRECTIONS as f32;
+            let distance = radius * r as f32 / RINGS as f32;
+            (r, [angle.cos() * distance, angle.sin() * distance])
+        })
+    };
+
+    // Most covered point in a circle
+    let dilate = |p: [f32; 2], radius: f32| {
+        taps(radius)
+        .map(|(_, t)| fetch(raster, [p[0] + t[0], p[1] + t[1]])[3])
+        .fold(fetch(raster, p)[3], f32::max)
+    };
+
+    // Average over a circle, closer points weigh more
+    let blur = |p: [f32; 2], radius: f32| {
+        let mut sum = fetch(raster, p);
+        let mut total = 1.;
+        for (r, t) in taps(radius) {
+            let w = 1. - r as f32 / (RINGS + 1) as f32;
+            let c = fetch(raster, [p[0] + t[0], p[1] + t[1]]);
+            (0..4).for_each(|i| sum[i] += c[i] * w);
+            total += w;
+        }
+        sum.map(|c| c / total)
+    };
+
+    let mut color = grade(fetch(raster, p), post);
+
+    let mut under = [0.; 4];
+    if post.outline > 0. {
+        let a = dilate(p, post.outline);
+        under = post.outline_color.map(|c| c * a);
+    }
+    if post.shadow_color[3] > 0. {
+        let [x, y] = post.shadow_offset;
+        let a = blur([p[0] - x, p[1] - y], post.shadow)[3];
+        let cover = 1. - under[3];
+        (0..4).for_each(|i| under[i] += post.shadow_color[i] * a * cover);
+    }
+    let cover = 1. - color[3];
+    (0..4).for_each(|i| color[i] += under[i] * cover);
+
+    // Glow adds light rather than covering anything
+    if post.glow > 0. {
+        let g = blur(p, post.glow_radius).map(|c| c * post.glow);
+        (0..3).for_each(|i| color[i] += g[i]);
+        color[3] += g[3] * (1. - color[3]);
+    }
+
+    color.map(|c| c.clamp(0., 1.))
+}
+
+// Hue turns around the gray axis, saturation goes from or to the luma
+fn grade(color: [f32; 4],
+         post:  &Post) -> [f32; 4]
+{
+    let a = color[3];
+    if a <= 0. {return color}
+
+    let [r, g, b] = [color[0] / a, color[1] / a, color[2] / a];
+    let cs = post.hue.cos();
+    let k = (1. - cs) / 3.;
+    let s = post.hue.sin() / 3f32.sqrt();
+    let c = [r * (cs + k) + g * (k - s) + b * (k + s),
+             r * (k + s) + g * (cs + k) + b * (k - s),
+             r * (k - s) + g * (k + s) + b * (cs + k)];
+
+    let luma = 0.2126 * c[0] + 0.7152 * c[1] + 0.0722 * c[2];
+    let c = c.map(|c| (luma + (c - luma) * post.saturation) * post.brightness);
+
+    let [r, g, b] = c.map(|c| c.clamp(0., 1.) * a);
+    [r, g, b, a]
+}
+
+// Bilinear on the raster, clamped to edge
+fn fetch(raster: &Raster,
+         p:      [f32; 2]) -> [f32; 4]
+{
+    let [w, h] = [raster.width, raster.height];
+    let x = p[0] - 0.5;
+    let y = p[1] - 0.5;
+
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+
+    let texel = |x: f32, y: f32| -> [f32; 4] {
+        let x = (x.max(0.) as usize).min(w - 1);
+        let y = (y.max(0.) as usize).min(h - 1);
+        raster.color[y * w + x]
+    };
+
+    let [a, b, c, d] = [
+        texel(x0,      y0),
+        texel(x0 + 1., y0),
+        texel(x0,      y0 + 1.),
+        texel(x0 + 1., y0 + 1.),
+    ];
+
+    [0, 1, 2, 3].map(|i| {
+        let top = a[i] + (b[i] - a[i]) * fx;
+        let bottom = c[i] + (d[i] - c[i]) * fx;
+        top + (bottom - top) * fy
+    })
+}
+
+//  _____         _
+// |_   _|__  ___| |_ ___
+//   | |/ _ \/ __| __/ __|
+//   | |  __/\__ \ |_\__ \
+//   |_|\___||___/\__|___/
+
+#[cfg(test)]
+mod tests {
+    use std::iter::zip;
+    use super::*;
+    use crate::renderer::{
+        Transform,
+        tests::{MODES, CLEARS, quad, texture, blending, golden},
+    };
+
+    fn pass(blend: BlendMode) -> Pass
+    {
+        Pass {
+            blend,
+            .. Pass::new(Transform::flat([1., 1.]))
+        }
+    }
+
+    fn close(a: [f32; 4],
+             b: [f32; 4]) -> bool
+    {
+        zip(a, b).all(|(a, b)| (a - b).abs() < 1e-5)
+    }
+
+    // Pixels of an 8 by 8 raster with their centers at [-1; 1], covered and
+    // how many times
+    fn coverage(mesh: &CpuMesh,
+                pass: &Pass) -> Vec<u32>
+    {
+        let raster = Raster::new([8, 8]);
+        let mut count = vec![0; 64];
+        raster.rasterize(mesh,
+                         pass,
+                         |i, _, _| count[i] += 1);
+        count
+    }
+
+    // Pixel edges of the 8 by 8 raster to clip space, rows going down
+    fn clip(x: f32,
+            y: f32) -> [f32; 2]
+    {
+        [x / 4. - 1., 1. - y / 4.]
+    }
+
+    //        _     _                _ _
+    //  _ _  | |__ | | ___ _ __   __| (_)_ __   __ _
+    // (_|_) | '_ \| |/ _ \ '_ \ / _` | | '_ \ / _` |
+    //  _ _  | |_) | |  __/ | | | (_| | | | | | (_| |
+    // (_|_) |_.__/|_|\___|_| |_|\__,_|_|_| |_|\__, |
+    //                                         |___/
+
+    // Same drawing as the GL backend is checked with
+    #[test]
+    fn blending_modes()
+    {
+        for (mode, m) in MODES {
+            for (clear, c) in CLEARS {
+                let mut raster = Raster::new([32, 32]);
+                blending(&Cpu, &mut raster, clear, mode).expect("blending");
+                golden("raster",
+                       &format!("{m}_{c}"),
+                       &raster.image());
+            }
+        }
+    }
+
+    //         __ _ _ _              _
+    //  _ _   / _(_) | |  _ __ _   _| | ___
+    // (_|_) | |_| | | | | '__| | | | |/ _ \
+    //  _ _  |  _| | | | | |  | |_| | |  __/
+    // (_|_) |_| |_|_|_| |_|   \__,_|_|\___|
+
+    // Edges right on pixel centers, the left and top ones are drawn and the
+    // diagonal both triangles share is drawn once
+    #[test]
+    fn fill_rule()
+    {
+        let [from, to] = [clip(1.5, 5.5), clip(5.5, 1.5)];
+        let count = coverage(&quad(&Cpu, from, to),
+                             &pass(BlendMode::Normal));
+
+        for (i, c) in count.into_iter().enumerate() {
+            let [x, y] = [i % 8, i / 8];
+            let inside = (1..5).contains(&x) && (1..5).contains(&y);
+            assert_eq!(c, inside as u32, "pixel {x}, {y}");
+        }
+    }
+
+    //                   _ _ _
+    //  _ _    ___ _   _| | (_)_ __   __ _
+    // (_|_)  / __| | | | | | | '_ \ / _` |
+    //  _ _  | (__| |_| | | | | | | | (_| |
+    // (_|_)  \___|\__,_|_|_|_|_| |_|\__, |
+    //                               |___/
+
+    // Clockwise in GL terms is the back, drawn only if two sided
+    #[test]
+    fn culling()
+    {
+        let front = Cpu.mesh(&[[-1., -1.], [1., -1.], [-1., 1.]],
+                             &[[0., 0.]; 3],
+                             &[0, 1, 2])
+                    .expect("mesh");
+        let back = Cpu.mesh(&[[-1., -1.], [-1., 1.], [1., -1.]],
+                            &[[0., 0.]; 3],
+                            &[0, 1, 2])
+                   .expect("mesh");
+
+        let one = Pass {two_sided: false, .. pass(BlendMode::Normal)};
+        let two = Pass {two_sided: true, .. pass(BlendMode::Normal)};
+        let drawn = |mesh, pass| coverage(mesh, pass).into_iter().sum::<u32>();
+
+        assert!(drawn(&front, &one) > 0);
+        assert_eq!(drawn(&back, &one), 0);
+        assert_eq!(drawn(&back, &two), drawn(&front, &two));
+    }
+
+    //                            _
+    //  _ _   _ __ ___   __ _ ___| | _____
+    // (_|_) | '_ ` _ \ / _` / __| |/ / __|
+    //  _ _  | | | | | | (_| \__ \   <\__ \
+    // (_|_) |_| |_| |_|\__,_|___/_|\_\___/
+
+    // Mask over the left half, drawing goes inside or outside of it
+    #[test]
+    fn masks()
+    {
+        let white = texture(&Cpu, 1, &[[255; 4]]);
+        let mask = quad(&Cpu, [-1., -1.], [0., 1.]);
+        let all = quad(&Cpu, [-1., -1.], [1., 1.]);
+
+        for (m, left, right) in [(Mask::None,    true,  true),
+                                 (Mask::Inside,  true,  false),
+                                 (Mask::Outside, false, true)] {
+            let mut raster = Raster::new([8, 8]);
+            raster.clear([0.; 4]);
+            raster.clear_mask();
+            raster.mask(&mask,
+                        &pass(BlendMode::Normal))
+            .expect("mask");
+            raster.draw(&all,
+                        &white,
+                        &Pass {mask: m, .. pass(BlendMode::Normal)})
+            .expect("draw");
+
+            for (i, c) in raster.color.iter().enumerate() {
+                let shown = match i % 8 < 4 {
+                    true  => left,
+                    false => right,
+                };
+                assert_eq!(c[3] == 1., shown, "pixel {i}");
+            }
+        }
+    }
+
+    //        _     _                _                       _
+    //  _ _  | |__ | | ___ _ __   __| |  _ __ ___   ___   __| | ___  ___
+    // (_|_) | '_ \| |/ _ \ '_ \ / _` | | '_ ` _ \ / _ \ / _` |/ _ \/ __|
+    //  _ _  | |_) | |  __/ | | | (_| | | | | | | | (_) | (_| |  __/\__ \
+    // (_|_) |_.__/|_|\___|_| |_|\__,_| |_| |_| |_|\___/ \__,_|\___||___/
+
+    // Premultiplied, only normal adds to the alpha
+    #[test]
+    fn blend_modes()
+    {
+        let dst = [0.8, 0.4, 0.2, 1.];
+
+        assert!(close(blend([0.5, 0., 0., 0.5], dst, BlendMode::Normal),
+                      [0.9, 0.2, 0.1, 1.]));
+        assert!(close(blend([0.1, 0.2, 0., 0.5], dst, BlendMode::Add),
+                      [0.9, 0.6, 0.2, 1.]));
+        assert!(close(blend([0.5, 0.5, 0.25, 0.5], dst, BlendMode::Mult),
+                      [0.8, 0.4, 0.15, 1.]));
+
+        // Nothing under stays see through for add and mult
+        let clear = [0.; 4];
+        assert!(close(blend([0.5, 0., 0., 0.5], clear, BlendMode::Normal),
+                      [0.5, 0., 0., 0.5]));
+        assert!(close(blend([0.5, 0., 0., 0.5], clear, BlendMode::Add),
+                      [0.5, 0., 0., 0.]));
+        assert!(close(blend([0.5, 0., 0., 0.5], clear, BlendMode::Mult),
+                      clear));
+    }
+
+    //                                  _
+    //  _ _   ___  __ _ _ __ ___  _ __ | | ___
+    // (_|_) / __|/ _` | '_ ` _ \| '_ \| |/ _ \
+    //  _ _  \__ \ (_| | | | | | | |_) | |  __/
+    // (_|_) |___/\__,_|_| |_| |_| .__/|_|\___|
+    //                           |_|
+
+    // Texel centers come out as they are, between them is a mix, outside is
+    // clamped to the edge
+    #[test]
+    fn sample_bilinear()
+    {
+        let t = texture(&Cpu, 2, &[[255, 0,   0,   255], [0,   255, 0,   255],
+                                   [0,   0,   255, 255], [255, 255, 255, 255]]);
+
+        assert!(close(sample(&t, [0.25, 0.75]), [1., 0., 0., 1.]));
+        assert!(close(sample(&t, [0.75, 0.25]), [1., 1., 1., 1.]));
+        assert!(close(sample(&t, [0.5, 0.5]), [0.5, 0.5, 0.5, 1.]));
+        assert!(close(sample(&t, [0.5, 0.75]), [0.5, 0.5, 0., 1.]));
+        assert!(close(sample(&t, [-1., 2.]), [1., 0., 0., 1.]));
+        assert!(close(sample(&t, [2., -1.]), [1., 1., 1., 1.]));
+    }
+}
@@ -0,0 +1,574 @@
+use std::{
+    rc::Rc,
+    iter::zip,
+    error::Error,
+};
+use glium::{
+    Blend,
+    Surface,
+    uniform,
+    BlendingFunction,
+    implement_vertex,
+    vertex::VertexBuffer,
+    backend::{Facade, Context},
+    LinearBlendingFactor as F,
+    index::{IndexBuffer, PrimitiveType},
+    uniforms::SamplerWrapFunction,
+    framebuffer::{SimpleFrameBuffer, StencilRenderBuffer},
+    texture::{
+        Texture2d,
+        RawImage2d,
+        StencilFormat,
+        MipmapsOption,
+        UncompressedFloatFormat,
+    },
+    program::{Program, ProgramCreationInput, ProgramCreationError},
+    draw_parameters::{
+        Stencil,
+        StencilTest,
+        DrawParameters,
+        StencilOperation,
+        BackfaceCullingMode,
+    },
+};
+use image::RgbaImage;
+use super::{
+    Pass,
+    Post,
+    Mask,
+    Device,
+    Renderer,
+    BlendMode,
+};
+
+//   ____ _
+//  / ___| |
+// | |  _| |
+// | |_| | |
+//  \____|_|
+
+// Cheap to clone, the context and programs are shared
+#[derive(Clone)]
+pub struct Gl {
+    context:  Rc<Context>,
+    programs: Programs,
+}
+
+impl Gl {
+
+    //  _ _   _ __   _____      __
+    // (_|_) | '_ \ / _ \ \ /\ / /
+    //  _ _  | | | |  __/\ V  V /
+    // (_|_) |_| |_|\___| \_/\_/
+
+    pub fn new<T>(display: &T) -> Result<Self, Box<dyn Error>>
+    where T: Facade + ?Sized
+    {
+        Ok(Self {
+            context:  display.get_context().clone(),
+            programs: programs(display)?,
+        })
+    }
+
+    //                           _
+    //  _ _   _ __ ___ _ __   __| | ___ _ __ ___ _ __
+    // (_|_) | '__/ _ \ '_ \ / _` |/ _ \ '__/ _ \ '__|
+    //  _ _  | | |  __/ | | | (_| |  __/ | |  __/ |
+    // (_|_) |_|  \___|_| |_|\__,_|\___|_|  \___|_|
+
+    pub fn renderer<'a, S>(&'a self,
+                           surface: &'a mut S) -> GlRenderer<'a, S>
+    where S: Surface
+    {
+        GlRenderer {
+            surface,
+            gl: self,
+        }
+    }
+}
+
+impl Facade for Gl {
+    fn get_context(&self) -> &Rc<Context> {&self.context}
+}
+
+impl Device for Gl {
+    type Texture = Texture2d;
+    type Mesh = GlMesh;
+    type Layer = GlLayer;
+
+    //        _            _
+    //  _ _  | |_ _____  _| |_ _   _ _ __ ___
+    // (_|_) | __/ _ \ \/ / __| | | | '__/ _ \
+    //  _ _  | ||  __/>  <| |_| |_| | | |  __/
+    // (_|_)  \__\___/_/\_\\__|\__,_|_|  \___|
+
+    fn texture(&self,
+               image: RgbaImage) -> Result<Texture2d, Box<dyn Error>>
+    {
+        let dimensions = image.dimensions();
+        let raw = RawImage2d::from_raw_rgba_reversed(&image.into_raw(),
+                                                     dimensions);
+
+        Ok(Texture2d::new(self,
+                          raw)?)
+    }
+
+    //                            _
+    //  _ _   _ __ ___   ___  ___| |__
+    // (_|_) | '_ ` _ \ / _ \/ __| '_ \
+    //  _ _  | | | | | |  __/\__ \ | | |
+    // (_|_) |_| |_| |_|\___||___/_| |_|
+
+    fn mesh(&self,
+            positions: &[[f32; 2]],
+            uvs:       &[[f32; 2]],
+            indices:   &[u16]) -> Result<GlMesh, Box<dyn Error>>
+    {
+        let vertex_buffer =
+            VertexBuffer::dynamic(self,
+                                  &verts(positions,
+                                         uvs))?;
+
+        let index_buffer =
+            IndexBuffer::new(self,
+                             PrimitiveType::TrianglesList,
+                             indices)?;
+
+        Ok(GlMesh {
+            vertex_buffer,
+            index_buffer,
+        })
+    }
+
+    //                      _ _
+    //  _ _  __      ___ __(_) |_ ___
+    // (_|_) \ \ /\ / / '__| | __/ _ \
+    //  _ _   \ V  V /| |  | | ||  __/
+    // (_|_)   \_/\_/ |_|  |_|\__\___|
+
+    fn write(mesh:      &mut GlMesh,
+             positions: &[[f32; 2]],
+             uvs:       &[[f32; 2]])
+    {
+        mesh.vertex_buffer.write(&verts(positions,
+                                        uvs));
+    }
+
+    //        _
+    //  _ _  | | __ _ _   _  ___ _ __
+    // (_|_) | |/ _` | | | |/ _ \ '__|
+    //  _ _  | | (_| | |_| |  __/ |
+    // (_|_) |_|\__,_|\__, |\___|_|
+    //                |___/
+
+    fn layer(&self,
+             size: [u32; 2]) -> Result<GlLayer, Box<dyn Error>>
+    {
+        let [width, height] = size;
+        let texture = || {
+            Texture2d::empty_with_format(self,
+                                         UncompressedFloatFormat::U8U8U8U8,
+                                         MipmapsOption::NoMipmap,
+                                         width,
+                                         height)
+        };
+
+        let quad = self.mesh(&[[-1., -1.], [-1.,  1.], [ 1., -1.], [ 1.,  1.]],
+                             &[[ 0.,  0.], [ 0.,  1.], [ 1.,  0.], [ 1.,  1.]],
+                             &[0, 1, 2, 2, 1, 3])?;
+
+        Ok(GlLayer {
+            picture: texture()?,
+            output:  texture()?,
+            stencil: StencilRenderBuffer::new(self,
+                                              StencilFormat::I8,
+                                              width,
+                                              height)?,
+            texel:   [1. / width as f32, 1. / height as f32],
+            quad,
+        })
+    }
+
+    //            _                _
+    //  _ _    __| | _____   _____| | ___  _ __
+    // (_|_)  / _` |/ _ \ \ / / _ \ |/ _ \| '_ \
+    //  _ _  | (_| |  __/\ V /  __/ | (_) | |_) |
+    // (_|_)  \__,_|\___| \_/ \___|_|\___/| .__/
+    //                                    |_|
+
+    fn develop<'a, F>(&self,
+                      layer: &'a mut GlLayer,
+                      post:  &Post,
+                      f:     F) -> Result<&'a Texture2d, Box<dyn Error>>
+    where F: FnOnce(&mut dyn Renderer<Device = Gl>)
+                    -> Result<(), Box<dyn Error>>
+    {
+        {
+            let mut picture =
+                SimpleFrameBuffer::with_stencil_buffer(self,
+                                                       &layer.picture,
+                                                       &layer.stencil)?;
+            let mut renderer = self.renderer(&mut picture);
+            renderer.clear([0.; 4]);
+            renderer.clear_mask();
+            f(&mut renderer)?;
+        }
+
+        // Edges of the view stay as they are, not to wrap around
+        let tex =
+            layer.picture
+            .sampled()
+            .wrap_function(SamplerWrapFunction::Clamp);
+
+        let uniforms = uniform!{
+            tex:           tex,
+            texel:         layer.texel,
+            outline:       post.outline,
+            outline_color: post.outline_color,
+            shadow:        post.shadow,
+            shadow_offset: post.shadow_offset,
+            shadow_color:  post.shadow_color,
+            glow:          post.glow,
+            glow_radius:   post.glow_radius,
+            hue:           post.hue,
+            saturation:    post.saturation,
+            brightness:    post.brightness,
+        };
+
+        let mut output = SimpleFrameBuffer::new(self,
+                                                &layer.output)?;
+        output.draw(&layer.quad.vertex_buffer,
+                    &layer.quad.index_buffer,
+                    &self.programs[ProgramVariant::Effects as usize],
+                    &uniforms,
+                    &DrawParameters::default())?;
+
+        Ok(&layer.output)
+    }
+}
+
+//   ____ _ __  __           _
+//  / ___| |  \/  | ___  ___| |__
+// | |  _| | |\/| |/ _ \/ __| '_ \
+// | |_| | | |  | |  __/\__ \ | | |
+//  \____|_|_|  |_|\___||___/_| |_|
+
+pub struct GlMesh {
+    vertex_buffer: VertexBuffer<Vert>,
+    index_buffer:  IndexBuffer<u16>,
+}
+
+//   ____ _ _
+//  / ___| | |    __ _ _   _  ___ _ __
+// | |  _| | |   / _` | | | |/ _ \ '__|
+// | |_| | | |__| (_| | |_| |  __/ |
+//  \____|_|_____\__,_|\__, |\___|_|
+//                     |___/
+
+// Model alone goes to the picture, and with the effects on to the output
+pub struct GlLayer {
+    picture: Texture2d,
+    output:  Texture2d,
+    stencil: StencilRenderBuffer,
+    texel:   [f32; 2],
+    quad:    GlMesh,
+}
+
+//   ____ _ ____                _
+//  / ___| |  _ \ ___ _ __   __| | ___ _ __ ___ _ __
+// | |  _| | |_) / _ \ '_ \ / _` |/ _ \ '__/ _ \ '__|
+// | |_| | |  _ <  __/ | | | (_| |  __/ | |  __/ |
+//  \____|_|_| \_\___|_| |_|\__,_|\___|_|  \___|_|
+
+pub struct GlRenderer<'a, S>
+where S: Surface
+{
+    surface: &'a mut S,
+    gl:      &'a Gl,
+}
+
+impl<S> Renderer for GlRenderer<'_, S>
+where S: Surface
+{
+    type Device = Gl;
+
+    //             _
+    //  _ _    ___| | ___  __ _ _ __
+    // (_|_)  / __| |/ _ \/ _` | '__|
+    //  _ _  | (__| |  __/ (_| | |
+    // (_|_)  \___|_|\___|\__,_|_|
+
+    fn clear(&mut self,
+             color: [f32; 4])
+    {
+        let [r, g, b, a] = color;
+        self.surface.clear_color(r, g, b, a);
+    }
+
+    //             _                                       _
+    //  _ _    ___| | ___  __ _ _ __   _ __ ___   __ _ ___| | __
+    // (_|_)  / __| |/ _ \/ _` | '__| | '_ ` _ \ / _` / __| |/ /
+    //  _ _  | (__| |  __/ (_| | |    | | | | | | (_| \__ \   <
+    // (_|_)  \___|_|\___|\__,_|_|    |_| |_| |_|\__,_|___/_|\_\
+
+    fn clear_mask(&mut self) {self.surface.clear_stencil(0)}
+
+    //                            _
+    //  _ _   _ __ ___   __ _ ___| | __
+    // (_|_) | '_ ` _ \ / _` / __| |/ /
+    //  _ _  | | | | | | (_| \__ \   <
+    // (_|_) |_| |_| |_|\__,_|___/_|\_\
+
+    fn mask(&mut self,
+            mesh: &GlMesh,
+            pass: &Pass) -> Result<(), Box<dyn Error>>
+    {
+        let t = &pass.transform;
+        let uniforms = uniform!{
+            size:    t.size,
+            origin:  t.origin,
+            scale:   t.scale,
+            aspect:  t.aspect,
+            offset:  t.offset,
+        };
+
+        let op = StencilOperation::Replace;
+        let params = DrawParameters {
+            color_mask: (false, false, false, false),
+            backface_culling: culling(pass),
+            stencil: Stencil {
+                fail_operation_clockwise:                    op,
+                pass_depth_fail_operation_clockwise:         op,
+                depth_pass_operation_clockwise:              op,
+                fail_operation_counter_clockwise:            op,
+                pass_depth_fail_operation_counter_clockwise: op,
+                depth_pass_operation_counter_clockwise:      op,
+                reference_value_clockwise:                   1,
+                reference_value_counter_clockwise:           1,
+                .. Default::default()
+            },
+            .. Default::default()
+        };
+
+        self.surface.draw(&mesh.vertex_buffer,
+                          &mesh.index_buffer,
+                          &self.gl.programs[ProgramVariant::Mask as usize],
+                          &uniforms,
+                          &params)?;
+
+        Ok(())
+    }
+
+    //            _
+    //  _ _    __| |_ __ __ ___      __
+    // (_|_)  / _` | '__/ _` \ \ /\ / /
+    //  _ _  | (_| | | | (_| |\ V  V /
+    // (_|_)  \__,_|_|  \__,_| \_/\_/
+
+    fn draw(&mut self,
+            mesh:    &GlMesh,
+            texture: &Texture2d,
+            pass:    &Pass) -> Result<(), Box<dyn Error>>
+    {
+        let t = &pass.transform;
+        // Clamped like the original framework does, and like the CPU backend
+        let tex =
+            texture
+            .sampled()
+            .wrap_function(SamplerWrapFunction::Clamp);
+        let uniforms = uniform!{
+            size:    t.size,
+            origin:  t.origin,
+            scale:   t.scale,
+            opacity: pass.opacity,
+            tex:     tex,
+            aspect:  t.aspect,
+            offset:  t.offset,
+            screen:  pass.screen,
+            mult:    pass.mult,
+        };
+
+        let stencil_test = match pass.mask {
+            Mask::None    => StencilTest::AlwaysPass,
+            Mask::Inside  => StencilTest::IfEqual{mask: 1},
+            Mask::Outside => StencilTest::IfEqual{mask: 0},
+        };
+        let params = DrawParameters {
+            blend: blend(pass.blend),
+            backface_culling: culling(pass),
+            stencil: Stencil {
+                test_clockwise:                    stencil_test,
+                test_counter_clockwise:            stencil_test,
+                reference_value_clockwise:         1,
+                reference_value_counter_clockwise: 1,
+                .. Default::default()
+            },
+            .. Default::default()
+        };
+
+        self.surface.draw(&mesh.vertex_buffer,
+                          &mesh.index_buffer,
+                          &self.gl.programs[ProgramVariant::BlendNormal as usize],
+                          &uniforms,
+                          &params)?;
+
+        Ok(())
+    }
+}
+
+//  ____  _                _
+// | __ )| | ___ _ __   __| |
+// |  _ \| |/ _ \ '_ \ / _` |
+// | |_) | |  __/ | | | (_| |
+// |____/|_|\___|_| |_|\__,_|
+
+// Textures are premultiplied, so these follow the blending of the original
+// framework as is
+fn blend(mode: BlendMode) -> Blend
+{
+    match mode {
+        BlendMode::Add => Blend {
+            color: BlendingFunction::Addition {
+                source:      F::One,
+                destination: F::One,
+            },
+            alpha: BlendingFunction::Addition {
+                source:      F::Zero,
+                destination: F::One,
+            },
+            .. Default::default()
+        },
+        BlendMode::Mult => Blend {
+            color: BlendingFunction::Addition {
+                source:      F::DestinationColor,
+                destination: F::OneMinusSourceAlpha,
+            },
+            alpha: BlendingFunction::Addition {
+                source:      F::Zero,
+                destination: F::One,
+            },
+            .. Default::default()
+        },
+        BlendMode::Normal => Blend {
+            color: BlendingFunction::Addition {
+                source:      F::One,
+                destination: F::OneMinusSourceAlpha,
+            },
+            alpha: BlendingFunction::Addition {
+                source:      F::One,
+                destination: F::OneMinusSourceAlpha,
+            },
+            .. Default::default()
+        },
+    }
+}
+
+//   ____      _ _ _
+//  / ___|   _| | (_)_ __   __ _
+// | |  | | | | | | | '_ \ / _` |
+// | |__| |_| | | | | | | | (_| |
+//  \____\__,_|_|_|_|_| |_|\__, |
+//                         |___/
+
+// Same as the original framework, one-sided meshes drop their back faces, so
+// that mirroring deformers do not show them flipped
+fn culling(pass: &Pass) -> BackfaceCullingMode
+{
+    if pass.two_sided {
+        BackfaceCullingMode::CullingDisabled
+    } else {
+        BackfaceCullingMode::CullClockwise
+    }
+}
+
+//  ____
+// |  _ \ _ __ ___   __ _ _ __ __ _ _ __ ___  ___
+// | |_) | '__/ _ \ / _` | '__/ _` | '_ ` _ \/ __|
+// |  __/| | | (_) | (_| | | | (_| | | | | | \__ \
+// |_|   |_|  \___/ \__, |_|  \__,_|_| |_| |_|___/
+//                  |___/
+
+#[derive(Copy, Clone)]
+enum ProgramVariant {
+    Mask = 0,
+    BlendNormal,
+    Effects,
+
+    Counter
+}
+
+type Programs = [Rc<Program>; ProgramVariant::Counter as usize];
+
+fn programs<T>(display: &T) -> Result<Programs, Box<dyn Error>>
+where T: Facade + ?Sized
+{
+    let prg_mask =
+        program(display,
+                include_str!("../shaders/mask_vert.glsl"),
+                include_str!("../shaders/mask_frag.glsl"))?;
+
+    let prg_normal =
+        program(display,
+                include_str!("../shaders/vert.glsl"),
+                include_str!("../shaders/frag.glsl"))?;
+
+    let prg_effects =
+        program(display,
+                include_str!("../shaders/effects_vert.glsl"),
+                include_str!("../shaders/effects_frag.glsl"))?;
+
+    // Ideally I'd like to initialize array with Rc::new_zeroed() and then fill
+    // with correct indices using PV::NormalBlend etc., but this method for Rc
+    // is currently in nightly, so I just have to make sure that correct
+    // programs are at correct indices
+    Ok([
+        Rc::new(prg_mask),
+        Rc::new(prg_normal),
+        Rc::new(prg_effects),
+    ])
+}
+
+// Blending is done on premultiplied colors as is, same as in the original
+// framework, so glium should not convert anything to sRGB
+fn program<T>(display:         &T,
+              vertex_shader:   &str,
+              fragment_shader: &str) -> Result<Program, ProgramCreationError>
+where T: Facade + ?Sized
+{
+    Program::new(display,
+                 ProgramCreationInput::SourceCode {
+                     vertex_shader,
+                     fragment_shader,
+                     geometry_shader:                None,
+                     tessellation_control_shader:    None,
+                     tessellation_evaluation_shader: None,
+                     transform_feedback_varyings:    None,
+                     outputs_srgb:                   true,
+                     uses_point_size:                false,
+                 })
+}
+
+// __     __        _
+// \ \   / /__ _ __| |_ _____  __
+//  \ \ / / _ \ '__| __/ _ \ \/ /
+//   \ V /  __/ |  | ||  __/>  <
+//    \_/ \___|_|   \__\___/_/\_\
+
+#[derive(Copy, Clone, Debug)]
+struct Vert {
+    position:   [f32; 2],
+    texture_uv: [f32; 2],
+}
+
+implement_vertex!(Vert, position, texture_uv);
+
+fn verts(positions: &[[f32; 2]],
+         uvs:       &[[f32; 2]]) -> Vec<Vert>
+{
+    zip(positions,
+        uvs)
+    .map(|(pos, uv)| Vert{
+        position:   *pos,
+        texture_uv: *uv,
+    }).collect()
+}
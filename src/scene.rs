@@ -0,0 +1,516 @@
+use std::{
+    mem,
+    iter,
+    error::Error,
+    path::Path,
+};
+use crate::{
+    calc_framing,
+    framework::Model,
+    renderer::{Gl, Pass, Post, Device, Renderer, Transform},
+    config::{Config, SceneModel, EffectsConfig},
+};
+
+// Seconds it takes for a loaded model to replace the old one
+const FADE: f32 = 0.5;
+
+//  ____
+// / ___|  ___ ___ _ __   ___
+// \___ \ / __/ _ \ '_ \ / _ \
+//  ___) | (_|  __/ | | |  __/
+// |____/ \___\___|_| |_|\___|
+
+// Models fading in and out are drawn on a layer one at a time, to be faded
+// as a whole
+pub struct Scene<D = Gl>
+where D: Device
+{
+    members: Vec<Member<D>>,
+    order:   Vec<usize>,
+    device:  D,
+    quad:    D::Mesh,
+    layer:   Option<(D::Layer, [u32; 2])>,
+}
+
+//  __  __                _
+// |  \/  | ___ _ __ ___ | |__   ___ _ __
+// | |\/| |/ _ \ '_ ` _ \| '_ \ / _ \ '__|
+// | |  | |  __/ | | | | | |_) |  __/ |
+// |_|  |_|\___|_| |_| |_|_.__/ \___|_|
+
+struct Member<D>
+where D: Device
+{
+    id:     String,
+    z:      i32,
+    offset: [f32; 2],
+    zoom:   f32,
+    idle:   Option<(String, String)>,
+    model:  Model<D>,
+    fade:   Option<Fade<D>>,
+}
+
+//  _____         _
+// |  ___|_ _  __| | ___
+// | |_ / _` |/ _` |/ _ \
+// |  _| (_| | (_| |  __/
+// |_|  \__,_|\__,_|\___|
+
+// Model on its way out after another one was loaded
+struct Fade<D>
+where D: Device
+{
+    model:   Model<D>,
+    elapsed: f32,
+}
+
+//  ____
+// / ___|  ___ ___ _ __   ___   _ _
+// \___ \ / __/ _ \ '_ \ / _ \ (_|_)
+//  ___) | (_|  __/ | | |  __/  _ _
+// |____/ \___\___|_| |_|\___| (_|_)
+
+impl<D> Scene<D>
+where D: Device + Clone
+{
+
+    //  _ _   _ __   _____      __
+    // (_|_) | '_ \ / _ \ \ /\ / /
+    //  _ _  | | | |  __/\ V  V /
+    // (_|_) |_| |_|\___| \_/\_/
+
+    // Without a scene configured, the model from [model] is the only one.
+    // First model can be some other file, e.g. one from the library
+    pub fn new(config: &Config,
+               first:  Option<&Path>,
+               device: &D) -> Result<Self, Box<dyn Error>>
+    {
+        let mut members = Vec::new();
+
+        if config.scene.models.is_empty() {
+            let model = match first {
+                Some(f) => Model::load(config,
+                                       f,
+                                       &[],
+                                       device)?,
+                None    => Model::new(config,
+                                      device)?,
+            };
+
+            members.push(Member {
+                id:     "".to_string(),
+                z:      0,
+                offset: [0., 0.],
+                zoom:   1.,
+                idle:   config.model.motions.idle.clone(),
+                model,
+                fade:   None,
+            });
+        }
+
+        for (i, m) in config.scene.models.iter().enumerate() {
+            let file = match (i, first) {
+                (0, Some(f)) => f.to_path_buf(),
+                _            => expanduser::expanduser(&m.file)?,
+            };
+
+            let model = Model::load(config,
+                                    &file,
+                                    &m.open,
+                                    device)
+                        .map_err(|e| format!("Failed to load {}: {e}", m.id))?;
+
+            let mut member = Member {
+                id:     m.id.clone(),
+                z:      0,
+                offset: [0., 0.],
+                zoom:   1.,
+                idle:   None,
+                model,
+                fade:   None,
+            };
+            member.configure(config,
+                             m);
+            members.push(member);
+        }
+
+        let quad = device.mesh(&[[-1., -1.], [-1.,  1.], [ 1., -1.], [ 1.,  1.]],
+                               &[[ 0.,  0.], [ 0.,  1.], [ 1.,  0.], [ 1.,  1.]],
+                               &[0, 1, 2, 2, 1, 3])?;
+
+        let mut scene = Self {
+            members,
+            order:  Vec::new(),
+            device: device.clone(),
+            quad,
+            layer:  None,
+        };
+        scene.sort();
+
+        Ok(scene)
+    }
+
+    //                       _
+    //  _ _   ___  ___  _ __| |_
+    // (_|_) / __|/ _ \| '__| __|
+    //  _ _  \__ \ (_) | |  | |_
+    // (_|_) |___/\___/|_|   \__|
+
+    // Back to front, models with the same z go in the order of the scene
+    fn sort(&mut self)
+    {
+        let members = &self.members;
+        self.order = (0..members.len()).collect();
+        self.order.sort_by_key(|i| members[*i].z);
+    }
+
+    //                         __ _
+    //  _ _    ___ ___  _ __  / _(_) __ _ _   _ _ __ ___
+    // (_|_)  / __/ _ \| '_ \| |_| |/ _` | | | | '__/ _ \
+    //  _ _  | (_| (_) | | | |  _| | (_| | |_| | | |  __/
+    // (_|_)  \___\___/|_| |_|_| |_|\__, |\__,_|_|  \___|
+    //                              |___/
+
+    // Placement, colors, parts and idle motions change on the fly, models
+    // themselves need a restart or a load
+    pub fn configure(&mut self,
+                     config: &Config)
+    {
+        self.members.iter_mut()
+        .flat_map(|m| iter::once(&mut m.model)
+                      .chain(m.fade.as_mut().map(|f| &mut f.model)))
+        .for_each(|m| {
+            m.set_colors(&config.model.colors);
+            m.set_parts(&config.model.parts);
+            m.set_tracking(&config.tracking);
+            m.set_lipsync(&config.lipsync);
+            m.set_blink(&config.model.blink);
+            m.set_graph(&config.model.graph);
+        });
+
+        if config.scene.models.is_empty() {
+            self.members.iter_mut()
+            .for_each(|m| {
+                m.idle = config.model.motions.idle.clone();
+                m.apply();
+            });
+            return
+        }
+
+        for m in &config.scene.models {
+            match self.members.iter_mut().find(|s| s.id == m.id) {
+                Some(s) => s.configure(config,
+                                       m),
+                None    => eprintln!("Model {} is not in the scene, restart \
+                                      to add it",
+                                     m.id),
+            }
+        }
+        self.sort();
+    }
+
+    //                        _       _
+    //  _ _   _   _ _ __   __| | __ _| |_ ___
+    // (_|_) | | | | '_ \ / _` |/ _` | __/ _ \
+    //  _ _  | |_| | |_) | (_| | (_| | ||  __/
+    // (_|_)  \__,_| .__/ \__,_|\__,_|\__\___|
+    //             |_|
+
+    pub fn update(&mut self,
+                  dt: f64) -> Result<(), Box<dyn Error>>
+    {
+        for m in &mut self.members {
+            m.model.update(dt)?;
+
+            let fade = match &mut m.fade {
+                Some(f) => f,
+                None    => continue,
+            };
+
+            fade.elapsed += dt as f32;
+            fade.model.update(dt)?;
+
+            // Old model goes away along with its textures and buffers
+            if fade.elapsed >= FADE {
+                m.fade = None;
+            }
+        }
+
+        Ok(())
+    }
+
+    //            _
+    //  _ _    __| |_ __ __ ___      __
+    // (_|_)  / _` | '__/ _` \ \ /\ / /
+    //  _ _  | (_| | | | (_| |\ V  V /
+    // (_|_)  \__,_|_|  \__,_| \_/\_/
+
+    // Each model keeps its own order of drawables, one model over another.
+    // Old model goes under the new one while they fade
+    pub fn draw(&mut self,
+                renderer: &mut dyn Renderer<Device = D>,
+                view:     [f32; 2],
+                config:   &Config) -> Result<(), Box<dyn Error>>
+    {
+        let post = Post::new(&EffectsConfig::default());
+        let size = view.map(|v| (v as u32).max(1));
+
+        for i in &self.order {
+            let m = &self.members[*i];
+            let draw = |r: &mut dyn Renderer<Device = D>,
+                        model: &Model<D>| -> Result<(), Box<dyn Error>> {
+                let [aspect, offset] = calc_framing(model,
+                                                    view,
+                                                    config);
+                model
+                .draw(r,
+                      aspect,
+                      offset)
+                .map_err(|e| format!("Failed to draw model {}: {e}", m.id))?;
+                Ok(())
+            };
+
+            let fade = match &m.fade {
+                Some(f) => f,
+                None    => {draw(renderer, &m.model)?; continue}
+            };
+
+            let t = (fade.elapsed / FADE).min(1.);
+            for (model, opacity) in [(&fade.model, 1. - t),
+                                     (&m.model,    t)] {
+                let layer = match self.layer.take() {
+                    Some((l, s)) if s == size => l,
+                    _                         => self.device.layer(size)?,
+                };
+                let (layer, _) = self.layer.insert((layer, size));
+
+                let texture = self.device.develop(layer,
+                                                  &post,
+                                                  |r| draw(r, model))?;
+
+                renderer.draw(&self.quad,
+                              texture,
+                              &Pass {
+                                  opacity,
+                                  .. Pass::new(Transform::flat([1., 1.]))
+                              })?;
+            }
+        }
+
+        // Nothing fading, nothing to hold on to
+        if self.members.iter().all(|m| m.fade.is_none()) {
+            self.layer = None;
+        }
+
+        Ok(())
+    }
+
+    //        _                   _
+    //  _ _  | |_ ___  _   _  ___| |__
+    // (_|_) | __/ _ \| | | |/ __| '_ \
+    //  _ _  | || (_) | |_| | (__| | | |
+    // (_|_)  \__\___/ \__,_|\___|_| |_|
+
+    // Topmost model with a hit area under the point takes it
+    pub fn touch(&mut self,
+                 point:  [f32; 2],
+                 view:   [f32; 2],
+                 config: &Config)
+    {
+        for i in self.order.iter().rev() {
+            let model = &mut self.members[*i].model;
+            let [aspect, offset] = calc_framing(model,
+                                                view,
+                                                config);
+
+            if model.touch(point,
+                           aspect,
+                           offset) {return}
+        }
+    }
+
+    //                    _
+    //  _ _   _ __   ___ | | _____
+    // (_|_) | '_ \ / _ \| |/ / _ \
+    //  _ _  | |_) | (_) |   <  __/
+    // (_|_) | .__/ \___/|_|\_\___|
+    //       |_|
+
+    pub fn poke(&mut self)
+    {
+        self.members.iter_mut()
+        .for_each(|m| m.model.poke());
+    }
+
+    //         __ _ _
+    //  _ _   / _(_) | ___  ___
+    // (_|_) | |_| | |/ _ \/ __|
+    //  _ _  |  _| | |  __/\__ \
+    // (_|_) |_| |_|_|\___||___/
+
+    pub fn files(&self) -> impl Iterator<Item = &Path>
+    {
+        self.members.iter()
+        .flat_map(|m| m.model.files())
+    }
+
+    //                 _                 _
+    //  _ _   _ __ ___| | ___   __ _  __| |
+    // (_|_) | '__/ _ \ |/ _ \ / _` |/ _` |
+    //  _ _  | | |  __/ | (_) | (_| | (_| |
+    // (_|_) |_|  \___|_|\___/ \__,_|\__,_|
+
+    // Returns whether any model was loaded anew
+    pub fn reload(&mut self,
+                  config: &Config,
+                  device: &D,
+                  file:   &Path) -> Result<bool, Box<dyn Error>>
+    {
+        let mut anew = false;
+        for m in &mut self.members {
+            anew |= m.model.reload(config,
+                                   device,
+                                   file)?;
+        }
+
+        Ok(anew)
+    }
+
+    //        _                 _
+    //  _ _  | | ___   __ _  __| |
+    // (_|_) | |/ _ \ / _` |/ _` |
+    //  _ _  | | (_) | (_| | (_| |
+    // (_|_) |_|\___/ \__,_|\__,_|
+
+    // Replaces a model, the first one if no one in particular
+    pub fn load(&mut self,
+                id:     Option<&str>,
+                config: &Config,
+                device: &D,
+                file:   &Path,
+                open:   &[(String, String)]) -> Result<(), Box<dyn Error>>
+    {
+        let i = self.index(id)?;
+
+        let new = Model::load(config,
+                                  file,
+                                  open,
+                                  device)?;
+
+        let member = &mut self.members[i];
+        let old = mem::replace(&mut member.model, new);
+        member.fade = Some(Fade {
+            model:   old,
+            elapsed: 0.,
+        });
+        member.apply();
+
+        eprintln!("Loaded model {}", file.display());
+        Ok(())
+    }
+
+    //                            _      _
+    //  _ _   _ __ ___   ___   __| | ___| |
+    // (_|_) | '_ ` _ \ / _ \ / _` |/ _ \ |
+    //  _ _  | | | | | | (_) | (_| |  __/ |
+    // (_|_) |_| |_| |_|\___/ \__,_|\___|_|
+
+    pub fn model(&self,
+                 id: Option<&str>) -> Result<&Model<D>, String>
+    {
+        let i = self.index(id)?;
+        Ok(&self.members[i].model)
+    }
+
+    //                 _           _
+    //  _ _   ___  ___| | ___  ___| |_
+    // (_|_) / __|/ _ \ |/ _ \/ __| __|
+    //  _ _  \__ \  __/ |  __/ (__| |_
+    // (_|_) |___/\___|_|\___|\___|\__|
+
+    // Models a message goes to, all of them if no one in particular
+    pub fn select(&mut self,
+                  id: Option<&str>) -> Result<Vec<&mut Model<D>>, String>
+    {
+        let models = match id {
+            None    => self.members.iter_mut()
+                       .map(|m| &mut m.model)
+                       .collect(),
+            Some(_) => {
+                let i = self.index(id)?;
+                vec![&mut self.members[i].model]
+            }
+        };
+
+        Ok(models)
+    }
+
+    //        _           _
+    //  _ _  (_)_ __   __| | _____  __
+    // (_|_) | | '_ \ / _` |/ _ \ \/ /
+    //  _ _  | | | | | (_| |  __/>  <
+    // (_|_) |_|_| |_|\__,_|\___/_/\_\
+
+    fn index(&self,
+             id: Option<&str>) -> Result<usize, String>
+    {
+        match id {
+            None     => Ok(0),
+            Some(id) => self.members.iter()
+                        .position(|m| m.id == id)
+                        .ok_or(format!("No model {id} in the scene")),
+        }
+    }
+}
+
+//  __  __                _
+// |  \/  | ___ _ __ ___ | |__   ___ _ __   _ _
+// | |\/| |/ _ \ '_ ` _ \| '_ \ / _ \ '__| (_|_)
+// | |  | |  __/ | | | | | |_) |  __/ |     _ _
+// |_|  |_|\___|_| |_| |_|_.__/ \___|_|    (_|_)
+
+impl<D> Member<D>
+where D: Device
+{
+
+    //                         __ _
+    //  _ _    ___ ___  _ __  / _(_) __ _ _   _ _ __ ___
+    // (_|_)  / __/ _ \| '_ \| |_| |/ _` | | | | '__/ _ \
+    //  _ _  | (_| (_) | | | |  _| | (_| | |_| | | |  __/
+    // (_|_)  \___\___/|_| |_|_| |_|\__, |\__,_|_|  \___|
+    //                              |___/
+
+    fn configure(&mut self,
+                 config: &Config,
+                 model:  &SceneModel)
+    {
+        self.z = model.z;
+        self.offset = model.position;
+        self.zoom = model.scale;
+        self.idle = match &model.idle {
+            Some(i) => Some(i.clone()),
+            None    => config.model.motions.idle.clone(),
+        };
+        self.apply();
+    }
+
+    //                          _
+    //  _ _    __ _ _ __  _ __ | |_   _
+    // (_|_)  / _` | '_ \| '_ \| | | | |
+    //  _ _  | (_| | |_) | |_) | | |_| |
+    // (_|_)  \__,_| .__/| .__/|_|\__, |
+    //             |_|   |_|      |___/
+
+    fn apply(&mut self)
+    {
+        let models =
+            iter::once(&mut self.model)
+            .chain(self.fade.as_mut().map(|f| &mut f.model));
+
+        for model in models {
+            model.set_placement(self.offset,
+                                self.zoom);
+            model.set_idle(&self.idle);
+        }
+    }
+}
@@ -0,0 +1,95 @@
+#version 330
+
+in vec2 frag_uv;
+
+uniform sampler2D tex;
+uniform vec2 texel;
+uniform float outline;
+uniform vec4 outline_color;
+uniform float shadow;
+uniform vec2 shadow_offset;
+uniform vec4 shadow_color;
+uniform float glow;
+uniform float glow_radius;
+uniform float hue;
+uniform float saturation;
+uniform float brightness;
+
+// Taps around a point, same as the cpu renderer takes them
+const int DIRECTIONS = 16;
+const int RINGS = 3;
+const float TAU = 6.28318530718;
+
+vec2 tap(int d, int r, float radius)
+{
+	float angle = TAU * float(d) / float(DIRECTIONS);
+	float distance = radius * float(r) / float(RINGS);
+	return vec2(cos(angle), sin(angle)) * distance * texel;
+}
+
+// Most covered point in a circle, which makes the model fatter
+float dilate(vec2 uv, float radius)
+{
+	float a = texture(tex, uv).a;
+	for (int r = 1; r <= RINGS; r++)
+		for (int d = 0; d < DIRECTIONS; d++)
+			a = max(a, texture(tex, uv + tap(d, r, radius)).a);
+	return a;
+}
+
+// Average over a circle, closer points weigh more
+vec4 blur(vec2 uv, float radius)
+{
+	vec4 sum = texture(tex, uv);
+	float total = 1.0;
+	for (int r = 1; r <= RINGS; r++) {
+		float w = 1.0 - float(r) / float(RINGS + 1);
+		for (int d = 0; d < DIRECTIONS; d++)
+			sum += texture(tex, uv + tap(d, r, radius)) * w;
+		total += w * float(DIRECTIONS);
+	}
+	return sum / total;
+}
+
+// Hue turns around the gray axis, saturation goes from or to the luma
+vec4 grade(vec4 color)
+{
+	if (color.a <= 0.0) return color;
+
+	vec3 c = color.rgb / color.a;
+	float cs = cos(hue);
+	float k = (1.0 - cs) / 3.0;
+	float s = sin(hue) / sqrt(3.0);
+	c = vec3(c.r * (cs + k) + c.g * (k - s) + c.b * (k + s),
+	         c.r * (k + s) + c.g * (cs + k) + c.b * (k - s),
+	         c.r * (k - s) + c.g * (k + s) + c.b * (cs + k));
+
+	float luma = dot(c, vec3(0.2126, 0.7152, 0.0722));
+	c = mix(vec3(luma), c, saturation) * brightness;
+
+	return vec4(clamp(c, 0.0, 1.0) * color.a, color.a);
+}
+
+void main()
+{
+	// Everything is premultiplied, things go under the model
+	vec4 color = grade(texture(tex, frag_uv));
+
+	vec4 under = vec4(0.0);
+	if (outline > 0.0)
+		under = outline_color * dilate(frag_uv, outline);
+	if (shadow_color.a > 0.0) {
+		vec2 uv = frag_uv - shadow_offset * vec2(1.0, -1.0) * texel;
+		under += shadow_color * blur(uv, shadow).a * (1.0 - under.a);
+	}
+	color += under * (1.0 - color.a);
+
+	// Glow adds light rather than covering anything
+	if (glow > 0.0) {
+		vec4 g = blur(frag_uv, glow_radius) * glow;
+		color.rgb += g.rgb;
+		color.a += g.a * (1.0 - color.a);
+	}
+
+	gl_FragColor = clamp(color, 0.0, 1.0);
+}
@@ -3,14 +3,11 @@
 in vec2 position;
 in vec2 texture_uv;
 
-uniform vec2 aspect;
-
 out vec2 frag_uv;
 
 void main()
 {
+	// Quad is the whole view already
 	frag_uv = texture_uv;
-	vec2 pos = position;
-	pos *= aspect;
-	gl_Position = vec4(pos, 0.0, 1.0);
+	gl_Position = vec4(position, 0.0, 1.0);
 }
@@ -9,12 +9,10 @@ uniform vec4 mult;
 
 void main()
 {
+	// Texture is premultiplied, so is the output
 	vec4 color = texture(tex, frag_uv);
 	color.rgb *= mult.rgb;
-	// This is taken from the Live2D Framework, I can not figure out where the
-	// fuck does this come from
-	color.rgb = color.rgb + screen.rgb - (color.rgb * screen.rgb);
-	color.a *= opacity;
+	color.rgb = color.rgb + screen.rgb * color.a - color.rgb * screen.rgb;
+	color *= opacity;
 	gl_FragColor = color;
 }
-
@@ -7,6 +7,7 @@ uniform vec2 size;
 uniform vec2 origin;
 uniform float scale;
 uniform vec2 aspect;
+uniform vec2 offset;
 
 void main()
 {
@@ -17,6 +18,7 @@ void main()
 	pos *= 2;      //        -> [ 0; 2]
 	pos -= 1;      //        -> [-1; 1]
 	pos *= aspect; // preserve aspect ratio
+	pos += offset; // place in the view
 	gl_Position = vec4(pos, 0.0, 1.0);
 }
 
@@ -0,0 +1,255 @@
+use std::{
+    thread,
+    error::Error,
+    time::{Duration, Instant},
+    net::UdpSocket,
+    collections::HashMap,
+};
+use cubism::{
+    core,
+    controller::Controller,
+};
+use crate::{
+    framework::approach,
+    config::TrackingConfig,
+};
+
+// Seconds it takes to hand the model over between tracking and motions
+const HANDOVER: f32 = 0.5;
+
+// Runs after the standard controllers, over whatever they did
+const PRIORITY: usize = 300;
+
+// Longest wait after the socket fails, the wait doubles up to it
+const BACKOFF: Duration = Duration::from_secs(5);
+
+//  _____               _    _
+// |_   _| __ __ _  ___| | _(_)_ __   __ _
+//   | || '__/ _` |/ __| |/ / | '_ \ / _` |
+//   | || | | (_| | (__|   <| | | | | (_| |
+//   |_||_|  \__,_|\___|_|\_\_|_| |_|\__, |
+//                                   |___/
+
+// Latest values from trackers, held over what motions do while they keep
+// coming
+pub struct Tracking {
+    values:    HashMap<String, f32>,
+    // Values the parameters are at, catching up with the tracked ones
+    smoothed:  HashMap<String, f32>,
+    neutral:   HashMap<String, f32>,
+    gain:      HashMap<String, f32>,
+    smoothing: f32,
+    seen:      Option<Instant>,
+    timeout:   f32,
+    // How much of the tracked values is shown
+    weight:    f32,
+}
+
+impl Tracking {
+
+    //  _ _   _ __   _____      __
+    // (_|_) | '_ \ / _ \ \ /\ / /
+    //  _ _  | | | |  __/\ V  V /
+    // (_|_) |_| |_|\___| \_/\_/
+
+    pub fn new(config: &TrackingConfig) -> Self
+    {
+        Self {
+            values:    HashMap::new(),
+            smoothed:  HashMap::new(),
+            neutral:   config.neutral.clone(),
+            gain:      config.gain.clone(),
+            smoothing: config.smoothing,
+            seen:      None,
+            timeout:   config.timeout,
+            weight:    0.,
+        }
+    }
+
+    //                         __ _
+    //  _ _    ___ ___  _ __  / _(_) __ _ _   _ _ __ ___
+    // (_|_)  / __/ _ \| '_ \| |_| |/ _` | | | | '__/ _ \
+    //  _ _  | (_| (_) | | | |  _| | (_| | |_| | | |  __/
+    // (_|_)  \___\___/|_| |_|_| |_|\__, |\__,_|_|  \___|
+    //                              |___/
+
+    // Neutral pose from calibration stays until the config is reloaded
+    pub fn configure(&mut self,
+                     config: &TrackingConfig)
+    {
+        self.neutral = config.neutral.clone();
+        self.gain = config.gain.clone();
+        self.smoothing = config.smoothing;
+        self.timeout = config.timeout;
+    }
+
+    //         __               _
+    //  _ _   / _| ___  ___  __| |
+    // (_|_) | |_ / _ \/ _ \/ _` |
+    //  _ _  |  _|  __/  __/ (_| |
+    // (_|_) |_|  \___|\___|\__,_|
+
+    // Parameters that are not in the model are kept, and do nothing
+    pub fn feed(&mut self,
+                values: &[(String, f32)])
+    {
+        self.values.extend(values.iter().cloned());
+        self.seen = Some(Instant::now());
+    }
+
+    //                  _ _ _               _
+    //  _ _    ___ __ _| (_) |__  _ __ __ _| |_ ___
+    // (_|_)  / __/ _` | | | '_ \| '__/ _` | __/ _ \
+    //  _ _  | (_| (_| | | | |_) | | | (_| | ||  __/
+    // (_|_)  \___\__,_|_|_|_.__/|_|  \__,_|\__\___|
+
+    // Pose being tracked right now is the one the model rests in, listed the
+    // way the config takes it
+    pub fn calibrate(&mut self) -> Result<String, String>
+    {
+        if self.values.is_empty() {
+            return Err("Nothing is being tracked".to_string())
+        }
+        self.neutral = self.values.clone();
+
+        let mut neutral: Vec<_> = self.neutral.iter().collect();
+        neutral.sort_by(|a, b| a.0.cmp(b.0));
+
+        Ok(neutral.into_iter()
+           .map(|(id, v)| format!("{id} = {v}\n"))
+           .collect())
+    }
+}
+
+impl Controller for Tracking {
+    // Tracked values replace what motions do, faded in and out as trackers
+    // come and go. Past the neutral pose they go from the parameter default
+    fn update_parameters(&mut self,
+                         model: &mut core::Model,
+                         delta: f32)
+    {
+        let live =
+            self.seen
+            .is_some_and(|t| t.elapsed().as_secs_f32() < self.timeout);
+        let target = match live {
+            true  => 1.,
+            false => 0.,
+        };
+        self.weight = approach(self.weight,
+                               target,
+                               delta / HANDOVER);
+
+        if self.weight == 0. {
+            self.values.clear();
+            self.smoothed.clear();
+            return
+        }
+
+        let indices: Vec<_> =
+            self.values.keys()
+            .map(|id| model.parameter_ids().iter().position(|p| p == id))
+            .collect();
+        let [min, max, default] = [model.parameter_min().to_vec(),
+                                   model.parameter_max().to_vec(),
+                                   model.parameter_default().to_vec()];
+        let values = model.parameter_values_mut();
+
+        let catch_up = match self.smoothing > 0. {
+            true  => 1. - (-delta / self.smoothing).exp(),
+            false => 1.,
+        };
+
+        for ((id, raw), i) in self.values.iter().zip(indices) {
+            let i = match i {
+                Some(i) => i,
+                None    => continue,
+            };
+            let gain = self.gain.get(id).copied().unwrap_or(1.);
+            let target = match self.neutral.get(id) {
+                Some(n) => default[i] + (raw - n) * gain,
+                None    => raw * gain,
+            };
+
+            let smoothed =
+                self.smoothed
+                .entry(id.clone())
+                .or_insert(target);
+            *smoothed += (target - *smoothed) * catch_up;
+
+            let motion = values[i];
+            let tracked = motion + (*smoothed - motion) * self.weight;
+            values[i] = tracked.clamp(min[i], max[i]);
+        }
+    }
+
+    fn priority(&self) -> usize {PRIORITY}
+}
+
+//                          _
+//  _ _   _ __ ___  ___ ___(_)_   _____
+// (_|_) | '__/ _ \/ __/ _ \ \ \ / / _ \
+//  _ _  | | |  __/ (_|  __/ |\ V /  __/
+// (_|_) |_|  \___|\___\___|_| \_/ \___|
+
+// Nothing is bound unless asked for, receiving goes on for as long as liver.
+// Each datagram is parsed on its own, parsers saying what is wrong with it
+pub fn receive<P, F>(address: &str,
+                     size:    usize,
+                     parse:   P,
+                     send:    F) -> Result<(), Box<dyn Error>>
+where P: Fn(&[u8]) -> Result<Vec<(String, f32)>, &'static str> + Send + 'static,
+      F: Fn(Vec<(String, f32)>) + Send + 'static
+{
+    let socket = UdpSocket::bind(address)?;
+    listen(socket,
+           size,
+           parse,
+           send);
+
+    Ok(())
+}
+
+//        _ _     _
+//  _ _  | (_)___| |_ ___ _ __
+// (_|_) | | / __| __/ _ \ '_ \
+//  _ _  | | \__ \ ||  __/ | | |
+// (_|_) |_|_|___/\__\___|_| |_|
+
+pub fn listen<P, F>(socket: UdpSocket,
+                    size:   usize,
+                    parse:  P,
+                    send:   F)
+where P: Fn(&[u8]) -> Result<Vec<(String, f32)>, &'static str> + Send + 'static,
+      F: Fn(Vec<(String, f32)>) + Send + 'static
+{
+    thread::spawn(move || {
+        let mut buffer = vec![0; size];
+        // Failing socket is waited on, and told about once, not on every try
+        let mut wait = Duration::ZERO;
+
+        loop {
+            let size = match socket.recv(&mut buffer) {
+                Ok(s)  => s,
+                Err(e) => {
+                    if wait.is_zero() {
+                        eprintln!("Failed to receive tracking: {e}");
+                    }
+                    wait = (wait * 2).clamp(Duration::from_millis(10),
+                                            BACKOFF);
+                    thread::sleep(wait);
+                    continue
+                }
+            };
+
+            if !wait.is_zero() {
+                eprintln!("Receiving tracking again");
+                wait = Duration::ZERO;
+            }
+
+            match parse(&buffer[..size]) {
+                Ok(v)  => if !v.is_empty() {send(v)},
+                Err(e) => eprintln!("{e}"),
+            }
+        }
+    });
+}
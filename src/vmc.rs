@@ -0,0 +1,493 @@
+use std::{
+    str,
+    iter::zip,
+    error::Error,
+    collections::HashMap,
+};
+use crate::{
+    tracking,
+    config::{VmcConfig, MapConfig},
+};
+
+// Largest datagram there is, a bundle of the whole skeleton comes close
+const DATAGRAM: usize = 65536;
+
+// Parameter values straight from the sender, past the mapping
+const PARAM: &str = "/liver/param";
+const BLEND: &str = "/VMC/Ext/Blend/Val";
+const BONE:  &str = "/VMC/Ext/Bone/Pos";
+
+//     _
+//    / \   _ __ __ _
+//   / _ \ | '__/ _` |
+//  / ___ \| | | (_| |
+// /_/   \_\_|  \__, |
+//              |___/
+
+// What an OSC message carries, as far as liver cares
+enum Arg {
+    Number(f32),
+    Text(String),
+    Other,
+}
+
+//                          _
+//  _ _   _ __ ___  ___ ___(_)_   _____
+// (_|_) | '__/ _ \/ __/ _ \ \ \ / / _ \
+//  _ _  | | |  __/ (_|  __/ |\ V /  __/
+// (_|_) |_|  \___|\___\___|_| \_/ \___|
+
+pub fn receive<F>(config: &VmcConfig,
+                  send:   F) -> Result<(), Box<dyn Error>>
+where F: Fn(Vec<(String, f32)>) + Send + 'static
+{
+    if !config.enabled {return Ok(())}
+
+    let map = config.map.clone();
+    tracking::receive(&config.address,
+                      DATAGRAM,
+                      move |d| parse(&map, d),
+                      send)
+}
+
+//  _ _   _ __   __ _ _ __ ___  ___
+// (_|_) | '_ \ / _` | '__/ __|/ _ \
+//  _ _  | |_) | (_| | |  \__ \  __/
+// (_|_) | .__/ \__,_|_|  |___/\___|
+//       |_|
+
+// Whole packets go in at once, so a frame is never half applied
+fn parse(map:  &HashMap<String, MapConfig>,
+         data: &[u8]) -> Result<Vec<(String, f32)>, &'static str>
+{
+    let mut values = Vec::new();
+    packet(data,
+           &mut |address, args| values.extend(translate(map,
+                                                        address,
+                                                        args)))
+    .ok_or("Malformed OSC packet")?;
+
+    Ok(values)
+}
+
+//                         _        _
+//  _ _   _ __   __ _  ___| | _____| |_
+// (_|_) | '_ \ / _` |/ __| |/ / _ \ __|
+//  _ _  | |_) | (_| | (__|   <  __/ |_
+// (_|_) | .__/ \__,_|\___|_|\_\___|\__|
+//       |_|
+
+// Bundles are gone through in order, their time tags are not waited for
+fn packet<F>(data: &[u8],
+             f:    &mut F) -> Option<()>
+where F: FnMut(&str, &[Arg])
+{
+    if let Some(mut rest) = data.strip_prefix(b"#bundle\0") {
+        take(&mut rest, 8)?;
+
+        while !rest.is_empty() {
+            let size = u32::from_be_bytes(bytes(&mut rest)?);
+            packet(take(&mut rest, size as usize)?,
+                   f)?;
+        }
+
+        return Some(())
+    }
+
+    let mut rest = data;
+    let address = string(&mut rest)?;
+    let tags = string(&mut rest)?;
+
+    let args =
+        tags.strip_prefix(',')?
+        .chars()
+        .map(|t| arg(t, &mut rest))
+        .collect::<Option<Vec<_>>>()?;
+
+    f(&address, &args);
+    Some(())
+}
+
+//  _ _    __ _ _ __ __ _
+// (_|_)  / _` | '__/ _` |
+//  _ _  | (_| | | | (_| |
+// (_|_)  \__,_|_|  \__, |
+//                  |___/
+
+fn arg(tag:  char,
+       rest: &mut &[u8]) -> Option<Arg>
+{
+    let arg = match tag {
+        'i'                   => Arg::Number(i32::from_be_bytes(bytes(rest)?)
+                                             as f32),
+        'f'                   => Arg::Number(f32::from_be_bytes(bytes(rest)?)),
+        'd'                   => Arg::Number(f64::from_be_bytes(bytes(rest)?)
+                                             as f32),
+        's' | 'S'             => Arg::Text(string(rest)?),
+        'h' | 't'             => {take(rest, 8)?; Arg::Other}
+        'c' | 'r' | 'm'       => {take(rest, 4)?; Arg::Other}
+        'b'                   => {
+            let size = u32::from_be_bytes(bytes(rest)?);
+            take(rest, pad(size as usize))?;
+            Arg::Other
+        }
+        'T' | 'F' | 'N' | 'I' => Arg::Other,
+        _                     => return None,
+    };
+
+    Some(arg)
+}
+
+//            _        _
+//  _ _   ___| |_ _ __(_)_ __   __ _
+// (_|_) / __| __| '__| | '_ \ / _` |
+//  _ _  \__ \ |_| |  | | | | | (_| |
+// (_|_) |___/\__|_|  |_|_| |_|\__, |
+//                             |___/
+
+// Null terminated, padded to four bytes
+fn string(rest: &mut &[u8]) -> Option<String>
+{
+    let end = rest.iter().position(|b| *b == 0)?;
+    let text = str::from_utf8(&rest[..end]).ok()?.to_string();
+    take(rest, pad(end + 1))?;
+
+    Some(text)
+}
+
+//        _        _
+//  _ _  | |_ __ _| | _____
+// (_|_) | __/ _` | |/ / _ \
+//  _ _  | || (_| |   <  __/
+// (_|_)  \__\__,_|_|\_\___|
+
+fn take<'a>(rest: &mut &'a [u8],
+            size: usize) -> Option<&'a [u8]>
+{
+    if rest.len() < size {return None}
+    let (head, tail) = rest.split_at(size);
+    *rest = tail;
+
+    Some(head)
+}
+
+fn bytes<const N: usize>(rest: &mut &[u8]) -> Option<[u8; N]>
+{
+    take(rest, N)?.try_into().ok()
+}
+
+fn pad(size: usize) -> usize {(size + 3) & !3}
+
+//        _                       _       _
+//  _ _  | |_ _ __ __ _ _ __  ___| | __ _| |_ ___
+// (_|_) | __| '__/ _` | '_ \/ __| |/ _` | __/ _ \
+//  _ _  | |_| | | (_| | | | \__ \ | (_| | ||  __/
+// (_|_)  \__|_|  \__,_|_| |_|___/_|\__,_|\__\___|
+
+// Messages to the parameter values they stand for, others are ignored
+fn translate(map:     &HashMap<String, MapConfig>,
+             address: &str,
+             args:    &[Arg]) -> Vec<(String, f32)>
+{
+    let mapped = |source: &str, value: f32| {
+        map.get(source)
+        .map(|m| (m.param.clone(), value * m.scale + m.offset))
+    };
+
+    match (address, args) {
+        (PARAM, [Arg::Text(id), Arg::Number(v)])   => vec![(id.clone(), *v)],
+        (BLEND, [Arg::Text(name), Arg::Number(v)]) => mapped(name, *v)
+                                                      .into_iter()
+                                                      .collect(),
+        (BONE,  [Arg::Text(bone), rest @ ..])      => {
+            let numbers: Option<Vec<_>> =
+                rest.iter()
+                .map(|a| match a {
+                    Arg::Number(n) => Some(*n),
+                    _              => None,
+                })
+                .collect();
+
+            // Position first, then rotation
+            let rotation = match numbers.as_deref() {
+                Some([_, _, _, x, y, z, w]) => [*x, *y, *z, *w],
+                _                           => return Vec::new(),
+            };
+
+            zip(["x", "y", "z"], euler(rotation))
+            .filter_map(|(axis, a)| mapped(&format!("{bone}.{axis}"), a))
+            .collect()
+        }
+        _                                          => Vec::new(),
+    }
+}
+
+//                   _
+//  _ _    ___ _   _| | ___ _ __
+// (_|_)  / _ \ | | | |/ _ \ '__|
+//  _ _  |  __/ |_| | |  __/ |
+// (_|_)  \___|\__,_|_|\___|_|
+
+// Quaternion to degrees around each axis, the way Unity takes them apart
+fn euler(q: [f32; 4]) -> [f32; 3]
+{
+    let [x, y, z, w] = q;
+
+    let pitch = (2. * (w * x - y * z)).clamp(-1., 1.).asin();
+    let yaw = (2. * (w * y + x * z)).atan2(1. - 2. * (x * x + y * y));
+    let roll = (2. * (w * z + x * y)).atan2(1. - 2. * (x * x + z * z));
+
+    [pitch, yaw, roll].map(f32::to_degrees)
+}
+
+//  _____         _
+// |_   _|__  ___| |_ ___
+//   | |/ _ \/ __| __/ __|
+//   | |  __/\__ \ |_\__ \
+//   |_|\___||___/\__|___/
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        time::Duration,
+        net::UdpSocket,
+        sync::mpsc,
+    };
+    use super::*;
+
+    // Null terminated and padded, as OSC has it
+    fn text(s: &str) -> Vec<u8>
+    {
+        let mut bytes = s.as_bytes().to_vec();
+        bytes.resize(pad(s.len() + 1), 0);
+        bytes
+    }
+
+    fn message(address: &str,
+               tags:    &str,
+               args:    &[&[u8]]) -> Vec<u8>
+    {
+        let mut bytes = text(address);
+        bytes.extend(text(tags));
+        args.iter().for_each(|a| bytes.extend(*a));
+        bytes
+    }
+
+    fn bundle(elements: &[Vec<u8>]) -> Vec<u8>
+    {
+        let mut bytes = b"#bundle\0".to_vec();
+        bytes.extend([0, 0, 0, 0, 0, 0, 0, 1]);
+        for e in elements {
+            bytes.extend((e.len() as u32).to_be_bytes());
+            bytes.extend(e);
+        }
+        bytes
+    }
+
+    // Addresses with their arguments written out, in the order they come
+    fn read(data: &[u8]) -> Option<Vec<String>>
+    {
+        let mut seen = Vec::new();
+        packet(data,
+               &mut |address, args| {
+                   let args: Vec<_> =
+                       args.iter()
+                       .map(|a| match a {
+                           Arg::Number(n) => n.to_string(),
+                           Arg::Text(t)   => t.clone(),
+                           Arg::Other     => "_".to_string(),
+                       })
+                       .collect();
+                   seen.push(format!("{address} {}", args.join(" ")));
+               })?;
+
+        Some(seen)
+    }
+
+    fn map() -> HashMap<String, MapConfig>
+    {
+        let map = |param: &str, scale, offset| MapConfig {
+            param: param.to_string(),
+            scale,
+            offset,
+        };
+
+        HashMap::from([
+            ("A".to_string(),      map("ParamMouthOpenY", 2., 0.1)),
+            ("Head.y".to_string(), map("ParamAngleX",     -1., 0.)),
+        ])
+    }
+
+    // Arguments of a bone message for the head
+    fn bone(numbers: &[f32]) -> Vec<Arg>
+    {
+        std::iter::once(Arg::Text("Head".to_string()))
+        .chain(numbers.iter().map(|n| Arg::Number(*n)))
+        .collect()
+    }
+
+    fn close(a: f32,
+             b: f32) -> bool
+    {
+        (a - b).abs() < 1e-4
+    }
+
+    //                         _        _
+    //  _ _   _ __   __ _  ___| | _____| |_
+    // (_|_) | '_ \ / _` |/ __| |/ / _ \ __|
+    //  _ _  | |_) | (_| | (__|   <  __/ |_
+    // (_|_) | .__/ \__,_|\___|_|\_\___|\__|
+    //       |_|
+
+    #[test]
+    fn packet_messages()
+    {
+        let data = message("/a", ",sfiT", &[&text("x"),
+                                             &1.5f32.to_be_bytes(),
+                                             &(-2i32).to_be_bytes()]);
+        assert_eq!(read(&data), Some(vec!["/a x 1.5 -2 _".to_string()]));
+
+        // Four characters and the terminator take up eight bytes
+        let data = message("/abc", ",s", &[&text("abcd")]);
+        assert_eq!(data.len(), 8 + 4 + 8);
+        assert_eq!(read(&data), Some(vec!["/abc abcd".to_string()]));
+    }
+
+    #[test]
+    fn packet_bundles()
+    {
+        let data = bundle(&[message("/a", ",f", &[&1f32.to_be_bytes()]),
+                            bundle(&[message("/b", ",", &[]),
+                                     message("/c", ",s", &[&text("z")])]),
+                            message("/d", ",", &[])]);
+
+        assert_eq!(read(&data), Some(vec!["/a 1".to_string(),
+                                           "/b ".to_string(),
+                                           "/c z".to_string(),
+                                           "/d ".to_string()]));
+    }
+
+    #[test]
+    fn packet_malformed()
+    {
+        // No terminator, or padding cut short
+        assert_eq!(read(b"/abc"), None);
+        assert_eq!(read(b"/abcd\0\0"), None);
+
+        // Arguments shorter than their tags say
+        let data = message("/a", ",f", &[&[0, 0]]);
+        assert_eq!(read(&data), None);
+        let data = message("/a", ",s", &[]);
+        assert_eq!(read(&data), None);
+
+        // Tags have to start with a comma and be known
+        let data = message("/a", "f", &[&1f32.to_be_bytes()]);
+        assert_eq!(read(&data), None);
+        let data = message("/a", ",x", &[&1f32.to_be_bytes()]);
+        assert_eq!(read(&data), None);
+
+        // Element of a bundle longer than what is left of it
+        let mut data = bundle(&[message("/a", ",", &[])]);
+        data.truncate(data.len() - 1);
+        assert_eq!(read(&data), None);
+    }
+
+    //        _                       _       _
+    //  _ _  | |_ _ __ __ _ _ __  ___| | __ _| |_ ___
+    // (_|_) | __| '__/ _` | '_ \/ __| |/ _` | __/ _ \
+    //  _ _  | |_| | | (_| | | | \__ \ | (_| | ||  __/
+    // (_|_)  \__|_|  \__,_|_| |_|___/_|\__,_|\__\___|
+
+    #[test]
+    fn translate_messages()
+    {
+        let map = map();
+        let number = |n: f32| Arg::Number(n);
+        let name = |t: &str| Arg::Text(t.to_string());
+
+        let values = translate(&map,
+                               PARAM,
+                               &[name("ParamCheek"), number(0.5)]);
+        assert_eq!(values, vec![("ParamCheek".to_string(), 0.5)]);
+
+        let values = translate(&map,
+                               BLEND,
+                               &[name("A"), number(0.5)]);
+        assert_eq!(values.len(), 1);
+        assert_eq!(values[0].0, "ParamMouthOpenY");
+        assert!(close(values[0].1, 1.1));
+
+        // Not in the map, or not what the address takes
+        assert!(translate(&map, BLEND, &[name("E"), number(1.)]).is_empty());
+        assert!(translate(&map, BLEND, &[number(1.), name("A")]).is_empty());
+        assert!(translate(&map, "/other", &[name("A"), number(1.)]).is_empty());
+
+        // Quarter turn around y, only the mapped axis comes out
+        let half = 0.5f32.sqrt();
+        let values = translate(&map,
+                               BONE,
+                               &bone(&[0., 0., 0., 0., half, 0., half]));
+        assert_eq!(values.len(), 1);
+        assert_eq!(values[0].0, "ParamAngleX");
+        assert!(close(values[0].1, -90.));
+
+        // Position without a rotation
+        assert!(translate(&map, BONE, &bone(&[0., 0., 0.])).is_empty());
+    }
+
+    //                   _
+    //  _ _    ___ _   _| | ___ _ __
+    // (_|_)  / _ \ | | | |/ _ \ '__|
+    //  _ _  |  __/ |_| | |  __/ |
+    // (_|_)  \___|\__,_|_|\___|_|
+
+    #[test]
+    fn euler_axes()
+    {
+        // Sixty degrees around each axis, away from where pitch locks
+        let [s, c] = [0.5, 0.75f32.sqrt()];
+        let cases = [([0., 0., 0., 1.],  [0.,  0.,  0.]),
+                     ([s,  0., 0., c],   [60., 0.,  0.]),
+                     ([0., s,  0., c],   [0.,  60., 0.]),
+                     ([0., 0., s,  c],   [0.,  0.,  60.]),
+                     ([0., 0., 0., -1.], [0.,  0.,  0.])];
+
+        for (q, expected) in cases {
+            let angles = euler(q);
+            assert!(zip(angles, expected).all(|(a, e)| close(a, e)),
+                    "{q:?} gave {angles:?}");
+        }
+    }
+
+    //                          _
+    //  _ _   _ __ ___  ___ ___(_)_   _____
+    // (_|_) | '__/ _ \/ __/ _ \ \ \ / / _ \
+    //  _ _  | | |  __/ (_|  __/ |\ V /  __/
+    // (_|_) |_|  \___|\___\___|_| \_/ \___|
+
+    #[test]
+    fn receive_loopback()
+    {
+        let socket = UdpSocket::bind("127.0.0.1:0").expect("bind");
+        let address = socket.local_addr().expect("address");
+
+        let map = map();
+        let (tx, rx) = mpsc::channel();
+        tracking::listen(socket,
+                         DATAGRAM,
+                         move |d| parse(&map, d),
+                         move |v| tx.send(v).unwrap_or(()));
+
+        let data = message(BLEND, ",sf", &[&text("A"),
+                                          &0.25f32.to_be_bytes()]);
+        UdpSocket::bind("127.0.0.1:0").expect("bind")
+        .send_to(&data, address).expect("send");
+
+        let values =
+            rx.recv_timeout(Duration::from_secs(5))
+            .expect("values");
+        assert_eq!(values.len(), 1);
+        assert_eq!(values[0].0, "ParamMouthOpenY");
+        assert!(close(values[0].1, 0.6));
+    }
+}
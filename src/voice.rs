@@ -0,0 +1,205 @@
+use std::{
+    fs::{self, File},
+    thread,
+    error::Error,
+    path::Path,
+    time::{Duration, Instant},
+    os::unix::fs::FileTypeExt,
+    io::{self, Read, ErrorKind},
+};
+use crate::{
+    config::{LipSyncConfig, PcmConfig},
+    lipsync::{analyze, decode, width, window},
+};
+
+// Standard input rather than a file
+const STDIN: &str = "-";
+
+//        _ _     _
+//  _ _  | (_)___| |_ ___ _ __
+// (_|_) | | / __| __/ _ \ '_ \
+//  _ _  | | \__ \ ||  __/ | | |
+// (_|_) |_|_|___/\__\___|_| |_|
+
+// Nothing is read unless a pipe is given. FIFO is opened again for every
+// writer, anything else is read through once
+pub fn listen<F>(config: &LipSyncConfig,
+                 send:   F) -> Result<(), Box<dyn Error>>
+where F: Fn(f32, f32) + Send + 'static
+{
+    let path = match &config.pipe {
+        Some(p) => p.clone(),
+        None    => return Ok(()),
+    };
+    if path != STDIN && !Path::new(&path).exists() {
+        return Err(format!("No lip sync pipe {path}").into())
+    }
+    let pcm = config.pcm.clone();
+
+    thread::spawn(move || loop {
+        let result = match path.as_str() {
+            STDIN => hear(io::stdin().lock(),
+                          &pcm,
+                          &send),
+            p     => File::open(p)
+                     .map_err(Into::into)
+                     .and_then(|f| hear(f,
+                                        &pcm,
+                                        &send)),
+        };
+        result.unwrap_or_else(|e| eprintln!("Failed to read lip sync \
+                                             pipe: {e}"));
+
+        let fifo =
+            fs::metadata(&path)
+            .is_ok_and(|m| m.file_type().is_fifo());
+        if !fifo {break}
+    });
+
+    Ok(())
+}
+
+//        _
+//  _ _  | |__   ___  __ _ _ __
+// (_|_) | '_ \ / _ \/ _` | '__|
+//  _ _  | | | |  __/ (_| | |
+// (_|_) |_| |_|\___|\__,_|_|
+
+// Voice is sent as fast as it would be heard, however fast it is written,
+// and a writer that falls behind is not caught up with in a burst
+fn hear<R, F>(mut reader: R,
+              pcm:        &PcmConfig,
+              send:       &F) -> Result<(), Box<dyn Error>>
+where R: Read,
+      F: Fn(f32, f32)
+{
+    let frames = window(pcm.rate);
+    let mut buffer = vec![0; frames * pcm.channels as usize * width(pcm.format)];
+    let step = Duration::from_secs_f32(frames as f32 / pcm.rate as f32);
+    let mut due = Instant::now();
+
+    loop {
+        match reader.read_exact(&mut buffer) {
+            Ok(())                                        => {}
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(()),
+            Err(e)                                        => {
+                return Err(e.into())
+            }
+        }
+
+        let [level, form] = analyze(&decode(&buffer, pcm.format),
+                                    pcm.channels,
+                                    pcm.rate);
+        send(level, form);
+
+        due += step;
+        let now = Instant::now();
+        match due.checked_duration_since(now) {
+            Some(wait) => thread::sleep(wait),
+            None       => due = now,
+        }
+    }
+}
+
+//  _____         _
+// |_   _|__  ___| |_ ___
+//   | |/ _ \/ __| __/ __|
+//   | |  __/\__ \ |_\__ \
+//   |_|\___||___/\__|___/
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::RefCell,
+        f32::consts::TAU,
+        io::Cursor,
+    };
+    use crate::{config::PcmFormat, lipsync::WINDOW};
+    use super::*;
+
+    const RATE: u32 = 48000;
+
+    // Whole periods of it in a window, halfway between a round vowel and a
+    // wide one
+    const PITCH: f32 = 900.;
+
+    // Silent window, loud one, silent one again, and a bit of a window that
+    // is never sent
+    fn voice(channels: u16) -> Vec<f32>
+    {
+        let w = window(RATE);
+        let sine = |frames: usize| {
+            (0..frames)
+            .map(|i| 0.5 * (TAU * PITCH * i as f32 / RATE as f32).sin())
+            .collect::<Vec<_>>()
+        };
+
+        [vec![0.; w], sine(w), vec![0.; w], sine(w / 2)].concat()
+        .into_iter()
+        .flat_map(|s| vec![s; channels as usize])
+        .collect()
+    }
+
+    fn encode(samples: &[f32],
+              format:  PcmFormat) -> Vec<u8>
+    {
+        samples.iter()
+        .flat_map(|s| match format {
+            PcmFormat::S16le => ((s * 32767.) as i16).to_le_bytes().to_vec(),
+            PcmFormat::F32le => s.to_le_bytes().to_vec(),
+            PcmFormat::U8    => vec![(s * 127. + 128.) as u8],
+        })
+        .collect()
+    }
+
+    fn close(a: f32,
+             b: f32,
+             by: f32) -> bool
+    {
+        (a - b).abs() < by
+    }
+
+    //        _
+    //  _ _  | |__   ___  __ _ _ __
+    // (_|_) | '_ \ / _ \/ _` | '__|
+    //  _ _  | | | |  __/ (_| | |
+    // (_|_) |_| |_|\___|\__,_|_|
+
+    // Every whole window is sent, paced as it would be heard
+    #[test]
+    fn hear_formats()
+    {
+        let cases = [(PcmFormat::S16le, 1),
+                     (PcmFormat::F32le, 2),
+                     (PcmFormat::U8,    1)];
+
+        for (format, channels) in cases {
+            let pcm = PcmConfig {
+                rate: RATE,
+                channels,
+                format,
+            };
+            let bytes = encode(&voice(channels), format);
+            let sent = RefCell::new(Vec::new());
+
+            let start = Instant::now();
+            hear(Cursor::new(bytes),
+                 &pcm,
+                 &|level, form| sent.borrow_mut().push([level, form]))
+            .unwrap();
+            let elapsed = start.elapsed().as_secs_f32();
+
+            let sent = sent.into_inner();
+            assert_eq!(sent.len(), 3, "windows sent at {} bytes a sample",
+                       width(format));
+            assert!(elapsed >= 3. * WINDOW, "sent in {elapsed}s");
+
+            assert_eq!(sent[0], [0., -1.]);
+            assert_eq!(sent[2], [0., -1.]);
+
+            let [level, form] = sent[1];
+            assert!(close(level, 0.5 / 2f32.sqrt(), 1e-2), "level {level}");
+            assert!(close(form, 0., 0.1), "form {form}");
+        }
+    }
+}
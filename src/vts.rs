@@ -0,0 +1,771 @@
+use std::{
+    thread,
+    error::Error,
+    path::Path,
+    collections::HashMap,
+    sync::{mpsc, Arc, Mutex},
+    net::{TcpListener, TcpStream},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tungstenite::{
+    accept_hdr,
+    Message as Frame,
+    Error as WsError,
+    handshake::server::{Request as Handshake, Response, ErrorResponse},
+    http::header::ORIGIN,
+};
+use cubism::id::param;
+use crate::{
+    library::MODEL3,
+    message::{Message, Load, Shelf, Shelved, Reply, Mood, Info, Access},
+    config::{VtsConfig, Tokens, Grant},
+};
+
+const API_NAME:    &str = "VTubeStudioPublicAPI";
+const API_VERSION: &str = "1.0";
+// Version plugins are told, the API subset is as old as that
+const VERSION:     &str = "1.28.0";
+
+// How long a plugin asking for a token waits to be let in
+const APPROVAL: Duration = Duration::from_secs(60);
+
+// Errors the way VTube Studio numbers them
+const INTERNAL:        u32 = 0;
+const NO_TYPE:         u32 = 4;
+const UNKNOWN_TYPE:    u32 = 5;
+const UNAUTHENTICATED: u32 = 8;
+const TOKEN_DENIED:    u32 = 50;
+const NO_MODEL:        u32 = 152;
+const NO_HOTKEY:       u32 = 202;
+const BAD_VALUE:       u32 = 451;
+const UNKNOWN_MODE:    u32 = 455;
+
+type Failure = (u32, String);
+
+//  ____                            _
+// |  _ \ ___  __ _ _   _  ___  ___| |_
+// | |_) / _ \/ _` | | | |/ _ \/ __| __|
+// |  _ <  __/ (_| | |_| |  __/\__ \ |_
+// |_| \_\___|\__, |\__,_|\___||___/\__|
+//               |_|
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Request {
+    #[serde(rename = "requestID", default)]
+    request_id:   String,
+    #[serde(default)]
+    message_type: String,
+    #[serde(default)]
+    data:         Value,
+}
+
+//  ____                _
+// / ___|  ___  ___ ___(_) ___  _ __
+// \___ \ / _ \/ __/ __| |/ _ \| '_ \
+//  ___) |  __/\__ \__ \ | (_) | | | |
+// |____/ \___||___/___/_|\___/|_| |_|
+
+// One plugin, let in once it shows a token
+struct Session<F> {
+    send:          F,
+    tokens:        Arc<Mutex<Tokens>>,
+    gate:          Gate,
+    allow:         Vec<String>,
+    // Page the session is from, if it is a browser
+    origin:        Option<String>,
+    authenticated: bool,
+}
+
+//   ____       _
+//  / ___| __ _| |_ ___
+// | |  _ / _` | __/ _ \
+// | |_| | (_| | ||  __/
+//  \____|\__,_|\__\___|
+
+// Plugins waiting for a token, by name, until bleed lets them in or not
+#[derive(Clone, Default)]
+pub struct Gate {
+    waiting: Arc<Mutex<HashMap<String, mpsc::Sender<bool>>>>,
+}
+
+//  _   _       _   _
+// | | | | ___ | |_| | _____ _   _
+// | |_| |/ _ \| __| |/ / _ \ | | |
+// |  _  | (_) | |_|   <  __/ |_| |
+// |_| |_|\___/ \__|_|\_\___|\__, |
+//                           |___/
+
+// Motions are triggered and expressions toggled, as VTube Studio does
+enum Hotkey {
+    Motion(String, String),
+    Expression(String),
+}
+
+//  _ _   ___  ___ _ ____   _____
+// (_|_) / __|/ _ \ '__\ \ / / _ \
+//  _ _  \__ \  __/ |   \ V /  __/
+// (_|_) |___/\___|_|    \_/ \___|
+
+// Nothing is bound unless asked for, every plugin gets a thread of its own.
+// Gate is where plugins asking for a token are let in
+pub fn serve<F>(config: &VtsConfig,
+                send:   F) -> Result<Gate, Box<dyn Error>>
+where F: Fn(Message) -> Result<(), String> + Clone + Send + 'static
+{
+    let gate = Gate::default();
+    if !config.enabled {return Ok(gate)}
+
+    let listener = TcpListener::bind(&config.address)?;
+    let tokens = Arc::new(Mutex::new(Tokens::load()?));
+    let allow = config.allow.clone();
+    let door = gate.clone();
+
+    thread::spawn(move || {
+        listener.incoming()
+        .for_each(|stream| {
+            let stream = match stream {
+                Ok(s)  => s,
+                Err(e) => {
+                    eprintln!("Plugin connection error: {e}");
+                    return
+                }
+            };
+            let session = Session {
+                send:          send.clone(),
+                tokens:        tokens.clone(),
+                gate:          door.clone(),
+                allow:         allow.clone(),
+                origin:        None,
+                authenticated: false,
+            };
+
+            thread::spawn(move || {
+                session.run(stream)
+                .unwrap_or_else(|e| eprintln!("Plugin session failed: {e}"))
+            });
+        })
+    });
+
+    Ok(gate)
+}
+
+//  ____                _
+// / ___|  ___  ___ ___(_) ___  _ __    _ _
+// \___ \ / _ \/ __/ __| |/ _ \| '_ \  (_|_)
+//  ___) |  __/\__ \__ \ | (_) | | | |  _ _
+// |____/ \___||___/___/_|\___/|_| |_| (_|_)
+
+impl<F> Session<F>
+where F: Fn(Message) -> Result<(), String>
+{
+
+    //  _ _   _ __ _   _ _ __
+    // (_|_) | '__| | | | '_ \
+    //  _ _  | |  | |_| | | | |
+    // (_|_) |_|   \__,_|_| |_|
+
+    // Browsers say where a page is from, which is kept, so that bleed is
+    // asked about every page
+    fn run(mut self,
+           stream: TcpStream) -> Result<(), Box<dyn Error>>
+    {
+        let mut origin = None;
+
+        // Error response is what tungstenite takes
+        #[allow(clippy::result_large_err)]
+        let from = |request: &Handshake,
+                    response: Response| -> Result<Response, ErrorResponse> {
+            origin =
+                request.headers()
+                .get(ORIGIN)
+                .map(|o| String::from_utf8_lossy(o.as_bytes()).into_owned());
+            Ok(response)
+        };
+
+        let mut socket =
+            accept_hdr(stream,
+                       from)
+            .map_err(|e| e.to_string())?;
+        self.origin = origin;
+
+        loop {
+            let text = match socket.read() {
+                Ok(Frame::Text(t))              => t,
+                Ok(_)                           => continue,
+                Err(WsError::ConnectionClosed)  => return Ok(()),
+                Err(e)                          => return Err(e.into()),
+            };
+
+            let answer = self.answer(&text);
+            socket.send(Frame::Text(answer.to_string()))?;
+        }
+    }
+
+    //  _ _    __ _ _ __  _____      _____ _ __
+    // (_|_)  / _` | '_ \/ __\ \ /\ / / _ \ '__|
+    //  _ _  | (_| | | | \__ \\ V  V /  __/ |
+    // (_|_)  \__,_|_| |_|___/ \_/\_/ \___|_|
+
+    fn answer(&mut self,
+              text: &str) -> Value
+    {
+        let (id, result) = match serde_json::from_str::<Request>(text) {
+            Ok(r)  => (r.request_id.clone(), self.handle(&r).map(|d| {
+                let kind = r.message_type.replace("Request", "Response");
+                (kind, d)
+            })),
+            Err(e) => (String::new(), Err((INTERNAL, e.to_string()))),
+        };
+
+        let (kind, data) = match result {
+            Ok(r)             => r,
+            Err((id, reason)) => ("APIError".to_string(),
+                                  json!({"errorID": id, "message": reason})),
+        };
+        let timestamp =
+            SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+
+        json!({
+            "apiName":     API_NAME,
+            "apiVersion":  API_VERSION,
+            "timestamp":   timestamp,
+            "requestID":   id,
+            "messageType": kind,
+            "data":        data,
+        })
+    }
+
+    //        _                     _ _
+    //  _ _  | |__   __ _ _ __   __| | | ___
+    // (_|_) | '_ \ / _` | '_ \ / _` | |/ _ \
+    //  _ _  | | | | (_| | | | | (_| | |  __/
+    // (_|_) |_| |_|\__,_|_| |_|\__,_|_|\___|
+
+    fn handle(&mut self,
+              request: &Request) -> Result<Value, Failure>
+    {
+        let data = &request.data;
+
+        match request.message_type.as_str() {
+            ""                             => Err((NO_TYPE,
+                                                   "No request type".to_string())),
+            "APIStateRequest"              => Ok(json!({
+                "active":                      true,
+                "vTubeStudioVersion":          VERSION,
+                "currentSessionAuthenticated": self.authenticated,
+            })),
+            "AuthenticationTokenRequest"   => self.grant(data),
+            "AuthenticationRequest"        => self.authenticate(data),
+            _ if !self.authenticated       => Err((UNAUTHENTICATED,
+                                                   "Authenticate first"
+                                                   .to_string())),
+            "CurrentModelRequest"          => self.current(),
+            "AvailableModelsRequest"       => self.available(),
+            "ModelLoadRequest"             => self.load(data),
+            "HotkeysInCurrentModelRequest" => self.hotkeys(),
+            "HotkeyTriggerRequest"         => self.trigger(data),
+            "InjectParameterDataRequest"   => self.inject(data),
+            t                              => Err((UNKNOWN_TYPE,
+                                                   format!("Unsupported \
+                                                            request {t}"))),
+        }
+    }
+
+    //                              _
+    //  _ _    __ _ _ __ __ _ _ __ | |_
+    // (_|_)  / _` | '__/ _` | '_ \| __|
+    //  _ _  | (_| | | | (_| | | | | |_
+    // (_|_)  \__, |_|  \__,_|_| |_|\__|
+    //        |___/
+
+    // Plugins in the config are let in, others wait for bleed to let them.
+    // Pages can take any name, so they always wait
+    fn grant(&mut self,
+             data: &Value) -> Result<Value, Failure>
+    {
+        let [plugin, developer] = ["pluginName", "pluginDeveloper"].map(|k| {
+            data[k].as_str().unwrap_or("").to_string()
+        });
+        if plugin.is_empty() || developer.is_empty() {
+            return Err((TOKEN_DENIED, "Plugin has no name or developer"
+                                      .to_string()))
+        }
+
+        let listed = self.origin.is_none() && self.allow.contains(&plugin);
+        if !listed && !self.gate.wait(&plugin,
+                                      &developer,
+                                      self.origin.as_deref()) {
+            return Err((TOKEN_DENIED, "User denied API access".to_string()))
+        }
+
+        let mut rng = fastrand::Rng::new();
+        let token: String =
+            (0..32)
+            .map(|_| format!("{:02x}", rng.u8(..)))
+            .collect();
+
+        let mut tokens = self.tokens.lock().map_err(|e| (INTERNAL,
+                                                        e.to_string()))?;
+        tokens.grants.retain(|g| g.plugin != plugin
+                                 || g.developer != developer);
+        tokens.grants.push(Grant {
+            plugin,
+            developer,
+            token: token.clone(),
+        });
+        tokens.store().map_err(|e| (INTERNAL, e.to_string()))?;
+
+        eprintln!("Plugin {} by {} is let in",
+                  data["pluginName"],
+                  data["pluginDeveloper"]);
+        Ok(json!({"authenticationToken": token}))
+    }
+
+    //                    _   _                _   _           _
+    //  _ _    __ _ _   _| |_| |__   ___ _ __ | |_(_) ___ __ _| |_ ___
+    // (_|_)  / _` | | | | __| '_ \ / _ \ '_ \| __| |/ __/ _` | __/ _ \
+    //  _ _  | (_| | |_| | |_| | | |  __/ | | | |_| | (_| (_| | ||  __/
+    // (_|_)  \__,_|\__,_|\__|_| |_|\___|_| |_|\__|_|\___\__,_|\__\___|
+
+    fn authenticate(&mut self,
+                    data: &Value) -> Result<Value, Failure>
+    {
+        let tokens = self.tokens.lock().map_err(|e| (INTERNAL,
+                                                    e.to_string()))?;
+        self.authenticated =
+            tokens.grants.iter()
+            .any(|g| data["pluginName"] == g.plugin.as_str()
+                     && data["pluginDeveloper"] == g.developer.as_str()
+                     && data["authenticationToken"] == g.token.as_str());
+
+        let reason = match self.authenticated {
+            true  => "Token is valid",
+            false => "Token is not valid for this plugin",
+        };
+
+        Ok(json!({
+            "authenticated": self.authenticated,
+            "reason":        reason,
+        }))
+    }
+
+    //                                       _
+    //  _ _    ___ _   _ _ __ _ __ ___ _ __ | |_
+    // (_|_)  / __| | | | '__| '__/ _ \ '_ \| __|
+    //  _ _  | (__| |_| | |  | | |  __/ | | | |_
+    // (_|_)  \___|\__,_|_|  |_|  \___|_| |_|\__|
+
+    fn current(&self) -> Result<Value, Failure>
+    {
+        let info = self.info()?;
+        let file = name(&info.file, "");
+
+        Ok(json!({
+            "modelLoaded":              true,
+            "modelName":                name(&info.file, MODEL3),
+            "modelID":                  info.file.display().to_string(),
+            "vtsModelName":             file,
+            "vtsModelIconName":         "",
+            "live2DModelName":          file,
+            "modelLoadTime":            0,
+            "timeSinceModelLoaded":     0,
+            "numberOfLive2DParameters": info.parameters,
+            "numberOfLive2DArtmeshes":  info.drawables,
+            "hasPhysicsFile":           false,
+            "numberOfTextures":         info.textures,
+            "textureResolution":        0,
+            "modelPosition":            {
+                "positionX": 0,
+                "positionY": 0,
+                "rotation":  0,
+                "size":      0,
+            },
+        }))
+    }
+
+    //                         _ _       _     _
+    //  _ _    __ ___   ____ _(_) | __ _| |__ | | ___
+    // (_|_)  / _` \ \ / / _` | | |/ _` | '_ \| |/ _ \
+    //  _ _  | (_| |\ V / (_| | | | (_| | |_) | |  __/
+    // (_|_)  \__,_| \_/ \__,_|_|_|\__,_|_.__/|_|\___|
+
+    // Library is what there is to load
+    fn available(&self) -> Result<Value, Failure>
+    {
+        let models: Vec<_> =
+            self.library()?
+            .iter()
+            .map(|m| json!({
+                "modelLoaded":      m.loaded,
+                "modelName":        m.name,
+                "modelID":          m.file.display().to_string(),
+                "vtsModelName":     self::name(&m.file, ""),
+                "vtsModelIconName": "",
+            }))
+            .collect();
+
+        Ok(json!({
+            "numberOfModels":  models.len(),
+            "availableModels": models,
+        }))
+    }
+
+    //        _                 _
+    //  _ _  | | ___   __ _  __| |
+    // (_|_) | |/ _ \ / _` |/ _` |
+    //  _ _  | | (_) | (_| | (_| |
+    // (_|_) |_|\___/ \__,_|\__,_|
+
+    // Only models of the library, ids are not taken for just any file
+    fn load(&self,
+            data: &Value) -> Result<Value, Failure>
+    {
+        let id =
+            data["modelID"].as_str()
+            .ok_or((NO_MODEL, "No model id".to_string()))?;
+
+        let known =
+            self.library()?
+            .iter()
+            .any(|m| m.file.as_os_str() == id);
+        if !known {
+            return Err((NO_MODEL, format!("No model {id} in the library")))
+        }
+
+        self.ask(|reply| Message::Load(Load {
+            file:  id.into(),
+            open:  Vec::new(),
+            reply,
+        }))
+        .map_err(|(_, e)| (NO_MODEL, e))?;
+
+        Ok(json!({"modelID": id}))
+    }
+
+    //        _           _   _
+    //  _ _  | |__   ___ | |_| | _____ _   _ ___
+    // (_|_) | '_ \ / _ \| __| |/ / _ \ | | / __|
+    //  _ _  | | | | (_) | |_|   <  __/ |_| \__ \
+    // (_|_) |_| |_|\___/ \__|_|\_\___|\__, |___/
+    //                                 |___/
+
+    fn hotkeys(&self) -> Result<Value, Failure>
+    {
+        let info = self.info()?;
+
+        let hotkeys: Vec<_> =
+            Hotkey::all(&info)
+            .map(|h| {
+                let (kind, file) = match &h {
+                    Hotkey::Motion(_, m)  => ("TriggerAnimation", m.as_str()),
+                    Hotkey::Expression(e) => ("ToggleExpression", e.as_str()),
+                };
+
+                json!({
+                    "name":             h.name(),
+                    "type":             kind,
+                    "description":      kind,
+                    "file":             file,
+                    "hotkeyID":         h.id(),
+                    "keyCombination":   [],
+                    "onScreenButtonID": -1,
+                })
+            })
+            .collect();
+
+        Ok(json!({
+            "modelLoaded":      true,
+            "modelName":        name(&info.file, MODEL3),
+            "modelID":          info.file.display().to_string(),
+            "availableHotkeys": hotkeys,
+        }))
+    }
+
+    //        _        _
+    //  _ _  | |_ _ __(_) __ _  __ _  ___ _ __
+    // (_|_) | __| '__| |/ _` |/ _` |/ _ \ '__|
+    //  _ _  | |_| |  | | (_| | (_| |  __/ |
+    // (_|_)  \__|_|  |_|\__, |\__, |\___|_|
+    //                   |___/ |___/
+
+    // Hotkeys are found by their names as well
+    fn trigger(&self,
+               data: &Value) -> Result<Value, Failure>
+    {
+        let id = data["hotkeyID"].as_str().unwrap_or("");
+        let info = self.info()?;
+
+        let hotkey =
+            Hotkey::all(&info)
+            .find(|h| h.id() == id || h.name() == id)
+            .ok_or((NO_HOTKEY, format!("No hotkey {id}")))?;
+
+        match &hotkey {
+            Hotkey::Motion(c, m)  => {
+                (self.send)(Message::SetMotion((c.clone(),
+                                                m.clone())))
+                .map_err(|e| (INTERNAL, e))?
+            }
+            Hotkey::Expression(e) => {
+                self.ask(|r| Message::Expression(Mood::Toggle(e.clone()),
+                                                 r))?;
+            }
+        }
+
+        Ok(json!({"hotkeyID": hotkey.id()}))
+    }
+
+    //        _        _           _
+    //  _ _  (_)_ __  (_) ___  ___| |_
+    // (_|_) | | '_ \ | |/ _ \/ __| __|
+    //  _ _  | | | | || |  __/ (__| |_
+    // (_|_) |_|_| |_|/ |\___|\___|\__|
+    //              |__/
+
+    // Injected values are tracked ones, so plugins have to keep sending them
+    // same as with VTube Studio
+    fn inject(&self,
+              data: &Value) -> Result<Value, Failure>
+    {
+        match data["mode"].as_str() {
+            None | Some("set") => {}
+            Some(m)            => return Err((UNKNOWN_MODE,
+                                              format!("Only set mode is \
+                                                       supported, not {m}"))),
+        }
+
+        let values =
+            data["parameterValues"].as_array()
+            .ok_or((BAD_VALUE, "No parameter values".to_string()))?
+            .iter()
+            .map(|p| {
+                let id = p["id"].as_str()?;
+                let value = p["value"].as_f64()?;
+                Some((live2d(id).to_string(), value as f32))
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or((BAD_VALUE, "Parameter without id or value".to_string()))?;
+
+        (self.send)(Message::Track(values))
+        .map_err(|e| (INTERNAL, e))?;
+
+        Ok(json!({}))
+    }
+
+    //        _        __
+    //  _ _  (_)_ __  / _| ___
+    // (_|_) | | '_ \| |_ / _ \
+    //  _ _  | | | | |  _| (_) |
+    // (_|_) |_|_| |_|_|  \___/
+
+    fn info(&self) -> Result<Info, Failure>
+    {
+        let info = self.ask(Message::Info)?;
+        serde_json::from_str(&info).map_err(|e| (INTERNAL, e.to_string()))
+    }
+
+    //        _ _ _
+    //  _ _  | (_) |__  _ __ __ _ _ __ _   _
+    // (_|_) | | | '_ \| '__/ _` | '__| | | |
+    //  _ _  | | | |_) | | | (_| | |  | |_| |
+    // (_|_) |_|_|_.__/|_|  \__,_|_|   \__, |
+    //                                 |___/
+
+    fn library(&self) -> Result<Vec<Shelved>, Failure>
+    {
+        let models = self.ask(|r| Message::Library(Shelf::Models, r))?;
+        serde_json::from_str(&models).map_err(|e| (INTERNAL, e.to_string()))
+    }
+
+    //                  _
+    //  _ _    __ _ ___| | __
+    // (_|_)  / _` / __| |/ /
+    //  _ _  | (_| \__ \   <
+    // (_|_)  \__,_|___/_|\_\
+
+    // Liver answers from its own loop, the plugin waits for it
+    fn ask<M>(&self,
+              message: M) -> Result<String, Failure>
+    where M: FnOnce(Reply) -> Message
+    {
+        let (tx, rx) = mpsc::channel();
+        (self.send)(message(Reply::new(tx))).map_err(|e| (INTERNAL, e))?;
+
+        rx.recv()
+        .map_err(|e| (INTERNAL, e.to_string()))?
+        .map_err(|e| (INTERNAL, e))
+    }
+}
+
+//   ____       _
+//  / ___| __ _| |_ ___   _ _
+// | |  _ / _` | __/ _ \ (_|_)
+// | |_| | (_| | ||  __/  _ _
+//  \____|\__,_|\__\___| (_|_)
+
+impl Gate {
+
+    //                      _ _
+    //  _ _  __      ____ _(_) |_
+    // (_|_) \ \ /\ / / _` | | __|
+    //  _ _   \ V  V / (_| | | |_
+    // (_|_)   \_/\_/ \__,_|_|\__|
+
+    // Whether the plugin is let in, it is not if nobody says in time
+    fn wait(&self,
+            plugin:    &str,
+            developer: &str,
+            origin:    Option<&str>) -> bool
+    {
+        let (tx, rx) = mpsc::channel();
+        match self.waiting.lock() {
+            Ok(mut w) => {w.insert(plugin.to_string(), tx);}
+            Err(_)    => return false,
+        }
+
+        let page = match origin {
+            Some(o) => format!(" from page {o}"),
+            None    => String::new(),
+        };
+        eprintln!("Plugin {plugin} by {developer}{page} asks to be let in, \
+                   `bleed vts allow {plugin}` or `bleed vts deny {plugin}`");
+        let allowed = rx.recv_timeout(APPROVAL).unwrap_or(false);
+
+        if let Ok(mut w) = self.waiting.lock() {
+            w.remove(plugin);
+        }
+        allowed
+    }
+
+    //  _ _    __ _ _ __  _____      _____ _ __
+    // (_|_)  / _` | '_ \/ __\ \ /\ / / _ \ '__|
+    //  _ _  | (_| | | | \__ \\ V  V /  __/ |
+    // (_|_)  \__,_|_| |_|___/ \_/\_/ \___|_|
+
+    pub fn answer(&self,
+                  access: &Access) -> Result<String, String>
+    {
+        let mut waiting = self.waiting.lock().map_err(|e| e.to_string())?;
+
+        let (plugin, allowed) = match access {
+            Access::List     => {
+                let mut names: Vec<_> = waiting.keys().cloned().collect();
+                names.sort();
+                return Ok(names.iter().map(|n| format!("{n}\n")).collect())
+            }
+            Access::Allow(p) => (p, true),
+            Access::Deny(p)  => (p, false),
+        };
+
+        waiting
+        .remove(plugin)
+        .ok_or(format!("No plugin {plugin} is waiting"))?
+        .send(allowed)
+        .map_err(|e| e.to_string())?;
+
+        Ok(String::new())
+    }
+}
+
+//  _   _       _   _
+// | | | | ___ | |_| | _____ _   _   _ _
+// | |_| |/ _ \| __| |/ / _ \ | | | (_|_)
+// |  _  | (_) | |_|   <  __/ |_| |  _ _
+// |_| |_|\___/ \__|_|\_\___|\__, | (_|_)
+//                           |___/
+
+impl Hotkey {
+
+    //              _ _
+    //  _ _    __ _| | |
+    // (_|_)  / _` | | |
+    //  _ _  | (_| | | |
+    // (_|_)  \__,_|_|_|
+
+    fn all(info: &Info) -> impl Iterator<Item = Hotkey> + '_
+    {
+        info.motions.iter()
+        .map(|(c, m)| Hotkey::Motion(c.clone(), m.clone()))
+        .chain(info.expressions.iter()
+               .map(|e| Hotkey::Expression(e.clone())))
+    }
+
+    //        _     _
+    //  _ _  (_) __| |
+    // (_|_) | |/ _` |
+    //  _ _  | | (_| |
+    // (_|_) |_|\__,_|
+
+    fn id(&self) -> String
+    {
+        match self {
+            Hotkey::Motion(c, m)  => format!("motion:{c}:{m}"),
+            Hotkey::Expression(e) => format!("expression:{e}"),
+        }
+    }
+
+    //  _ _   _ __   __ _ _ __ ___   ___
+    // (_|_) | '_ \ / _` | '_ ` _ \ / _ \
+    //  _ _  | | | | (_| | | | | | |  __/
+    // (_|_) |_| |_|\__,_|_| |_| |_|\___|
+
+    fn name(&self) -> String
+    {
+        match self {
+            Hotkey::Motion(c, m) if c.is_empty() => m.clone(),
+            Hotkey::Motion(c, m)                 => format!("{c}/{m}"),
+            Hotkey::Expression(e)                => e.clone(),
+        }
+    }
+}
+
+//  _ _   _ __   __ _ _ __ ___   ___
+// (_|_) | '_ \ / _` | '_ ` _ \ / _ \
+//  _ _  | | | | (_| | | | | | |  __/
+// (_|_) |_| |_|\__,_|_| |_| |_|\___|
+
+// File name of a model, without the suffix if it has one
+fn name(file:   &Path,
+        suffix: &str) -> String
+{
+    let name =
+        file.file_name()
+        .and_then(|f| f.to_str())
+        .unwrap_or("");
+
+    name.strip_suffix(suffix)
+    .unwrap_or(name)
+    .to_string()
+}
+
+//        _ _           ____     _
+//  _ _  | (_)_   _____|___ \ __| |
+// (_|_) | | \ \ / / _ \ __) / _` |
+//  _ _  | | |\ V /  __// __/ (_| |
+// (_|_) |_|_| \_/ \___|_____\__,_|
+
+// Tracking parameters of VTube Studio to the standard ones of Live2D, anything
+// else is taken for a parameter of the model
+fn live2d(id: &str) -> &str
+{
+    match id {
+        "FaceAngleX"   => param::ANGLE_X,
+        "FaceAngleY"   => param::ANGLE_Y,
+        "FaceAngleZ"   => param::ANGLE_Z,
+        "EyeOpenLeft"  => param::EYE_L_OPEN,
+        "EyeOpenRight" => param::EYE_R_OPEN,
+        "EyeLeftX"     => param::EYE_BALL_X,
+        "EyeLeftY"     => param::EYE_BALL_Y,
+        "BrowLeftY"    => param::BROW_LY,
+        "BrowRightY"   => param::BROW_RY,
+        "MouthOpen"    => param::MOUTH_OPEN_Y,
+        "MouthSmile"   => param::MOUTH_FORM,
+        id             => id,
+    }
+}
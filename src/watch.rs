@@ -0,0 +1,79 @@
+use std::{
+    thread,
+    error::Error,
+    sync::mpsc,
+    time::Duration,
+    path::{Path, PathBuf},
+    collections::HashSet,
+};
+use notify::{
+    Event,
+    Watcher,
+    EventKind,
+    RecursiveMode,
+    RecommendedWatcher,
+};
+
+// Editors write files in several steps, so changes are collected for a bit
+// before reporting them, not to read the same file half-written
+const SETTLE: Duration = Duration::from_millis(200);
+
+//                      _       _
+//  _ _  __      ____ _| |_ ___| |__
+// (_|_) \ \ /\ / / _` | __/ __| '_ \
+//  _ _   \ V  V / (_| | || (__| | | |
+// (_|_)   \_/\_/ \__,_|\__\___|_| |_|
+
+// Watching stops when the returned watcher is dropped
+pub fn watch<'a, I, F>(files: I,
+                       send:  F) -> Result<RecommendedWatcher, Box<dyn Error>>
+where I: IntoIterator<Item = &'a Path>,
+      F: Fn(PathBuf) + Send + 'static
+{
+    let files: HashSet<PathBuf> =
+        files.into_iter()
+        .map(Path::to_path_buf)
+        .collect();
+
+    // Files are often replaced rather than written to, so it is their
+    // directories that are watched
+    let dirs: HashSet<PathBuf> =
+        files.iter()
+        .filter_map(|f| f.parent())
+        .map(Path::to_path_buf)
+        .collect();
+
+    let (tx, rx) = mpsc::channel();
+
+    let mut watcher =
+        notify::recommended_watcher(move |event: notify::Result<Event>| {
+            match event {
+                Ok(e) => match e.kind {
+                    EventKind::Create(_) |
+                    EventKind::Modify(_) => e.paths.into_iter()
+                                            .filter(|p| files.contains(p))
+                                            .for_each(|p| tx.send(p)
+                                                          .unwrap_or(())),
+                    _ => {}
+                }
+                Err(e) => eprintln!("Failed to watch files: {e}"),
+            }
+        })?;
+
+    for dir in dirs {
+        watcher.watch(&dir,
+                      RecursiveMode::NonRecursive)?;
+    }
+
+    thread::spawn(move || {
+        while let Ok(first) = rx.recv() {
+            thread::sleep(SETTLE);
+
+            let mut changed = HashSet::from([first]);
+            changed.extend(rx.try_iter());
+            changed.into_iter().for_each(&send);
+        }
+    });
+
+    Ok(watcher)
+}
@@ -0,0 +1,273 @@
+use std::{
+    env,
+    fs,
+    process::Command,
+    path::Path,
+};
+use image::RgbaImage;
+
+// Rendering is compared against reference frames from tests/golden, made
+// with Mesa llvmpipe from the models in tests/fixtures. Fixtures can not be
+// redistributed, so the test only runs when asked for,
+//
+// cargo test --test golden -- --ignored
+//
+// skipping cases without their model. A case without its reference fails,
+// LIVER_BLESS=1 in front writes current frames as new references.
+
+// Per channel difference that still counts as the same
+const TOLERANCE: u8 = 2;
+// Share of pixels allowed to differ more than that
+const MAX_DIFFERENT: f64 = 0.001;
+
+// Voice is a tone, loud and as raw PCM as liver takes it by default
+const VOICE_RATE:  u32 = 16000;
+const VOICE_PITCH: f32 = 220.;
+
+//   ____
+//  / ___|__ _ ___  ___  ___
+// | |   / _` / __|/ _ \/ __|
+// | |__| (_| \__ \  __/\__ \
+//  \____\__,_|___/\___||___/
+
+struct Case {
+    name:   &'static str,
+    model:  &'static str,
+    motion: Option<&'static str>,
+    time:   f64,
+    fit:    &'static str,
+    // Mouth moves to the voice from voice()
+    speak:  bool,
+}
+
+const CASES: &[Case] = &[
+    Case {
+        name:   "mask",
+        model:  "mask/mask.model3.json",
+        motion: None,
+        time:   0.,
+        fit:    "contain",
+        speak:  false,
+    },
+    Case {
+        name:   "mask_inverted",
+        model:  "mask_inverted/mask_inverted.model3.json",
+        motion: None,
+        time:   0.,
+        fit:    "contain",
+        speak:  false,
+    },
+    Case {
+        name:   "blend_add",
+        model:  "blend/blend.model3.json",
+        motion: Some("Blend:Add"),
+        time:   1.,
+        fit:    "contain",
+        speak:  false,
+    },
+    Case {
+        name:   "blend_mult",
+        model:  "blend/blend.model3.json",
+        motion: Some("Blend:Mult"),
+        time:   1.,
+        fit:    "contain",
+        speak:  false,
+    },
+    Case {
+        name:   "lipsync",
+        model:  "lipsync/lipsync.model3.json",
+        motion: Some("Speak:Loud"),
+        time:   0.5,
+        fit:    "contain",
+        speak:  false,
+    },
+    Case {
+        name:   "speak",
+        model:  "lipsync/lipsync.model3.json",
+        motion: None,
+        time:   0.5,
+        fit:    "contain",
+        speak:  true,
+    },
+    Case {
+        name:   "fit_contain",
+        model:  "wide/wide.model3.json",
+        motion: None,
+        time:   0.,
+        fit:    "contain",
+        speak:  false,
+    },
+    Case {
+        name:   "fit_cover",
+        model:  "wide/wide.model3.json",
+        motion: None,
+        time:   0.,
+        fit:    "cover",
+        speak:  false,
+    },
+];
+
+//  _____         _
+// |_   _|__  ___| |_
+//   | |/ _ \/ __| __|
+//   | |  __/\__ \ |_
+//   |_|\___||___/\__|
+
+#[test]
+#[ignore = "needs models in tests/fixtures"]
+fn golden()
+{
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+    let out = Path::new(env!("CARGO_TARGET_TMPDIR")).join("golden");
+    fs::create_dir_all(&out).expect("output directory");
+
+    let bless = env::var_os("LIVER_BLESS").is_some();
+    voice(&out.join("voice.pcm"));
+
+    let failed: Vec<String> =
+        CASES.iter()
+        .filter_map(|case| {
+            let model = root.join("fixtures").join(case.model);
+            if !model.exists() {
+                eprintln!("Skipping {}, no fixture {}",
+                          case.name,
+                          model.display());
+                return None
+            }
+
+            let actual = out.join(format!("{}.png", case.name));
+            let reference = root.join("golden").join(format!("{}.png",
+                                                             case.name));
+
+            if let Err(e) = render(case, &model, &actual, &out) {
+                return Some(format!("{}: {e}", case.name))
+            }
+
+            if bless {
+                eprintln!("Writing reference for {}", case.name);
+                fs::create_dir_all(reference.parent().expect("golden"))
+                .and_then(|_| fs::copy(&actual, &reference))
+                .expect("reference to be written");
+                return None
+            }
+
+            if !reference.exists() {
+                return Some(format!("{}: no reference {}",
+                                    case.name,
+                                    reference.display()))
+            }
+
+            compare(&actual, &reference)
+            .err()
+            .map(|e| format!("{}: {e}, frame is at {}",
+                             case.name,
+                             actual.display()))
+        })
+        .collect();
+
+    assert!(failed.is_empty(), "\n{}", failed.join("\n"));
+}
+
+//                           _
+//  _ _   _ __ ___ _ __   __| | ___ _ __
+// (_|_) | '__/ _ \ '_ \ / _` |/ _ \ '__|
+//  _ _  | | |  __/ | | | (_| |  __/ |
+// (_|_) |_|  \___|_| |_|\__,_|\___|_|
+
+fn render(case:  &Case,
+          model: &Path,
+          frame: &Path,
+          home:  &Path) -> Result<(), String>
+{
+    let mut command = Command::new(env!("CARGO_BIN_EXE_liver"));
+    command
+    // Same software rasterizer on every machine, and no user config
+    .env("LIBGL_ALWAYS_SOFTWARE", "1")
+    .env("GALLIUM_DRIVER", "llvmpipe")
+    .env("XDG_CONFIG_HOME", home)
+    .arg("render")
+    .arg(model)
+    .args(["-size", "400x300"])
+    .args(["-fit", case.fit])
+    .args(["-bg-variant", "color"])
+    .args(["-time", &case.time.to_string()])
+    .arg("-o")
+    .arg(frame);
+
+    if let Some(motion) = case.motion {
+        command.args(["-motion", motion]);
+    }
+    if case.speak {
+        command.arg("-speak").arg(home.join("voice.pcm"));
+    }
+
+    let output =
+        command.output()
+        .map_err(|e| format!("Failed to run liver: {e}"))?;
+
+    match output.status.success() {
+        true  => Ok(()),
+        false => Err(String::from_utf8_lossy(&output.stderr).into_owned()),
+    }
+}
+
+//                   _
+//  _ _  __   _____ (_) ___ ___
+// (_|_) \ \ / / _ \| |/ __/ _ \
+//  _ _   \ V / (_) | | (_|  __/
+// (_|_)   \_/ \___/|_|\___\___|
+
+// Second of a tone, 16 bit mono
+fn voice(path: &Path)
+{
+    let bytes: Vec<u8> =
+        (0..VOICE_RATE)
+        .map(|i| {
+            let t = i as f32 / VOICE_RATE as f32;
+            let s = (t * VOICE_PITCH * std::f32::consts::TAU).sin() * 0.5;
+            (s * i16::MAX as f32) as i16
+        })
+        .flat_map(i16::to_le_bytes)
+        .collect();
+
+    fs::write(path, bytes).expect("voice to be written");
+}
+
+//  _ _    ___ ___  _ __ ___  _ __   __ _ _ __ ___
+// (_|_)  / __/ _ \| '_ ` _ \| '_ \ / _` | '__/ _ \
+//  _ _  | (_| (_) | | | | | | |_) | (_| | | |  __/
+// (_|_)  \___\___/|_| |_| |_| .__/ \__,_|_|  \___|
+//                           |_|
+
+fn compare(actual:    &Path,
+           reference: &Path) -> Result<(), String>
+{
+    let open = |p: &Path| -> Result<RgbaImage, String> {
+        image::open(p)
+        .map(|i| i.into_rgba8())
+        .map_err(|e| format!("Failed to open {}: {e}", p.display()))
+    };
+
+    let actual = open(actual)?;
+    let reference = open(reference)?;
+
+    if actual.dimensions() != reference.dimensions() {
+        return Err(format!("size is {:?}, expected {:?}",
+                           actual.dimensions(),
+                           reference.dimensions()))
+    }
+
+    let different =
+        actual.pixels()
+        .zip(reference.pixels())
+        .filter(|(a, r)| a.0.iter()
+                         .zip(r.0.iter())
+                         .any(|(a, r)| a.abs_diff(*r) > TOLERANCE))
+        .count();
+
+    let share = different as f64 / actual.pixels().len() as f64;
+    match share > MAX_DIFFERENT {
+        true  => Err(format!("{:.2}% of pixels differ", share * 100.)),
+        false => Ok(()),
+    }
+}